@@ -0,0 +1,176 @@
+//! Cache of upstream responses, by question and client subnet.
+//!
+//! Responses tailored to a client subnet are only served to clients of the
+//! same network, of the response scope prefix (RFC 7871 section 7.3).
+
+use std::collections::HashMap;
+
+use crate::message::{ClientSubnet, Message, QuestionSection, ResourceRecordType, ResponseCode};
+
+/// Default maximum number of cached responses.
+pub const DEFAULT_CAPACITY: usize = 10_000;
+
+#[derive(Debug, Clone)]
+pub struct Cache {
+    entries: HashMap<QuestionSection, Vec<Entry>>,
+    /// Number of cached responses, for all questions.
+    len: usize,
+    capacity: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    /// Network the response is valid for (scope as source prefix), `None` for any client.
+    network: Option<ClientSubnet>,
+    /// Insertion and expiration times, in seconds since epoch.
+    inserted: u64,
+    expiration: u64,
+    response: Message,
+}
+
+impl Cache {
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// Cache holding at most `capacity` responses.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            len: 0,
+            capacity,
+        }
+    }
+
+    /// Number of cached responses, including expired ones not evicted yet.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Cached response to `question`, for a query sent with `subnet`.
+    ///
+    /// The most specific response is picked if several match, with TTLs
+    /// decreased by the time spent in the cache (RFC 1035 section 7.4).
+    /// Expired responses to `question` are evicted.
+    pub fn get(
+        &mut self,
+        question: &QuestionSection,
+        subnet: Option<&ClientSubnet>,
+        now: u64,
+    ) -> Option<Message> {
+        self.retain(question, |entry| entry.expiration > now);
+        self.entries
+            .get(question)?
+            .iter()
+            .filter(|entry| match (&entry.network, subnet) {
+                (None, _) => true,
+                (Some(network), Some(subnet)) => {
+                    let prefix = network.source_prefix();
+                    prefix <= subnet.source_prefix()
+                        && ClientSubnet::new(subnet.address(), prefix) == *network
+                }
+                (Some(_), None) => false,
+            })
+            .max_by_key(|entry| entry.network.map_or(0, |x| x.source_prefix()))
+            .map(|entry| {
+                let age = now.saturating_sub(entry.inserted) as u32;
+                let mut response = entry.response.clone();
+                for record in response
+                    .answers
+                    .iter_mut()
+                    .chain(&mut response.authorities)
+                    .chain(&mut response.additionals)
+                    .filter(|x| x.rr_type != ResourceRecordType::OPT)
+                {
+                    record.ttl = record.ttl.saturating_sub(age);
+                }
+                response
+            })
+    }
+
+    /// Cache a response to `question`, until its first answer expires.
+    ///
+    /// The client subnet option of the response, if any, limits which
+    /// clients it is served to. Only successful responses with answers are cached.
+    ///
+    /// When the cache is full, expired responses are evicted, then those
+    /// expiring first.
+    pub fn insert(&mut self, question: QuestionSection, response: Message, now: u64) {
+        if response.response_code() != ResponseCode::NoError || self.capacity == 0 {
+            return;
+        }
+        let Some(ttl) = response.answers.iter().map(|x| x.ttl).min() else {
+            return;
+        };
+
+        // Scope is at most the source prefix (RFC 7871 section 7.3.1).
+        let network = response
+            .edns()
+            .and_then(|edns| edns.client_subnet())
+            .map(|x| ClientSubnet::new(x.address(), x.scope_prefix().min(x.source_prefix())))
+            .filter(|x| x.source_prefix() > 0);
+
+        self.retain(&question, |entry| {
+            entry.expiration > now && entry.network != network
+        });
+        if self.len >= self.capacity {
+            self.purge(now);
+        }
+        if self.len >= self.capacity {
+            self.evict();
+        }
+
+        self.entries.entry(question).or_default().push(Entry {
+            network,
+            inserted: now,
+            expiration: now + ttl as u64,
+            response,
+        });
+        self.len += 1;
+    }
+
+    /// Evict all expired responses.
+    pub fn purge(&mut self, now: u64) {
+        self.entries.retain(|_, entries| {
+            entries.retain(|entry| entry.expiration > now);
+            !entries.is_empty()
+        });
+        self.len = self.entries.values().map(Vec::len).sum();
+    }
+
+    /// Evict the responses expiring first.
+    fn evict(&mut self) {
+        let first = self
+            .entries
+            .iter()
+            .flat_map(|(question, entries)| entries.iter().map(move |x| (x.expiration, question)))
+            .min_by_key(|(expiration, _)| *expiration)
+            .map(|(expiration, question)| (expiration, question.clone()));
+        if let Some((expiration, question)) = first {
+            self.retain(&question, |entry| entry.expiration != expiration);
+        }
+    }
+
+    /// Keep the responses to `question` matching `f`.
+    fn retain(&mut self, question: &QuestionSection, f: impl FnMut(&Entry) -> bool) {
+        let Some(entries) = self.entries.get_mut(question) else {
+            return;
+        };
+        self.len -= entries.len();
+        entries.retain(f);
+        self.len += entries.len();
+        if entries.is_empty() {
+            self.entries.remove(question);
+        }
+    }
+}
+
+impl Default for Cache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,19 +1,47 @@
 use std::{
+    collections::HashMap,
     io,
-    net::{ToSocketAddrs, UdpSocket},
+    net::{IpAddr, SocketAddr, ToSocketAddrs, UdpSocket},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use rand::prelude::*;
 
-use crate::{message::*, DnsError};
+use crate::{
+    dnssec::{TrustAnchor, ValidationStatus, Validator},
+    message::*,
+    tsig::{self, TsigKey},
+    DnsError,
+};
 
-// const MAX_DATAGRAM_SIZE: usize = 65_507;
-const MAX_DATAGRAM_SIZE: usize = 512;
+/// Default EDNS buffer size advertised to the server (DNS flag day 2020).
+pub const DEFAULT_UDP_PAYLOAD_SIZE: u16 = 1232;
 
 #[derive(Debug)]
 pub struct DnsClient {
     socket: UdpSocket,
     rng: ThreadRng,
+    /// EDNS buffer size advertised in queries, `None` to disable EDNS.
+    udp_payload_size: Option<u16>,
+    /// DNSSEC trust anchor, `None` to disable validation.
+    trust_anchor: Option<TrustAnchor>,
+    /// Key to sign queries with, `None` to send unsigned queries.
+    tsig_key: Option<TsigKey>,
+    /// Client subnet sent in EDNS queries, `None` to not send one.
+    client_subnet: Option<ClientSubnet>,
+    /// DNS cookie sent in EDNS queries, `None` to disable cookies.
+    client_cookie: Option<[u8; 8]>,
+    /// Last server cookie received from each upstream.
+    server_cookies: HashMap<SocketAddr, Vec<u8>>,
+    /// Extended DNS errors of the last response.
+    extended_errors: Vec<ExtendedError>,
+}
+
+/// Response with its DNSSEC validation status.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ValidatedResponse {
+    pub response: Message,
+    pub status: ValidationStatus,
 }
 
 impl DnsClient {
@@ -24,34 +52,226 @@ impl DnsClient {
         let socket = UdpSocket::bind(local_addr)?;
         socket.connect(remote_addr)?;
 
-        let rng = rand::thread_rng();
+        let mut rng = rand::thread_rng();
+        let client_cookie = Some(rng.gen());
+
+        Ok(Self {
+            socket,
+            rng,
+            udp_payload_size: Some(DEFAULT_UDP_PAYLOAD_SIZE),
+            trust_anchor: None,
+            tsig_key: None,
+            client_subnet: None,
+            client_cookie,
+            server_cookies: HashMap::new(),
+            extended_errors: vec![],
+        })
+    }
+
+    pub fn timeout(&self) -> io::Result<Option<Duration>> {
+        self.socket.read_timeout()
+    }
+
+    /// Set how long to wait for a response, `None` to wait forever.
+    ///
+    /// Queries then fail with `DnsError::Timeout`.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+        self.socket.set_read_timeout(timeout)
+    }
+
+    pub fn udp_payload_size(&self) -> Option<u16> {
+        self.udp_payload_size
+    }
+
+    /// Set EDNS buffer size advertised in queries, `None` to send plain DNS queries.
+    pub fn set_udp_payload_size(&mut self, size: Option<u16>) {
+        self.udp_payload_size = size.map(|x| x.max(MIN_UDP_PAYLOAD_SIZE));
+    }
+
+    pub fn trust_anchor(&self) -> Option<&TrustAnchor> {
+        self.trust_anchor.as_ref()
+    }
+
+    /// Set DNSSEC trust anchor: queries then request DNSSEC records (DO bit).
+    pub fn set_trust_anchor(&mut self, anchor: Option<TrustAnchor>) {
+        self.trust_anchor = anchor;
+    }
+
+    pub fn tsig_key(&self) -> Option<&TsigKey> {
+        self.tsig_key.as_ref()
+    }
+
+    /// Set TSIG key: queries are signed, and responses must be signed with the same key.
+    pub fn set_tsig_key(&mut self, key: Option<TsigKey>) {
+        self.tsig_key = key;
+    }
+
+    pub fn client_subnet(&self) -> Option<&ClientSubnet> {
+        self.client_subnet.as_ref()
+    }
+
+    /// Set client subnet (RFC 7871) sent in EDNS queries.
+    ///
+    /// Use a source prefix of 0 to ask the server not to use the client address.
+    pub fn set_client_subnet(&mut self, subnet: Option<ClientSubnet>) {
+        self.client_subnet = subnet;
+    }
+
+    pub fn client_cookie(&self) -> Option<[u8; 8]> {
+        self.client_cookie
+    }
+
+    /// Enable or disable DNS cookies (RFC 7873), enabled by default.
+    ///
+    /// A new client cookie is generated when enabled, and known server cookies are forgotten.
+    pub fn set_cookies(&mut self, enabled: bool) {
+        self.client_cookie = enabled.then(|| self.rng.gen());
+        self.server_cookies.clear();
+    }
 
-        Ok(Self { socket, rng })
+    /// Server cookie last received from the upstream server.
+    pub fn server_cookie(&self) -> Option<&[u8]> {
+        let upstream = self.socket.peer_addr().ok()?;
+        self.server_cookies.get(&upstream).map(Vec::as_slice)
+    }
+
+    /// Extended DNS errors (RFC 8914) of the last response, explaining
+    /// error response codes in particular.
+    pub fn extended_errors(&self) -> &[ExtendedError] {
+        &self.extended_errors
     }
 
     pub fn query(&mut self, question: &QuestionSection) -> Result<AnswerSection, DnsError> {
-        let id = (self.rng.next_u32() % u16::MAX as u32) as u16;
+        let response = self.send_query(Self::new_query(question))?;
+
+        // Check response content
+        if response.response_code() != ResponseCode::NoError {
+            return Err(DnsError::ErrorResponse(response.response_code()));
+        }
+        if response.answers.len() != 1 {
+            return Err(DnsError::InvalidResponse("Invalid response content"));
+        }
 
-        let msg = Message {
-            header: Header {
-                id,
-                flags: HeaderFlags {
-                    qr: QrFlag::Query,
-                    opcode: OpCode::Query,
-                    is_authoritative_answer: false,
-                    is_truncation: false,
-                    is_recursion_desired: true,
-                    is_recursion_available: false,
-                    response_code: ResponseCode::NoError,
-                },
-                question_count: 1,
-                answer_count: 0,
-                authority_resource_record_count: 0,
-                additional_resource_record_count: 0,
-            },
-            questions: vec![question.clone()],
-            answers: vec![],
+        Ok(response.answers[0].clone())
+    }
+
+    /// Names of an address, from its PTR records.
+    ///
+    /// CNAME records are followed, as used for classless delegation (RFC 2317).
+    pub fn reverse_lookup(&mut self, addr: IpAddr) -> Result<Vec<DomainName>, DnsError> {
+        let question = QuestionSection::new_ptr(addr);
+        let response = self.send_query(Self::new_query(&question))?;
+        if response.response_code() != ResponseCode::NoError {
+            return Err(DnsError::ErrorResponse(response.response_code()));
+        }
+
+        let mut name = question.name;
+        for answer in &response.answers {
+            match &answer.data {
+                RData::CNAME(target) if answer.name == name => name = target.clone(),
+                _ => {}
+            }
+        }
+        Ok(response
+            .answers
+            .iter()
+            .filter(|x| x.name == name)
+            .filter_map(|x| match &x.data {
+                RData::PTR(target) => Some(target.clone()),
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// Query and validate the response against the trust anchor.
+    ///
+    /// DNSKEY and DS records are queried as needed. Without trust anchor,
+    /// the response is reported insecure.
+    pub fn query_validated(
+        &mut self,
+        question: &QuestionSection,
+    ) -> Result<ValidatedResponse, DnsError> {
+        let response = self.send_query(Self::new_query(question))?;
+        match response.response_code() {
+            ResponseCode::NoError | ResponseCode::NonExistentDomain => {}
+            code => return Err(DnsError::ErrorResponse(code)),
+        }
+
+        let status = match self.trust_anchor.clone() {
+            Some(anchor) => {
+                Validator::new(self, anchor, unix_time() as u32).validate(question, &response)?
+            }
+            None => ValidationStatus::Insecure,
         };
+        Ok(ValidatedResponse { response, status })
+    }
+
+    /// Build a recursive query for a single question.
+    pub fn new_query(question: &QuestionSection) -> Message {
+        MessageBuilder::query()
+            .recursion_desired(true)
+            .question(question.clone())
+            .build()
+    }
+
+    /// Send a query and wait for its response.
+    ///
+    /// Query ID is replaced by a random one, and EDNS is added if enabled and missing.
+    /// DO bit is set if a trust anchor is configured. Client subnet and
+    /// COOKIE options are added if enabled and the query has none. Query is signed
+    /// last if a TSIG key is configured, and the response signature verified.
+    /// Datagrams that do not match the query ID and question are ignored.
+    ///
+    /// A BADCOOKIE response is retried once, with the new server cookie.
+    pub fn send_query(&mut self, msg: Message) -> Result<Message, DnsError> {
+        let response = self.exchange(msg.clone())?;
+
+        // Retry with the server cookie just received (RFC 7873 section 5.3).
+        let has_cookie = msg
+            .edns()
+            .is_some_and(|edns| edns.option(EdnsOption::COOKIE).is_some());
+        if response.response_code() == ResponseCode::BadCookie && !has_cookie {
+            return self.exchange(msg);
+        }
+
+        Ok(response)
+    }
+
+    /// Send a query once, see `send_query`.
+    fn exchange(&mut self, mut msg: Message) -> Result<Message, DnsError> {
+        let id = (self.rng.next_u32() % u16::MAX as u32) as u16;
+        msg.header.id = id;
+        if msg.edns().is_none() {
+            msg.set_edns(self.udp_payload_size.map(Edns::new));
+        }
+        if self.trust_anchor.is_some() {
+            let mut edns = msg
+                .edns()
+                .unwrap_or_else(|| Edns::new(DEFAULT_UDP_PAYLOAD_SIZE));
+            edns.dnssec_ok = true;
+            msg.set_edns(Some(edns));
+        }
+        if let (Some(mut edns), Some(subnet)) = (msg.edns(), self.client_subnet) {
+            if edns.option(EdnsOption::CLIENT_SUBNET).is_none() {
+                edns.set_option(EdnsOption::ClientSubnet(subnet));
+                msg.set_edns(Some(edns));
+            }
+        }
+        let upstream = self.socket.peer_addr()?;
+        if let (Some(mut edns), Some(client)) = (msg.edns(), self.client_cookie) {
+            if edns.option(EdnsOption::COOKIE).is_none() {
+                let server = self.server_cookies.get(&upstream).cloned();
+                edns.set_option(EdnsOption::Cookie {
+                    client,
+                    server: server.unwrap_or_default(),
+                });
+                msg.set_edns(Some(edns));
+            }
+        }
+        let request_mac = self
+            .tsig_key
+            .as_ref()
+            .map(|key| tsig::sign(&mut msg, key, unix_time(), None));
 
         // Send msg to dns server
         let mut buf = Vec::with_capacity(4096);
@@ -59,22 +279,80 @@ impl DnsClient {
         self.socket.send(&buf)?;
 
         // Read response
-        let mut socket_data = vec![0u8; MAX_DATAGRAM_SIZE];
-        let len = self.socket.recv(&mut socket_data)?;
-        if len == 0 {
-            return Err(DnsError::EmptyResponse);
-        }
-
-        let (_, response) = Message::parse(&socket_data[..len])?;
+        self.extended_errors.clear();
+        let max_size = msg.edns().map_or(MIN_UDP_PAYLOAD_SIZE, |x| {
+            x.udp_payload_size.max(MIN_UDP_PAYLOAD_SIZE)
+        });
+        let mut socket_data = vec![0u8; max_size as usize];
+        let timeout = self.socket.read_timeout()?;
+        let received = self.receive(&msg, &mut socket_data, timeout);
+        self.socket.set_read_timeout(timeout)?;
+        let (len, response) = received?;
 
-        // Check response content
-        if response.header.id != id {
-            return Err(DnsError::InvalidResponse("Invalid response ID"));
+        if let (Some(key), Some(mac)) = (&self.tsig_key, &request_mac) {
+            tsig::verify(&socket_data[..len], key, unix_time(), Some(mac))?;
         }
-        if response.answers.len() != 1 {
-            return Err(DnsError::InvalidResponse("Invalid response content"));
+
+        // Responses must echo our client cookie (RFC 7873 section 5.3).
+        let sent_cookie = msg
+            .edns()
+            .and_then(|edns| edns.option(EdnsOption::COOKIE).cloned());
+        let received_cookie = response
+            .edns()
+            .and_then(|edns| edns.option(EdnsOption::COOKIE).cloned());
+        match (sent_cookie, received_cookie) {
+            (_, None) => {}
+            (
+                Some(EdnsOption::Cookie { client: sent, .. }),
+                Some(EdnsOption::Cookie { client, server }),
+            ) if client == sent => {
+                if !server.is_empty() && Some(client) == self.client_cookie {
+                    self.server_cookies.insert(upstream, server);
+                }
+            }
+            _ => return Err(DnsError::InvalidResponse("Invalid response cookie")),
         }
+        self.extended_errors = response.extended_errors();
 
-        Ok(response.answers[0].clone())
+        Ok(response)
     }
+
+    /// Wait for the response to `query` until `timeout`, discarding datagrams
+    /// with another ID or question (such as late responses to earlier queries).
+    fn receive(
+        &self,
+        query: &Message,
+        buf: &mut [u8],
+        timeout: Option<Duration>,
+    ) -> Result<(usize, Message), DnsError> {
+        let deadline = timeout.map(|x| Instant::now() + x);
+        loop {
+            if let Some(deadline) = deadline {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    return Err(DnsError::Timeout);
+                }
+                self.socket.set_read_timeout(Some(remaining))?;
+            }
+            let len = self.socket.recv(buf)?;
+            if len == 0 {
+                return Err(DnsError::EmptyResponse);
+            }
+            if buf[..len.min(2)] != query.header.id.to_be_bytes() {
+                continue;
+            }
+
+            let (_, response) = Message::parse(&buf[..len])?;
+            if response.questions.is_empty() || response.questions == query.questions {
+                return Ok((len, response));
+            }
+        }
+    }
+}
+
+/// Current time, in seconds since epoch.
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |x| x.as_secs())
 }
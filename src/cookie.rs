@@ -0,0 +1,170 @@
+//! DNS cookies: lightweight protection against off-path spoofing and amplification.
+//!
+//! Server cookies follow the RFC 9018 layout, with an HMAC-SHA256 hash.
+//!
+//! Check: https://www.rfc-editor.org/rfc/rfc7873
+
+use std::net::IpAddr;
+
+use crate::{
+    dnssec::sha,
+    message::{EdnsOption, Message},
+};
+
+/// Server cookie format version (RFC 9018 section 4.2).
+const VERSION: u8 = 1;
+/// Server cookies are accepted up to one hour old...
+const MAX_AGE: i64 = 3600;
+/// ...or dated up to five minutes in the future (RFC 9018 section 4.3).
+const MAX_FUTURE: i64 = 300;
+/// Server cookies older than half an hour are replaced in responses.
+const REFRESH_AGE: i64 = 1800;
+/// Version, reserved bytes and timestamp: the server cookie before its hash.
+const HEADER_LEN: usize = 8;
+const HASH_LEN: usize = 8;
+
+/// COOKIE option of a request, checked by the server (RFC 7873 section 5.2).
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum CookieCheck {
+    /// No COOKIE option.
+    Missing,
+    /// COOKIE option of invalid length: the request gets a FORMERR.
+    Malformed,
+    /// Client cookie only: first query to this server.
+    ClientOnly([u8; 8]),
+    /// Server cookie not issued by us to this client, or expired.
+    Invalid([u8; 8]),
+    /// Server cookie issued by us to this client.
+    Valid {
+        client: [u8; 8],
+        server: Vec<u8>,
+        /// Whether the server cookie can be sent back as is.
+        is_fresh: bool,
+    },
+}
+
+impl CookieCheck {
+    pub fn client_cookie(&self) -> Option<[u8; 8]> {
+        match self {
+            Self::Missing | Self::Malformed => None,
+            Self::ClientOnly(client) | Self::Invalid(client) | Self::Valid { client, .. } => {
+                Some(*client)
+            }
+        }
+    }
+}
+
+/// Server secrets: server cookies are built with the current one, and
+/// accepted with the previous one too while secrets rotate.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CookieSecrets {
+    current: Vec<u8>,
+    previous: Option<Vec<u8>>,
+}
+
+impl CookieSecrets {
+    pub fn new(secret: Vec<u8>) -> Self {
+        Self {
+            current: secret,
+            previous: None,
+        }
+    }
+
+    /// Build server cookies with a new secret.
+    ///
+    /// Rotate at most once per hour, so that cookies of the previous secret
+    /// stay valid until they expire.
+    pub fn rotate(&mut self, secret: Vec<u8>) {
+        self.previous = Some(std::mem::replace(&mut self.current, secret));
+    }
+
+    /// New server cookie for a client, with `now` in seconds since epoch.
+    pub fn server_cookie(&self, client: &[u8; 8], client_ip: IpAddr, now: u64) -> Vec<u8> {
+        let mut cookie = vec![VERSION, 0, 0, 0];
+        cookie.extend((now as u32).to_be_bytes());
+        cookie.extend(hash(&self.current, client, &cookie, client_ip));
+        cookie
+    }
+
+    /// Check the COOKIE option of a request sent from `client_ip`.
+    pub fn check(&self, request: &Message, client_ip: IpAddr, now: u64) -> CookieCheck {
+        let Some(edns) = request.edns() else {
+            return CookieCheck::Missing;
+        };
+        let (client, server) = match edns.option(EdnsOption::COOKIE) {
+            None => return CookieCheck::Missing,
+            Some(EdnsOption::Cookie { client, server }) => (*client, server),
+            Some(_) => return CookieCheck::Malformed,
+        };
+        if server.is_empty() {
+            return CookieCheck::ClientOnly(client);
+        }
+        if server.len() != HEADER_LEN + HASH_LEN || server[0] != VERSION {
+            return CookieCheck::Invalid(client);
+        }
+
+        // Timestamps are compared in serial number arithmetic.
+        let timestamp = u32::from_be_bytes(server[4..8].try_into().unwrap());
+        let age = (now as u32).wrapping_sub(timestamp) as i32 as i64;
+        if !(-MAX_FUTURE..=MAX_AGE).contains(&age) {
+            return CookieCheck::Invalid(client);
+        }
+
+        let (header, mac) = server.split_at(HEADER_LEN);
+        let matches = |secret: &[u8]| {
+            // Compare in constant time.
+            hash(secret, &client, header, client_ip)
+                .iter()
+                .zip(mac)
+                .fold(0, |acc, (x, y)| acc | (x ^ y))
+                == 0
+        };
+        let is_current = matches(&self.current);
+        if !is_current && !self.previous.as_deref().is_some_and(matches) {
+            return CookieCheck::Invalid(client);
+        }
+
+        CookieCheck::Valid {
+            client,
+            server: server.clone(),
+            is_fresh: is_current && age < REFRESH_AGE,
+        }
+    }
+
+    /// COOKIE option for the response to a checked request: fresh server
+    /// cookies are sent back, others replaced by a new one.
+    pub fn response_option(
+        &self,
+        check: &CookieCheck,
+        client_ip: IpAddr,
+        now: u64,
+    ) -> Option<EdnsOption> {
+        let server = match check {
+            CookieCheck::Valid {
+                server,
+                is_fresh: true,
+                ..
+            } => server.clone(),
+            _ => self.server_cookie(&check.client_cookie()?, client_ip, now),
+        };
+
+        Some(EdnsOption::Cookie {
+            client: check.client_cookie()?,
+            server,
+        })
+    }
+}
+
+/// Server cookie hash (RFC 9018 section 4.4, with HMAC-SHA256 instead of SipHash).
+fn hash(secret: &[u8], client: &[u8; 8], header: &[u8], client_ip: IpAddr) -> Vec<u8> {
+    let mut data = client.to_vec();
+    data.extend(header);
+    match client_ip {
+        IpAddr::V4(ip) => data.extend(ip.octets()),
+        IpAddr::V6(ip) => data.extend(ip.octets()),
+    }
+
+    let mut mac = sha::hmac::<64>(secret, &data, |x| sha::sha256(x).to_vec());
+    mac.truncate(HASH_LEN);
+    mac
+}
@@ -0,0 +1,313 @@
+//! Unsigned big integers and modular arithmetic, as needed to verify signatures.
+//!
+//! Nothing here runs in constant time: only public data is processed.
+
+use std::cmp::Ordering;
+
+/// Unsigned integer, as little-endian 64 bits limbs.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BigUint(Vec<u64>);
+
+impl BigUint {
+    pub(crate) fn from_u64(value: u64) -> Self {
+        Self(vec![value])
+    }
+
+    pub(crate) fn from_be_bytes(bytes: &[u8]) -> Self {
+        let limbs = bytes
+            .rchunks(8)
+            .map(|chunk| chunk.iter().fold(0, |acc, x| (acc << 8) | *x as u64))
+            .collect();
+        Self(limbs)
+    }
+
+    pub(crate) fn from_le_bytes(bytes: &[u8]) -> Self {
+        let reversed: Vec<u8> = bytes.iter().rev().copied().collect();
+        Self::from_be_bytes(&reversed)
+    }
+
+    /// Big-endian bytes, left padded to `len` bytes. Value must fit.
+    pub(crate) fn to_be_bytes(&self, len: usize) -> Vec<u8> {
+        let bytes: Vec<u8> = self.0.iter().rev().flat_map(|x| x.to_be_bytes()).collect();
+        let start = bytes.iter().position(|x| *x != 0).unwrap_or(bytes.len());
+        assert!(bytes.len() - start <= len, "Integer does not fit");
+
+        let mut output = vec![0; len];
+        output[len - (bytes.len() - start)..].copy_from_slice(&bytes[start..]);
+        output
+    }
+
+    pub(crate) fn to_le_bytes(&self, len: usize) -> Vec<u8> {
+        let mut output = self.to_be_bytes(len);
+        output.reverse();
+        output
+    }
+
+    pub(crate) fn is_zero(&self) -> bool {
+        self.0.iter().all(|x| *x == 0)
+    }
+
+    pub(crate) fn is_odd(&self) -> bool {
+        self.0.first().is_some_and(|x| x & 1 == 1)
+    }
+
+    /// Number of significant bits.
+    pub(crate) fn bits(&self) -> usize {
+        match self.0.iter().rposition(|x| *x != 0) {
+            Some(idx) => idx * 64 + 64 - self.0[idx].leading_zeros() as usize,
+            None => 0,
+        }
+    }
+
+    pub(crate) fn bit(&self, idx: usize) -> bool {
+        self.0
+            .get(idx / 64)
+            .is_some_and(|x| (x >> (idx % 64)) & 1 == 1)
+    }
+
+    fn limb(&self, idx: usize) -> u64 {
+        self.0.get(idx).copied().unwrap_or(0)
+    }
+
+    /// `self - other`, `other` must not be greater.
+    pub(crate) fn sub(&self, other: &Self) -> Self {
+        let mut output = self.0.clone();
+        sub_in_place(&mut output, &other.0);
+        Self(output)
+    }
+
+    /// Remainder of the division by `m` (bit by bit: slow, but seldom used).
+    pub(crate) fn rem(&self, m: &Self) -> Self {
+        assert!(!m.is_zero(), "Division by zero");
+        let mut output = Self(vec![0; m.0.len() + 1]);
+        for idx in (0..self.bits()).rev() {
+            shl1_in_place(&mut output.0);
+            output.0[0] |= self.bit(idx) as u64;
+            if output >= *m {
+                sub_in_place(&mut output.0, &m.0);
+            }
+        }
+        output.0.truncate(m.0.len());
+        output
+    }
+}
+
+impl PartialEq for BigUint {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for BigUint {}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let len = self.0.len().max(other.0.len());
+        (0..len)
+            .rev()
+            .map(|idx| self.limb(idx).cmp(&other.limb(idx)))
+            .find(|x| x.is_ne())
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+/// Subtract `b` from `a` in place, returning the final borrow.
+fn sub_in_place(a: &mut [u64], b: &[u64]) -> bool {
+    let mut borrow = false;
+    for (idx, x) in a.iter_mut().enumerate() {
+        let y = b.get(idx).copied().unwrap_or(0);
+        let (value, b1) = x.overflowing_sub(y);
+        let (value, b2) = value.overflowing_sub(borrow as u64);
+        *x = value;
+        borrow = b1 || b2;
+    }
+    borrow
+}
+
+/// Add `b` to `a` in place, returning the final carry.
+fn add_in_place(a: &mut [u64], b: &[u64]) -> bool {
+    let mut carry = false;
+    for (x, y) in a.iter_mut().zip(b) {
+        let (value, c1) = x.overflowing_add(*y);
+        let (value, c2) = value.overflowing_add(carry as u64);
+        *x = value;
+        carry = c1 || c2;
+    }
+    carry
+}
+
+fn shl1_in_place(a: &mut [u64]) -> bool {
+    let mut carry = 0;
+    for x in a.iter_mut() {
+        let next = *x >> 63;
+        *x = (*x << 1) | carry;
+        carry = next;
+    }
+    carry == 1
+}
+
+/// Arithmetic modulo an odd number, with values in Montgomery form.
+#[derive(Debug, Clone)]
+pub(crate) struct Modulus {
+    m: BigUint,
+    /// `-m^-1 mod 2^64`
+    m_inv: u64,
+    /// `R^2 mod m`, with `R = 2^(64 * limbs)`.
+    r2: BigUint,
+}
+
+impl Modulus {
+    pub(crate) fn new(m: &BigUint) -> Self {
+        assert!(m.is_odd(), "Modulus must be odd");
+        let limbs = m.0.iter().rposition(|x| *x != 0).unwrap() + 1;
+        let m = BigUint(m.0[..limbs].to_vec());
+
+        // Newton iteration, each step doubles the number of correct bits.
+        let mut inv = 1_u64;
+        for _ in 0..6 {
+            inv = inv.wrapping_mul(2_u64.wrapping_sub(m.0[0].wrapping_mul(inv)));
+        }
+
+        // Double 1 up to R^2, reducing on the way.
+        let mut r2 = BigUint(vec![0; limbs]);
+        r2.0[0] = 1;
+        for _ in 0..128 * limbs {
+            let carry = shl1_in_place(&mut r2.0);
+            if carry || r2 >= m {
+                sub_in_place(&mut r2.0, &m.0);
+            }
+        }
+
+        Self {
+            m,
+            m_inv: inv.wrapping_neg(),
+            r2,
+        }
+    }
+
+    pub(crate) fn value(&self) -> &BigUint {
+        &self.m
+    }
+
+    fn limbs(&self) -> usize {
+        self.m.0.len()
+    }
+
+    /// Convert to Montgomery form, reducing value first if needed.
+    pub(crate) fn enter_mont(&self, a: &BigUint) -> BigUint {
+        let a = if a.0.len() > self.limbs() || *a >= self.m {
+            a.rem(&self.m)
+        } else {
+            a.clone()
+        };
+        self.mul(&self.resize(a), &self.r2)
+    }
+
+    /// Convert back from Montgomery form.
+    pub(crate) fn leave_mont(&self, a: &BigUint) -> BigUint {
+        let mut one = vec![0; self.limbs()];
+        one[0] = 1;
+        self.mul(a, &BigUint(one))
+    }
+
+    fn resize(&self, mut a: BigUint) -> BigUint {
+        a.0.resize(self.limbs(), 0);
+        a
+    }
+
+    pub(crate) fn zero(&self) -> BigUint {
+        BigUint(vec![0; self.limbs()])
+    }
+
+    pub(crate) fn one(&self) -> BigUint {
+        self.enter_mont(&BigUint::from_u64(1))
+    }
+
+    pub(crate) fn add(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        let mut output = a.0.clone();
+        let carry = add_in_place(&mut output, &b.0);
+        let mut output = BigUint(output);
+        if carry || output >= self.m {
+            sub_in_place(&mut output.0, &self.m.0);
+        }
+        output
+    }
+
+    pub(crate) fn sub(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        let mut output = a.0.clone();
+        if sub_in_place(&mut output, &b.0) {
+            add_in_place(&mut output, &self.m.0);
+        }
+        BigUint(output)
+    }
+
+    pub(crate) fn neg(&self, a: &BigUint) -> BigUint {
+        self.sub(&self.zero(), a)
+    }
+
+    /// Montgomery product: `a * b / R mod m`.
+    pub(crate) fn mul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        let n = self.limbs();
+        let m = &self.m.0;
+        let mut t = vec![0_u64; n + 2];
+
+        for idx in 0..n {
+            let bi = b.limb(idx) as u128;
+            let mut carry = 0_u128;
+            for (jdx, x) in t.iter_mut().take(n).enumerate() {
+                let value = *x as u128 + a.limb(jdx) as u128 * bi + carry;
+                *x = value as u64;
+                carry = value >> 64;
+            }
+            let value = t[n] as u128 + carry;
+            t[n] = value as u64;
+            t[n + 1] = (value >> 64) as u64;
+
+            let factor = t[0].wrapping_mul(self.m_inv) as u128;
+            let mut carry = (t[0] as u128 + factor * m[0] as u128) >> 64;
+            for jdx in 1..n {
+                let value = t[jdx] as u128 + factor * m[jdx] as u128 + carry;
+                t[jdx - 1] = value as u64;
+                carry = value >> 64;
+            }
+            let value = t[n] as u128 + carry;
+            t[n - 1] = value as u64;
+            t[n] = t[n + 1] + (value >> 64) as u64;
+        }
+
+        let overflow = t[n] != 0;
+        t.truncate(n);
+        let mut output = BigUint(t);
+        if overflow || output >= self.m {
+            sub_in_place(&mut output.0, m);
+        }
+        output
+    }
+
+    pub(crate) fn square(&self, a: &BigUint) -> BigUint {
+        self.mul(a, a)
+    }
+
+    /// `a^e`, with `a` in Montgomery form and plain exponent.
+    pub(crate) fn pow(&self, a: &BigUint, e: &BigUint) -> BigUint {
+        let mut output = self.one();
+        for idx in (0..e.bits()).rev() {
+            output = self.square(&output);
+            if e.bit(idx) {
+                output = self.mul(&output, a);
+            }
+        }
+        output
+    }
+
+    /// Inverse of `a` (Fermat), modulus must be prime.
+    pub(crate) fn inv(&self, a: &BigUint) -> BigUint {
+        self.pow(a, &self.m.sub(&BigUint::from_u64(2)))
+    }
+}
@@ -0,0 +1,383 @@
+//! Authenticated denial of existence, from validated NSEC and NSEC3 records.
+//!
+//! Check: https://www.rfc-editor.org/rfc/rfc4035#section-5.4
+//! and https://www.rfc-editor.org/rfc/rfc5155#section-8
+
+use std::cell::RefCell;
+
+use crate::message::{
+    codec::decode_base32hex, AnswerSection, DomainName, RData, ResourceRecordType,
+};
+
+use super::{has_type, nsec3_hash, NSEC3_FLAG_OPT_OUT, NSEC3_HASH_SHA1, NSEC3_MAX_ITERATIONS};
+
+/// Outcome of a denial of existence check.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum Proof {
+    Proven,
+    /// Only proven if there is no unsigned delegation in an NSEC3 opt-out span.
+    OptOut,
+    /// Not checked, NSEC3 records use too many iterations (RFC 9276 section 3.2).
+    Insecure,
+    Missing,
+}
+
+/// Outcome of a check for a delegation without DS.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum DsDenial {
+    /// Name is not a delegation point.
+    NotDelegation,
+    /// Name is a delegation to an unsigned zone.
+    InsecureDelegation,
+    Missing,
+}
+
+/// Prove that `name` does not exist (NXDOMAIN).
+pub(crate) fn name_error(name: &DomainName, records: &[AnswerSection]) -> Proof {
+    let hashes = Hashes::default();
+    let nsec3s = nsec3s(records, &hashes);
+    insecure_if_costly(prove_name_error(name, records, &nsec3s), records)
+}
+
+fn prove_name_error(name: &DomainName, records: &[AnswerSection], nsec3s: &[Nsec3]) -> Proof {
+    if let Some(encloser) = nsec_closest_encloser(name, records) {
+        let Ok(wildcard) = encloser.prepend_label("*") else {
+            return Proof::Missing;
+        };
+        if nsecs(records).any(|x| nsec_covers(x, &wildcard)) {
+            return Proof::Proven;
+        }
+    }
+
+    match nsec3_closest_encloser(name, nsec3s) {
+        Some((encloser, cover)) => {
+            let Ok(wildcard) = encloser.prepend_label("*") else {
+                return Proof::Missing;
+            };
+            if !nsec3s.iter().any(|x| x.covers(&wildcard)) {
+                Proof::Missing
+            } else if cover.is_opt_out() {
+                Proof::OptOut
+            } else {
+                Proof::Proven
+            }
+        }
+        None => Proof::Missing,
+    }
+}
+
+/// Prove that `name` has no record of type `rr_type` (NODATA).
+pub(crate) fn no_data(
+    name: &DomainName,
+    rr_type: ResourceRecordType,
+    records: &[AnswerSection],
+) -> Proof {
+    let hashes = Hashes::default();
+    let nsec3s = nsec3s(records, &hashes);
+    insecure_if_costly(prove_no_data(name, rr_type, records, &nsec3s), records)
+}
+
+fn prove_no_data(
+    name: &DomainName,
+    rr_type: ResourceRecordType,
+    records: &[AnswerSection],
+    nsec3s: &[Nsec3],
+) -> Proof {
+    let lacks_type = |data: &RData| {
+        !has_type(data, rr_type)
+            && !has_type(data, ResourceRecordType::CNAME)
+            && is_right_side(data, rr_type)
+    };
+
+    // Matching record
+    if nsecs(records).any(|x| x.name == *name && lacks_type(&x.data)) {
+        return Proof::Proven;
+    }
+    if nsec3s
+        .iter()
+        .any(|x| x.matches(name) && lacks_type(&x.record.data))
+    {
+        return Proof::Proven;
+    }
+
+    // Wildcard match
+    if let Some(encloser) = nsec_closest_encloser(name, records) {
+        if let Ok(wildcard) = encloser.prepend_label("*") {
+            if nsecs(records).any(|x| x.name == wildcard && lacks_type(&x.data)) {
+                return Proof::Proven;
+            }
+        }
+    }
+    if let Some((encloser, cover)) = nsec3_closest_encloser(name, nsec3s) {
+        if let Ok(wildcard) = encloser.prepend_label("*") {
+            if nsec3s
+                .iter()
+                .any(|x| x.matches(&wildcard) && lacks_type(&x.record.data))
+            {
+                return Proof::Proven;
+            }
+        }
+        // Unsigned delegation in an opt-out span (RFC 5155 section 8.6)
+        if rr_type == ResourceRecordType::DS && cover.is_opt_out() {
+            return Proof::OptOut;
+        }
+    }
+
+    Proof::Missing
+}
+
+/// Check if `name` is not a delegation, or a delegation without DS.
+pub(crate) fn ds_denial(name: &DomainName, records: &[AnswerSection]) -> DsDenial {
+    let hashes = Hashes::default();
+    let nsec3s = nsec3s(records, &hashes);
+    let matching = nsecs(records)
+        .find(|x| x.name == *name)
+        .map(|x| &x.data)
+        .or_else(|| {
+            nsec3s
+                .iter()
+                .find(|x| x.matches(name))
+                .map(|x| &x.record.data)
+        });
+
+    if let Some(data) = matching {
+        let has = |rr_type| has_type(data, rr_type);
+        return match (
+            has(ResourceRecordType::NS),
+            has(ResourceRecordType::SOA),
+            has(ResourceRecordType::DS),
+        ) {
+            (_, _, true) => DsDenial::Missing,
+            (false, _, false) => DsDenial::NotDelegation,
+            // Child side of the cut: DS must come from the parent zone.
+            (true, true, false) => DsDenial::Missing,
+            (true, false, false) => DsDenial::InsecureDelegation,
+        };
+    }
+
+    let no_data = prove_no_data(name, ResourceRecordType::DS, records, &nsec3s);
+    match insecure_if_costly(no_data, records) {
+        Proof::OptOut | Proof::Insecure => DsDenial::InsecureDelegation,
+        _ if prove_name_error(name, records, &nsec3s) == Proof::Proven => DsDenial::NotDelegation,
+        _ => DsDenial::Missing,
+    }
+}
+
+/// Prove that a wildcard answer was needed: `name` does not exist below
+/// `encloser` (RFC 4035 section 5.3.4, RFC 5155 section 8.8).
+pub(crate) fn wildcard_answer(
+    name: &DomainName,
+    encloser: &DomainName,
+    records: &[AnswerSection],
+) -> Proof {
+    if nsecs(records).any(|x| nsec_covers(x, name)) {
+        return Proof::Proven;
+    }
+
+    let Some(next_closer) = next_closer(name, encloser) else {
+        return Proof::Missing;
+    };
+    let hashes = Hashes::default();
+    let proof = match nsec3s(records, &hashes)
+        .iter()
+        .find(|x| x.covers(&next_closer))
+    {
+        Some(cover) if cover.is_opt_out() => Proof::OptOut,
+        Some(_) => Proof::Proven,
+        None => Proof::Missing,
+    };
+    insecure_if_costly(proof, records)
+}
+
+/// Missing proofs are insecure if NSEC3 records were ignored for their
+/// iterations (RFC 9276 section 3.2).
+fn insecure_if_costly(proof: Proof, records: &[AnswerSection]) -> Proof {
+    let is_costly = records.iter().any(
+        |x| matches!(x.data, RData::NSEC3 { iterations, .. } if iterations > NSEC3_MAX_ITERATIONS),
+    );
+    match proof {
+        Proof::Missing if is_costly => Proof::Insecure,
+        proof => proof,
+    }
+}
+
+/// NSEC and NSEC3 records of a zone apex or a delegation can only prove
+/// things about the zone side they come from (RFC 6840 section 4.4).
+fn is_right_side(data: &RData, rr_type: ResourceRecordType) -> bool {
+    let is_delegation =
+        has_type(data, ResourceRecordType::NS) && !has_type(data, ResourceRecordType::SOA);
+    if rr_type == ResourceRecordType::DS {
+        !has_type(data, ResourceRecordType::SOA)
+    } else {
+        !is_delegation
+    }
+}
+
+/// Name below `encloser`, with one more label of `name`.
+fn next_closer(name: &DomainName, encloser: &DomainName) -> Option<DomainName> {
+    let count = encloser.label_count() + 1;
+    if !name.is_subdomain_of(encloser) || name.label_count() < count {
+        return None;
+    }
+    std::iter::once(name.clone())
+        .chain(name.parents())
+        .find(|x| x.label_count() == count)
+}
+
+fn nsecs(records: &[AnswerSection]) -> impl Iterator<Item = &AnswerSection> {
+    records
+        .iter()
+        .filter(|x| matches!(x.data, RData::NSEC { .. }))
+}
+
+/// Check if NSEC proves `name` does not exist.
+fn nsec_covers(nsec: &AnswerSection, name: &DomainName) -> bool {
+    let RData::NSEC {
+        next_domain_name, ..
+    } = &nsec.data
+    else {
+        return false;
+    };
+
+    // Names below a delegation or a DNAME are not in the zone.
+    let is_cut = (has_type(&nsec.data, ResourceRecordType::NS)
+        && !has_type(&nsec.data, ResourceRecordType::SOA))
+        || has_type(&nsec.data, ResourceRecordType::DNAME);
+    if is_cut && name.is_subdomain_of(&nsec.name) {
+        return false;
+    }
+
+    if nsec.name < *next_domain_name {
+        nsec.name < *name && name < next_domain_name
+    } else {
+        // Last NSEC of the zone, next name is the apex.
+        name.is_subdomain_of(next_domain_name) && nsec.name < *name
+    }
+}
+
+/// Closest existing ancestor of `name`, from an NSEC covering it.
+fn nsec_closest_encloser(name: &DomainName, records: &[AnswerSection]) -> Option<DomainName> {
+    let nsec = nsecs(records).find(|x| nsec_covers(x, name))?;
+    let RData::NSEC {
+        next_domain_name, ..
+    } = &nsec.data
+    else {
+        return None;
+    };
+
+    name.parents()
+        .find(|x| nsec.name.is_subdomain_of(x) || next_domain_name.is_subdomain_of(x))
+}
+
+/// Name, salt and iterations of an NSEC3 hash.
+type HashInput = (DomainName, Vec<u8>, u16);
+
+/// Hashes of names computed for a proof, each name being hashed once.
+#[derive(Default)]
+struct Hashes(RefCell<Vec<(HashInput, Vec<u8>)>>);
+
+impl Hashes {
+    fn get(&self, name: &DomainName, salt: &[u8], iterations: u16) -> Vec<u8> {
+        let mut hashes = self.0.borrow_mut();
+        let cached = hashes
+            .iter()
+            .find(|((n, s, i), _)| n == name && s == salt && *i == iterations);
+        if let Some((_, hash)) = cached {
+            return hash.clone();
+        }
+        let hash = nsec3_hash(name, salt, iterations);
+        hashes.push(((name.clone(), salt.to_vec(), iterations), hash.clone()));
+        hash
+    }
+}
+
+/// NSEC3 record, with decoded hashes.
+struct Nsec3<'a> {
+    record: &'a AnswerSection,
+    zone: DomainName,
+    owner_hash: Vec<u8>,
+    hashes: &'a Hashes,
+}
+
+impl Nsec3<'_> {
+    fn hash(&self, name: &DomainName) -> Option<Vec<u8>> {
+        let RData::NSEC3 {
+            salt, iterations, ..
+        } = &self.record.data
+        else {
+            return None;
+        };
+        name.is_subdomain_of(&self.zone)
+            .then(|| self.hashes.get(name, salt, *iterations))
+    }
+
+    fn matches(&self, name: &DomainName) -> bool {
+        self.hash(name).is_some_and(|x| x == self.owner_hash)
+    }
+
+    fn covers(&self, name: &DomainName) -> bool {
+        let RData::NSEC3 {
+            next_hashed_owner_name: next,
+            ..
+        } = &self.record.data
+        else {
+            return false;
+        };
+        let Some(hash) = self.hash(name) else {
+            return false;
+        };
+
+        if self.owner_hash < *next {
+            self.owner_hash < hash && hash < *next
+        } else {
+            // Last NSEC3 of the zone
+            self.owner_hash < hash || hash < *next
+        }
+    }
+
+    fn is_opt_out(&self) -> bool {
+        matches!(self.record.data, RData::NSEC3 { flags, .. } if flags & NSEC3_FLAG_OPT_OUT != 0)
+    }
+}
+
+/// Supported NSEC3 records, hashing names with `hashes`. Records with more
+/// iterations than the limit are ignored.
+fn nsec3s<'a>(records: &'a [AnswerSection], hashes: &'a Hashes) -> Vec<Nsec3<'a>> {
+    records
+        .iter()
+        .filter_map(|record| {
+            let RData::NSEC3 {
+                hash_algorithm,
+                iterations,
+                ..
+            } = &record.data
+            else {
+                return None;
+            };
+            if *hash_algorithm != NSEC3_HASH_SHA1 || *iterations > NSEC3_MAX_ITERATIONS {
+                return None;
+            }
+            let label = record.name.labels().first()?;
+            Some(Nsec3 {
+                record,
+                zone: record.name.parent()?,
+                owner_hash: decode_base32hex(std::str::from_utf8(label).ok()?)?,
+                hashes,
+            })
+        })
+        .collect()
+}
+
+/// Closest encloser proof (RFC 5155 section 8.3): closest existing ancestor
+/// of `name`, and the NSEC3 covering the next closer name.
+fn nsec3_closest_encloser<'a, 'b>(
+    name: &DomainName,
+    nsec3s: &'b [Nsec3<'a>],
+) -> Option<(DomainName, &'b Nsec3<'a>)> {
+    let encloser = name
+        .parents()
+        .find(|x| nsec3s.iter().any(|n| n.matches(x)))?;
+    let next_closer = next_closer(name, &encloser)?;
+    let cover = nsec3s.iter().find(|x| x.covers(&next_closer))?;
+    Some((encloser, cover))
+}
@@ -0,0 +1,192 @@
+//! Ed25519 signature verification (RFC 8032 section 5.1.7).
+
+use super::{
+    bigint::{BigUint, Modulus},
+    sha::sha512,
+};
+use crate::message::codec::decode_hex;
+
+/// Field prime: `2^255 - 19`.
+const P: &str = "7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffed";
+/// Group order: `2^252 + 27742317777372353535851937790883648493`.
+const L: &str = "1000000000000000000000000000000014def9dea2f79cd65812631a5cf5d3ed";
+/// Curve constant: `-121665 / 121666`.
+const D: &str = "52036cee2b6ffe738cc740797779e89800700a4d4141d8ab75eb4dca135978a3";
+/// Square root of -1.
+const SQRT_M1: &str = "2b8324804fc1df0b2b4d00993dfbd7a72f431806ad2fe478c4ee1b274a0ea0b0";
+/// Exponent of square root candidates: `(p - 5) / 8`.
+const SQRT_EXPONENT: &str = "0ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffd";
+/// Base point, encoded.
+const BASE: &str = "5866666666666666666666666666666666666666666666666666666666666666";
+
+fn constant(hex: &str) -> BigUint {
+    BigUint::from_be_bytes(&decode_hex(hex).unwrap())
+}
+
+/// Point in extended coordinates, in Montgomery form.
+#[derive(Debug, Clone)]
+struct Point {
+    x: BigUint,
+    y: BigUint,
+    z: BigUint,
+    t: BigUint,
+}
+
+struct Curve {
+    field: Modulus,
+    d: BigUint,
+    d2: BigUint,
+}
+
+impl Curve {
+    fn new() -> Self {
+        let field = Modulus::new(&constant(P));
+        let d = field.enter_mont(&constant(D));
+        let d2 = field.add(&d, &d);
+        Self { field, d, d2 }
+    }
+
+    fn neutral(&self) -> Point {
+        Point {
+            x: self.field.zero(),
+            y: self.field.one(),
+            z: self.field.one(),
+            t: self.field.zero(),
+        }
+    }
+
+    /// Decode a point (RFC 8032 section 5.1.3).
+    fn decode(&self, bytes: &[u8]) -> Option<Point> {
+        let f = &self.field;
+        let bytes: [u8; 32] = bytes.try_into().ok()?;
+        let x_sign = bytes[31] >> 7 == 1;
+        let mut y_bytes = bytes;
+        y_bytes[31] &= 0x7F;
+
+        let y = BigUint::from_le_bytes(&y_bytes);
+        if y >= *f.value() {
+            return None;
+        }
+        let y = f.enter_mont(&y);
+
+        // x^2 = (y^2 - 1) / (d y^2 + 1)
+        let y2 = f.square(&y);
+        let u = f.sub(&y2, &f.one());
+        let v = f.add(&f.mul(&self.d, &y2), &f.one());
+
+        // Candidate root: u v^3 (u v^7)^((p - 5) / 8)
+        let v3 = f.mul(&f.square(&v), &v);
+        let v7 = f.mul(&f.square(&v3), &v);
+        let root = f.pow(&f.mul(&u, &v7), &constant(SQRT_EXPONENT));
+        let mut x = f.mul(&f.mul(&u, &v3), &root);
+
+        let vx2 = f.mul(&v, &f.square(&x));
+        if vx2 != u {
+            if vx2 != f.neg(&u) {
+                return None;
+            }
+            x = f.mul(&x, &f.enter_mont(&constant(SQRT_M1)));
+        }
+
+        if x.is_zero() && x_sign {
+            return None;
+        }
+        if f.leave_mont(&x).is_odd() != x_sign {
+            x = f.neg(&x);
+        }
+
+        let t = f.mul(&x, &y);
+        Some(Point {
+            x,
+            y,
+            z: f.one(),
+            t,
+        })
+    }
+
+    fn encode(&self, p: &Point) -> Vec<u8> {
+        let f = &self.field;
+        let z_inv = f.inv(&p.z);
+        let x = f.leave_mont(&f.mul(&p.x, &z_inv));
+        let y = f.leave_mont(&f.mul(&p.y, &z_inv));
+
+        let mut bytes = y.to_le_bytes(32);
+        bytes[31] |= (x.is_odd() as u8) << 7;
+        bytes
+    }
+
+    /// Unified addition (add-2008-hwcd-3), also valid for doubling.
+    fn add(&self, p: &Point, q: &Point) -> Point {
+        let f = &self.field;
+        let a = f.mul(&f.sub(&p.y, &p.x), &f.sub(&q.y, &q.x));
+        let b = f.mul(&f.add(&p.y, &p.x), &f.add(&q.y, &q.x));
+        let c = f.mul(&f.mul(&p.t, &self.d2), &q.t);
+        let d = f.mul(&p.z, &q.z);
+        let d = f.add(&d, &d);
+
+        let e = f.sub(&b, &a);
+        let ff = f.sub(&d, &c);
+        let g = f.add(&d, &c);
+        let h = f.add(&b, &a);
+
+        Point {
+            x: f.mul(&e, &ff),
+            y: f.mul(&g, &h),
+            z: f.mul(&ff, &g),
+            t: f.mul(&e, &h),
+        }
+    }
+
+    fn neg(&self, p: &Point) -> Point {
+        Point {
+            x: self.field.neg(&p.x),
+            y: p.y.clone(),
+            z: p.z.clone(),
+            t: self.field.neg(&p.t),
+        }
+    }
+
+    /// `a * p + b * q`, sharing doublings (Shamir's trick).
+    fn double_mul(&self, a: &BigUint, p: &Point, b: &BigUint, q: &Point) -> Point {
+        let pq = self.add(p, q);
+        let mut output = self.neutral();
+        for idx in (0..a.bits().max(b.bits())).rev() {
+            output = self.add(&output, &output);
+            match (a.bit(idx), b.bit(idx)) {
+                (true, true) => output = self.add(&output, &pq),
+                (true, false) => output = self.add(&output, p),
+                (false, true) => output = self.add(&output, q),
+                (false, false) => {}
+            }
+        }
+        output
+    }
+}
+
+/// Verify signature (`R || S`) of `message` with an encoded public key.
+pub(crate) fn verify(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    if public_key.len() != 32 || signature.len() != 64 {
+        return false;
+    }
+
+    let curve = Curve::new();
+    let Some(a) = curve.decode(public_key) else {
+        return false;
+    };
+    let base = curve.decode(&decode_hex(BASE).unwrap()).unwrap();
+
+    let order = constant(L);
+    let s = BigUint::from_le_bytes(&signature[32..]);
+    if s >= order {
+        return false;
+    }
+
+    let mut data = signature[..32].to_vec();
+    data.extend(public_key);
+    data.extend(message);
+    let k = BigUint::from_le_bytes(&sha512(&data)).rem(&order);
+
+    // Check: [S]B - [k]A == R
+    let point = curve.double_mul(&s, &base, &k, &curve.neg(&a));
+    curve.encode(&point) == signature[..32]
+}
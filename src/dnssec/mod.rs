@@ -0,0 +1,394 @@
+//! DNSSEC validation (RFC 4033, RFC 4034, RFC 4035 and RFC 5155).
+//!
+//! Supported algorithms are RSA/SHA-1, RSA/SHA-256, RSA/SHA-512,
+//! ECDSA P-256/SHA-256 and Ed25519. Cryptography is implemented here, and
+//! only verifies public data.
+
+use std::collections::BTreeSet;
+
+use crate::{
+    message::{AnswerSection, DomainName, Encoder, RData, ResourceRecordType},
+    DnssecError,
+};
+
+mod bigint;
+mod denial;
+mod ed25519;
+mod p256;
+mod rsa;
+pub(crate) mod sha;
+mod validator;
+
+pub(crate) use validator::Validator;
+
+/// RSA/SHA-1 algorithm number.
+pub const RSASHA1: u8 = 5;
+/// RSA/SHA-1 algorithm number, NSEC3 aware alias.
+pub const RSASHA1_NSEC3_SHA1: u8 = 7;
+/// RSA/SHA-256 algorithm number (RFC 5702).
+pub const RSASHA256: u8 = 8;
+/// RSA/SHA-512 algorithm number (RFC 5702).
+pub const RSASHA512: u8 = 10;
+/// ECDSA P-256 with SHA-256 algorithm number (RFC 6605).
+pub const ECDSAP256SHA256: u8 = 13;
+/// Ed25519 algorithm number (RFC 8080).
+pub const ED25519: u8 = 15;
+
+/// SHA-1 DS digest type.
+pub const DIGEST_SHA1: u8 = 1;
+/// SHA-256 DS digest type (RFC 4509).
+pub const DIGEST_SHA256: u8 = 2;
+
+/// DNSKEY flag of zone keys.
+pub const DNSKEY_FLAG_ZONE: u16 = 0x0100;
+/// DNSKEY flag of key signing keys (Secure Entry Point).
+pub const DNSKEY_FLAG_SEP: u16 = 0x0001;
+
+/// SHA-1 NSEC3 hash algorithm.
+pub const NSEC3_HASH_SHA1: u8 = 1;
+/// NSEC3 flag: the record may cover unsigned delegations.
+pub const NSEC3_FLAG_OPT_OUT: u8 = 0x01;
+/// Most NSEC3 iterations validated, more are treated as insecure (RFC 9276 section 3.2).
+pub const NSEC3_MAX_ITERATIONS: u16 = 150;
+
+/// Security status of a response (RFC 4033 section 5).
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ValidationStatus {
+    /// Chain of trust is complete from the trust anchor.
+    Secure,
+    /// Data is proven to be unsigned (or no trust anchor covers it).
+    Insecure,
+    /// Data should be signed, yet validation failed.
+    Bogus(DnssecError),
+}
+
+/// Keys trusted without validation for a zone, and all its subdomains.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TrustAnchor {
+    zone: DomainName,
+    /// DS or DNSKEY data.
+    records: Vec<RData>,
+}
+
+impl TrustAnchor {
+    /// Build a trust anchor from DS or DNSKEY data (other types are ignored).
+    pub fn new(zone: DomainName, records: Vec<RData>) -> Self {
+        let records = records
+            .into_iter()
+            .filter(|x| matches!(x, RData::DS { .. } | RData::DNSKEY { .. }))
+            .collect();
+        Self { zone, records }
+    }
+
+    pub fn zone(&self) -> &DomainName {
+        &self.zone
+    }
+
+    pub fn records(&self) -> &[RData] {
+        &self.records
+    }
+}
+
+/// Key tag of DNSKEY data (RFC 4034 appendix B), `None` for other types.
+pub fn key_tag(dnskey: &RData) -> Option<u16> {
+    if !matches!(dnskey, RData::DNSKEY { .. }) {
+        return None;
+    }
+
+    let data = rdata_bytes(dnskey);
+    let mut sum = 0_u32;
+    for chunk in data.chunks(2) {
+        sum += (chunk[0] as u32) << 8;
+        sum += chunk.get(1).copied().unwrap_or(0) as u32;
+    }
+    sum += sum >> 16;
+    Some(sum as u16)
+}
+
+/// Digest of a DNSKEY owned by `owner`, as in DS records (RFC 4034 section 5.1.4).
+pub fn ds_digest(
+    owner: &DomainName,
+    dnskey: &RData,
+    digest_type: u8,
+) -> Result<Vec<u8>, DnssecError> {
+    let mut data = name_bytes(owner);
+    data.extend(rdata_bytes(dnskey));
+
+    match digest_type {
+        DIGEST_SHA1 => Ok(sha::sha1(&data).to_vec()),
+        DIGEST_SHA256 => Ok(sha::sha256(&data).to_vec()),
+        _ => Err(DnssecError::UnsupportedDigest(digest_type)),
+    }
+}
+
+/// Check if DS data (owned by `owner`) refers to a DNSKEY.
+pub fn ds_matches(owner: &DomainName, ds: &RData, dnskey: &RData) -> bool {
+    let (
+        RData::DS {
+            key_tag: tag,
+            algorithm,
+            digest_type,
+            digest,
+        },
+        RData::DNSKEY {
+            algorithm: key_algorithm,
+            ..
+        },
+    ) = (ds, dnskey)
+    else {
+        return false;
+    };
+
+    algorithm == key_algorithm
+        && key_tag(dnskey) == Some(*tag)
+        && ds_digest(owner, dnskey, *digest_type).is_ok_and(|x| x == *digest)
+}
+
+/// Check if algorithm and digest type of DS or DNSKEY data are supported.
+pub(crate) fn is_supported(data: &RData) -> bool {
+    match data {
+        RData::DS {
+            algorithm,
+            digest_type,
+            ..
+        } => {
+            is_supported_algorithm(*algorithm)
+                && matches!(*digest_type, DIGEST_SHA1 | DIGEST_SHA256)
+        }
+        RData::DNSKEY { algorithm, .. } => is_supported_algorithm(*algorithm),
+        _ => false,
+    }
+}
+
+fn is_supported_algorithm(algorithm: u8) -> bool {
+    matches!(
+        algorithm,
+        RSASHA1 | RSASHA1_NSEC3_SHA1 | RSASHA256 | RSASHA512 | ECDSAP256SHA256 | ED25519
+    )
+}
+
+/// Verify that `rrsig` signs `rrset` with `dnskey`, at time `now` (seconds since epoch).
+///
+/// All records of `rrset` must share owner, class and type.
+pub fn verify_rrsig(
+    rrset: &[AnswerSection],
+    rrsig: &AnswerSection,
+    dnskey: &RData,
+    now: u32,
+) -> Result<(), DnssecError> {
+    let RData::RRSIG {
+        type_covered,
+        algorithm,
+        labels,
+        original_ttl,
+        expiration,
+        inception,
+        key_tag: tag,
+        signer_name,
+        signature,
+    } = &rrsig.data
+    else {
+        return Err(DnssecError::RrsigMismatch);
+    };
+    let RData::DNSKEY {
+        flags,
+        protocol,
+        algorithm: key_algorithm,
+        public_key,
+    } = dnskey
+    else {
+        return Err(DnssecError::KeyMismatch);
+    };
+
+    // RRSIG must cover the RRset (RFC 4035 section 5.3.1)
+    let Some(first) = rrset.first() else {
+        return Err(DnssecError::RrsigMismatch);
+    };
+    let owner_labels = first.name.label_count() - is_wildcard(&first.name) as usize;
+    if rrset
+        .iter()
+        .any(|x| x.name != first.name || x.rr_type != first.rr_type || x.rr_class != first.rr_class)
+        || rrsig.name != first.name
+        || rrsig.rr_class != first.rr_class
+        || *type_covered != first.rr_type
+        || !first.name.is_subdomain_of(signer_name)
+        || *labels as usize > owner_labels
+    {
+        return Err(DnssecError::RrsigMismatch);
+    }
+
+    if flags & DNSKEY_FLAG_ZONE == 0
+        || *protocol != 3
+        || key_algorithm != algorithm
+        || key_tag(dnskey) != Some(*tag)
+    {
+        return Err(DnssecError::KeyMismatch);
+    }
+
+    // Serial number arithmetic (RFC 4034 section 3.1.5)
+    if expiration.wrapping_sub(now) > i32::MAX as u32 {
+        return Err(DnssecError::SignatureExpired);
+    }
+    if now.wrapping_sub(*inception) > i32::MAX as u32 {
+        return Err(DnssecError::SignatureNotYetValid);
+    }
+
+    // Signed data (RFC 4034 section 3.1.8.1)
+    let mut data = Vec::with_capacity(512);
+    data.extend(u16::from(*type_covered).to_be_bytes());
+    data.extend([*algorithm, *labels]);
+    data.extend(original_ttl.to_be_bytes());
+    data.extend(expiration.to_be_bytes());
+    data.extend(inception.to_be_bytes());
+    data.extend(tag.to_be_bytes());
+    data.extend(name_bytes(signer_name));
+
+    // Wildcard expansion: sign the wildcard owner name (RFC 4035 section 5.3.2)
+    let mut owner = name_bytes(&first.name);
+    if (*labels as usize) < owner_labels {
+        let labels = &first.name.labels()[first.name.label_count() - *labels as usize..];
+        let wildcard = DomainName::from_labels(labels.iter().cloned())
+            .and_then(|x| x.prepend_label("*"))
+            .map_err(|_| DnssecError::RrsigMismatch)?;
+        owner = name_bytes(&wildcard);
+    }
+
+    // RRs in canonical order, without duplicates (RFC 4034 section 6.3)
+    let rdatas: BTreeSet<Vec<u8>> = rrset.iter().map(|x| rdata_bytes(&x.data)).collect();
+    for rdata in rdatas {
+        data.extend(&owner);
+        data.extend(u16::from(first.rr_type).to_be_bytes());
+        data.extend(u16::from(first.rr_class).to_be_bytes());
+        data.extend(original_ttl.to_be_bytes());
+        data.extend((rdata.len() as u16).to_be_bytes());
+        data.extend(rdata);
+    }
+
+    verify_signature(*algorithm, public_key, &data, signature)
+}
+
+fn verify_signature(
+    algorithm: u8,
+    public_key: &[u8],
+    data: &[u8],
+    signature: &[u8],
+) -> Result<(), DnssecError> {
+    let is_valid = match algorithm {
+        RSASHA1 | RSASHA1_NSEC3_SHA1 => {
+            rsa::verify(public_key, rsa::SHA1_PREFIX, &sha::sha1(data), signature)
+        }
+        RSASHA256 => rsa::verify(
+            public_key,
+            rsa::SHA256_PREFIX,
+            &sha::sha256(data),
+            signature,
+        ),
+        RSASHA512 => rsa::verify(
+            public_key,
+            rsa::SHA512_PREFIX,
+            &sha::sha512(data),
+            signature,
+        ),
+        ECDSAP256SHA256 => p256::verify(public_key, &sha::sha256(data), signature),
+        ED25519 => ed25519::verify(public_key, data, signature),
+        _ => return Err(DnssecError::UnsupportedAlgorithm(algorithm)),
+    };
+
+    if is_valid {
+        Ok(())
+    } else {
+        Err(DnssecError::BadSignature)
+    }
+}
+
+/// Hashed owner name of NSEC3 records (RFC 5155 section 5), with SHA-1.
+pub fn nsec3_hash(name: &DomainName, salt: &[u8], iterations: u16) -> Vec<u8> {
+    let mut data = name_bytes(name);
+    data.extend(salt);
+    let mut digest = sha::sha1(&data);
+
+    for _ in 0..iterations {
+        let mut data = digest.to_vec();
+        data.extend(salt);
+        digest = sha::sha1(&data);
+    }
+    digest.to_vec()
+}
+
+fn is_wildcard(name: &DomainName) -> bool {
+    name.labels().first().is_some_and(|x| x == b"*")
+}
+
+/// Canonical wire form of a name: lower-cased and uncompressed.
+fn name_bytes(name: &DomainName) -> Vec<u8> {
+    let mut encoder = Encoder::without_compression();
+    encoder
+        .encode_name(&name.to_lowercase(), false)
+        .expect("Encoding to memory cannot fail");
+    encoder.into_bytes()
+}
+
+/// Canonical wire form of RDATA (RFC 4034 section 6.2, updated by RFC 6840 section 5.1).
+fn rdata_bytes(data: &RData) -> Vec<u8> {
+    let data = match data {
+        RData::NS(name) => RData::NS(name.to_lowercase()),
+        RData::CNAME(name) => RData::CNAME(name.to_lowercase()),
+        RData::PTR(name) => RData::PTR(name.to_lowercase()),
+        RData::MX {
+            preference,
+            exchange,
+        } => RData::MX {
+            preference: *preference,
+            exchange: exchange.to_lowercase(),
+        },
+        RData::SOA {
+            mname,
+            rname,
+            serial,
+            refresh,
+            retry,
+            expire,
+            minimum,
+        } => RData::SOA {
+            mname: mname.to_lowercase(),
+            rname: rname.to_lowercase(),
+            serial: *serial,
+            refresh: *refresh,
+            retry: *retry,
+            expire: *expire,
+            minimum: *minimum,
+        },
+        RData::SRV {
+            priority,
+            weight,
+            port,
+            target,
+        } => RData::SRV {
+            priority: *priority,
+            weight: *weight,
+            port: *port,
+            target: target.to_lowercase(),
+        },
+        RData::RRSIG { .. } => {
+            let mut data = data.clone();
+            if let RData::RRSIG { signer_name, .. } = &mut data {
+                *signer_name = signer_name.to_lowercase();
+            }
+            data
+        }
+        _ => data.clone(),
+    };
+
+    let mut encoder = Encoder::without_compression();
+    data.encode_into(&mut encoder)
+        .expect("Encoding to memory cannot fail");
+    encoder.into_bytes()
+}
+
+/// Check if a record type is set in NSEC or NSEC3 data.
+fn has_type(data: &RData, rr_type: ResourceRecordType) -> bool {
+    match data {
+        RData::NSEC { types, .. } | RData::NSEC3 { types, .. } => types.contains(&rr_type),
+        _ => false,
+    }
+}
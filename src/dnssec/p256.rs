@@ -0,0 +1,182 @@
+//! ECDSA signature verification over curve P-256 (FIPS 186-4, RFC 6605).
+
+use super::bigint::{BigUint, Modulus};
+use crate::message::codec::decode_hex;
+
+const P: &str = "ffffffff00000001000000000000000000000000ffffffffffffffffffffffff";
+const N: &str = "ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551";
+const B: &str = "5ac635d8aa3a93e7b3ebbd55769886bc651d06b0cc53b0f63bce3c3e27d2604b";
+const GX: &str = "6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296";
+const GY: &str = "4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5";
+
+fn constant(hex: &str) -> BigUint {
+    BigUint::from_be_bytes(&decode_hex(hex).unwrap())
+}
+
+/// Point in Jacobian coordinates, in Montgomery form. `z == 0` for infinity.
+#[derive(Debug, Clone)]
+struct Point {
+    x: BigUint,
+    y: BigUint,
+    z: BigUint,
+}
+
+struct Curve {
+    field: Modulus,
+}
+
+impl Curve {
+    fn new() -> Self {
+        Self {
+            field: Modulus::new(&constant(P)),
+        }
+    }
+
+    fn infinity(&self) -> Point {
+        Point {
+            x: self.field.one(),
+            y: self.field.one(),
+            z: self.field.zero(),
+        }
+    }
+
+    /// Point from affine coordinates, if it is on the curve.
+    fn point(&self, x: &BigUint, y: &BigUint) -> Option<Point> {
+        let f = &self.field;
+        if x >= f.value() || y >= f.value() {
+            return None;
+        }
+        let (x, y) = (f.enter_mont(x), f.enter_mont(y));
+
+        // y^2 = x^3 - 3x + b
+        let three = f.enter_mont(&BigUint::from_u64(3));
+        let rhs = f.mul(&f.square(&x), &x);
+        let rhs = f.sub(&rhs, &f.mul(&three, &x));
+        let rhs = f.add(&rhs, &f.enter_mont(&constant(B)));
+        (f.square(&y) == rhs).then(|| Point { x, y, z: f.one() })
+    }
+
+    fn double(&self, p: &Point) -> Point {
+        let f = &self.field;
+        if p.z.is_zero() || p.y.is_zero() {
+            return self.infinity();
+        }
+
+        // dbl-2001-b, for a = -3
+        let delta = f.square(&p.z);
+        let gamma = f.square(&p.y);
+        let beta = f.mul(&p.x, &gamma);
+        let alpha = f.mul(&f.sub(&p.x, &delta), &f.add(&p.x, &delta));
+        let alpha = f.add(&f.add(&alpha, &alpha), &alpha);
+
+        let beta4 = f.add(&beta, &beta);
+        let beta4 = f.add(&beta4, &beta4);
+        let x = f.sub(&f.square(&alpha), &f.add(&beta4, &beta4));
+        let z = f.sub(&f.sub(&f.square(&f.add(&p.y, &p.z)), &gamma), &delta);
+
+        let gamma2 = f.square(&gamma);
+        let gamma8 = f.add(&gamma2, &gamma2);
+        let gamma8 = f.add(&gamma8, &gamma8);
+        let gamma8 = f.add(&gamma8, &gamma8);
+        let y = f.sub(&f.mul(&alpha, &f.sub(&beta4, &x)), &gamma8);
+
+        Point { x, y, z }
+    }
+
+    fn add(&self, p: &Point, q: &Point) -> Point {
+        let f = &self.field;
+        if p.z.is_zero() {
+            return q.clone();
+        }
+        if q.z.is_zero() {
+            return p.clone();
+        }
+
+        // add-2007-bl
+        let z1z1 = f.square(&p.z);
+        let z2z2 = f.square(&q.z);
+        let u1 = f.mul(&p.x, &z2z2);
+        let u2 = f.mul(&q.x, &z1z1);
+        let s1 = f.mul(&f.mul(&p.y, &q.z), &z2z2);
+        let s2 = f.mul(&f.mul(&q.y, &p.z), &z1z1);
+
+        let h = f.sub(&u2, &u1);
+        let r = f.sub(&s2, &s1);
+        if h.is_zero() {
+            return if r.is_zero() {
+                self.double(p)
+            } else {
+                self.infinity()
+            };
+        }
+        let r = f.add(&r, &r);
+
+        let i = f.square(&f.add(&h, &h));
+        let j = f.mul(&h, &i);
+        let v = f.mul(&u1, &i);
+
+        let x = f.sub(&f.sub(&f.square(&r), &j), &f.add(&v, &v));
+        let s1j = f.mul(&s1, &j);
+        let y = f.sub(&f.mul(&r, &f.sub(&v, &x)), &f.add(&s1j, &s1j));
+        let z = f.sub(&f.sub(&f.square(&f.add(&p.z, &q.z)), &z1z1), &z2z2);
+        let z = f.mul(&z, &h);
+
+        Point { x, y, z }
+    }
+
+    /// `a * p + b * q`, sharing doublings (Shamir's trick).
+    fn double_mul(&self, a: &BigUint, p: &Point, b: &BigUint, q: &Point) -> Point {
+        let pq = self.add(p, q);
+        let mut output = self.infinity();
+        for idx in (0..a.bits().max(b.bits())).rev() {
+            output = self.double(&output);
+            match (a.bit(idx), b.bit(idx)) {
+                (true, true) => output = self.add(&output, &pq),
+                (true, false) => output = self.add(&output, p),
+                (false, true) => output = self.add(&output, q),
+                (false, false) => {}
+            }
+        }
+        output
+    }
+
+    /// Affine x coordinate, out of Montgomery form.
+    fn affine_x(&self, p: &Point) -> BigUint {
+        let f = &self.field;
+        let z_inv = f.inv(&p.z);
+        f.leave_mont(&f.mul(&p.x, &f.square(&z_inv)))
+    }
+}
+
+/// Verify signature (`r || s`) of a SHA-256 `digest` with public key (`x || y`).
+pub(crate) fn verify(public_key: &[u8], digest: &[u8; 32], signature: &[u8]) -> bool {
+    if public_key.len() != 64 || signature.len() != 64 {
+        return false;
+    }
+
+    let curve = Curve::new();
+    let Some(q) = curve.point(
+        &BigUint::from_be_bytes(&public_key[..32]),
+        &BigUint::from_be_bytes(&public_key[32..]),
+    ) else {
+        return false;
+    };
+    let g = curve.point(&constant(GX), &constant(GY)).unwrap();
+
+    let order = Modulus::new(&constant(N));
+    let r = BigUint::from_be_bytes(&signature[..32]);
+    let s = BigUint::from_be_bytes(&signature[32..]);
+    if r.is_zero() || s.is_zero() || r >= *order.value() || s >= *order.value() {
+        return false;
+    }
+
+    let w = order.inv(&order.enter_mont(&s));
+    let u1 = order.leave_mont(&order.mul(&order.enter_mont(&BigUint::from_be_bytes(digest)), &w));
+    let u2 = order.leave_mont(&order.mul(&order.enter_mont(&r), &w));
+
+    let point = curve.double_mul(&u1, &g, &u2, &q);
+    if point.z.is_zero() {
+        return false;
+    }
+    curve.affine_x(&point).rem(order.value()) == r
+}
@@ -0,0 +1,59 @@
+//! RSASSA-PKCS1-v1_5 signature verification (RFC 8017 section 8.2.2, RFC 3110).
+
+use super::bigint::{BigUint, Modulus};
+
+/// DER encoded `DigestInfo` prefix, per hash function.
+pub(crate) const SHA1_PREFIX: &[u8] = &[
+    0x30, 0x21, 0x30, 0x09, 0x06, 0x05, 0x2b, 0x0e, 0x03, 0x02, 0x1a, 0x05, 0x00, 0x04, 0x14,
+];
+pub(crate) const SHA256_PREFIX: &[u8] = &[
+    0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05,
+    0x00, 0x04, 0x20,
+];
+pub(crate) const SHA512_PREFIX: &[u8] = &[
+    0x30, 0x51, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03, 0x05,
+    0x00, 0x04, 0x40,
+];
+
+/// Split DNSKEY public key in (exponent, modulus) (RFC 3110 section 2).
+fn parse_key(public_key: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (len, rest) = match public_key {
+        [0, high, low, rest @ ..] => (u16::from_be_bytes([*high, *low]) as usize, rest),
+        [len, rest @ ..] => (*len as usize, rest),
+        [] => return None,
+    };
+    (len > 0 && rest.len() > len).then(|| rest.split_at(len))
+}
+
+/// Verify `signature` of a `digest`, `prefix` identifying the hash function.
+pub(crate) fn verify(public_key: &[u8], prefix: &[u8], digest: &[u8], signature: &[u8]) -> bool {
+    let Some((exponent, modulus)) = parse_key(public_key) else {
+        return false;
+    };
+    let modulus_value = BigUint::from_be_bytes(modulus);
+    if signature.len() != modulus.len() || !modulus_value.is_odd() {
+        return false;
+    }
+
+    let s = BigUint::from_be_bytes(signature);
+    if s >= modulus_value {
+        return false;
+    }
+
+    let field = Modulus::new(&modulus_value);
+    let m = field.leave_mont(&field.pow(&field.enter_mont(&s), &BigUint::from_be_bytes(exponent)));
+
+    // EM = 0x00 || 0x01 || PS (0xFF...) || 0x00 || DigestInfo
+    let len = modulus.len();
+    let padding_len = len.saturating_sub(3 + prefix.len() + digest.len());
+    if padding_len < 8 {
+        return false;
+    }
+    let mut expected = vec![0x00, 0x01];
+    expected.resize(2 + padding_len, 0xFF);
+    expected.push(0x00);
+    expected.extend(prefix);
+    expected.extend(digest);
+
+    m.to_be_bytes(len) == expected
+}
@@ -0,0 +1,406 @@
+//! Chain of trust building, from a trust anchor down to the answer.
+
+use std::collections::HashMap;
+
+use crate::{
+    client::DnsClient,
+    message::{
+        AnswerSection, DomainName, Message, QuestionSection, RData, ResourceRecordType,
+        ResponseCode,
+    },
+    DnsError, DnssecError,
+};
+
+use super::{
+    denial::{self, DsDenial, Proof},
+    ds_matches, is_supported, key_tag, verify_rrsig, TrustAnchor, ValidationStatus,
+};
+
+/// Reason to stop validating: a final status or a query failure.
+enum Stop {
+    Status(ValidationStatus),
+    Error(DnsError),
+}
+
+impl From<DnsError> for Stop {
+    fn from(value: DnsError) -> Self {
+        Self::Error(value)
+    }
+}
+
+impl From<DnssecError> for Stop {
+    fn from(value: DnssecError) -> Self {
+        Self::Status(ValidationStatus::Bogus(value))
+    }
+}
+
+type Step<T> = Result<T, Stop>;
+
+/// Validate responses, querying DNSKEY and DS records as needed.
+pub(crate) struct Validator<'a> {
+    client: &'a mut DnsClient,
+    anchor: TrustAnchor,
+    /// Validation time, in seconds since epoch.
+    now: u32,
+    /// Validated DNSKEY data (or final status) per zone.
+    keys: HashMap<DomainName, Result<Vec<RData>, ValidationStatus>>,
+}
+
+impl<'a> Validator<'a> {
+    pub(crate) fn new(client: &'a mut DnsClient, anchor: TrustAnchor, now: u32) -> Self {
+        Self {
+            client,
+            anchor,
+            now,
+            keys: HashMap::new(),
+        }
+    }
+
+    /// Validate the response to a question.
+    pub(crate) fn validate(
+        &mut self,
+        question: &QuestionSection,
+        response: &Message,
+    ) -> Result<ValidationStatus, DnsError> {
+        match self.validate_response(question, response) {
+            Ok(()) => Ok(ValidationStatus::Secure),
+            Err(Stop::Status(status)) => Ok(status),
+            Err(Stop::Error(e)) => Err(e),
+        }
+    }
+
+    fn validate_response(&mut self, question: &QuestionSection, response: &Message) -> Step<()> {
+        let mut is_insecure = false;
+        let mut wildcards = vec![];
+
+        for rrset in rrsets(&response.answers) {
+            match self.validate_rrset(&rrset, &response.answers) {
+                Ok(Some(encloser)) => wildcards.push((rrset[0].name.clone(), encloser)),
+                Ok(None) => {}
+                Err(Stop::Status(ValidationStatus::Insecure)) => is_insecure = true,
+                Err(e) => return Err(e),
+            }
+        }
+
+        // Follow CNAME chain to the final name.
+        let mut target = question.name.clone();
+        if question.rr_type != ResourceRecordType::CNAME {
+            while let Some(RData::CNAME(next)) = response
+                .answers
+                .iter()
+                .find(|x| x.name == target && x.rr_type == ResourceRecordType::CNAME)
+                .map(|x| &x.data)
+            {
+                if *next == question.name {
+                    break;
+                }
+                target = next.clone();
+            }
+        }
+        let is_answered = question.rr_type == ResourceRecordType::ANY
+            || response
+                .answers
+                .iter()
+                .any(|x| x.name == target && x.rr_type == question.rr_type);
+
+        if !wildcards.is_empty() || !is_answered {
+            let records = self.denial_records(&target, response)?;
+
+            for (name, encloser) in &wildcards {
+                check_proof(denial::wildcard_answer(name, encloser, &records))?;
+            }
+            if !is_answered {
+                let proof = if response.response_code() == ResponseCode::NonExistentDomain {
+                    denial::name_error(&target, &records)
+                } else {
+                    denial::no_data(&target, question.rr_type, &records)
+                };
+                check_proof(proof)?;
+            }
+        }
+
+        if is_insecure {
+            return Err(Stop::Status(ValidationStatus::Insecure));
+        }
+        Ok(())
+    }
+
+    /// Validate an RRset with its signatures from `section`.
+    ///
+    /// Return the closest encloser if the RRset was expanded from a wildcard.
+    fn validate_rrset(
+        &mut self,
+        rrset: &[AnswerSection],
+        section: &[AnswerSection],
+    ) -> Step<Option<DomainName>> {
+        let first = &rrset[0];
+        let rrsigs: Vec<&AnswerSection> = section
+            .iter()
+            .filter(|x| {
+                x.name == first.name
+                    && x.rr_class == first.rr_class
+                    && matches!(x.data, RData::RRSIG { type_covered, .. } if type_covered == first.rr_type)
+            })
+            .collect();
+        if rrsigs.is_empty() {
+            return Err(self.unsigned(&first.name));
+        }
+
+        let mut error = DnssecError::MissingSignature;
+        for rrsig in rrsigs {
+            let RData::RRSIG {
+                signer_name,
+                labels,
+                ..
+            } = &rrsig.data
+            else {
+                continue;
+            };
+
+            let keys = match self.zone_keys(signer_name) {
+                Ok(keys) => keys,
+                Err(Stop::Status(ValidationStatus::Bogus(e))) => {
+                    error = e;
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+
+            for key in &keys {
+                match verify_rrsig(rrset, rrsig, key, self.now) {
+                    Ok(()) => return Ok(closest_encloser(&first.name, *labels)),
+                    Err(DnssecError::KeyMismatch) => {}
+                    Err(e) => error = e,
+                }
+            }
+        }
+        Err(error.into())
+    }
+
+    /// Validated DNSKEY data of a zone.
+    fn zone_keys(&mut self, zone: &DomainName) -> Step<Vec<RData>> {
+        if let Some(keys) = self.keys.get(zone) {
+            return keys.clone().map_err(Stop::Status);
+        }
+
+        // Break loops between zones claiming to sign each other.
+        self.keys.insert(
+            zone.clone(),
+            Err(ValidationStatus::Bogus(DnssecError::NoTrustedKey)),
+        );
+
+        let keys = self.fetch_zone_keys(zone);
+        match &keys {
+            Ok(keys) => {
+                self.keys.insert(zone.clone(), Ok(keys.clone()));
+            }
+            Err(Stop::Status(status)) => {
+                self.keys.insert(zone.clone(), Err(status.clone()));
+            }
+            Err(Stop::Error(_)) => {
+                self.keys.remove(zone);
+            }
+        }
+        keys
+    }
+
+    fn fetch_zone_keys(&mut self, zone: &DomainName) -> Step<Vec<RData>> {
+        if !zone.is_subdomain_of(&self.anchor.zone) {
+            return Err(Stop::Status(ValidationStatus::Insecure));
+        }
+
+        let trusted = if *zone == self.anchor.zone {
+            self.anchor.records.clone()
+        } else {
+            match self.delegation_signer(zone)? {
+                Some(ds) => ds,
+                None => return Err(DnssecError::NoTrustedKey.into()),
+            }
+        };
+
+        // Zone is treated as unsigned if no algorithm is supported (RFC 4035 section 5.2).
+        if !trusted.iter().any(is_supported) {
+            return Err(Stop::Status(ValidationStatus::Insecure));
+        }
+
+        let response = self.query(zone, ResourceRecordType::DNSKEY)?;
+        let dnskeys: Vec<AnswerSection> = response
+            .answers
+            .iter()
+            .filter(|x| x.name == *zone && x.rr_type == ResourceRecordType::DNSKEY)
+            .cloned()
+            .collect();
+
+        let mut error = DnssecError::NoTrustedKey;
+        for key in &dnskeys {
+            let is_trusted = trusted.iter().any(|x| match x {
+                RData::DS { .. } => ds_matches(zone, x, &key.data),
+                _ => *x == key.data,
+            });
+            if !is_trusted {
+                continue;
+            }
+
+            // DNSKEY RRset must be signed by a trusted key.
+            let tag = key_tag(&key.data);
+            for rrsig in response.answers.iter().filter(|x| {
+                x.name == *zone
+                    && matches!(&x.data, RData::RRSIG { type_covered: ResourceRecordType::DNSKEY, key_tag, signer_name, .. }
+                        if Some(*key_tag) == tag && signer_name == zone)
+            }) {
+                match verify_rrsig(&dnskeys, rrsig, &key.data, self.now) {
+                    Ok(()) => return Ok(dnskeys.into_iter().map(|x| x.data).collect()),
+                    Err(e) => error = e,
+                }
+            }
+        }
+        Err(error.into())
+    }
+
+    /// Validated DS data of a zone, `None` if name is not a delegation point.
+    fn delegation_signer(&mut self, zone: &DomainName) -> Step<Option<Vec<RData>>> {
+        let response = self.query(zone, ResourceRecordType::DS)?;
+        let ds: Vec<AnswerSection> = response
+            .answers
+            .iter()
+            .filter(|x| x.name == *zone && x.rr_type == ResourceRecordType::DS)
+            .cloned()
+            .collect();
+
+        // DS records are signed by the parent zone.
+        if !ds.is_empty() {
+            let section: Vec<AnswerSection> = response
+                .answers
+                .iter()
+                .filter(
+                    |x| !matches!(&x.data, RData::RRSIG { signer_name, .. } if signer_name == zone),
+                )
+                .cloned()
+                .collect();
+            self.validate_rrset(&ds, &section)?;
+            return Ok(Some(ds.into_iter().map(|x| x.data).collect()));
+        }
+
+        let parent = zone.parent().unwrap_or_default();
+        let records = self.denial_records(&parent, &response)?;
+        match denial::ds_denial(zone, &records) {
+            DsDenial::NotDelegation => Ok(None),
+            DsDenial::InsecureDelegation => Err(Stop::Status(ValidationStatus::Insecure)),
+            DsDenial::Missing => Err(DnssecError::MissingDenial.into()),
+        }
+    }
+
+    /// Validated NSEC and NSEC3 records from the authority section.
+    ///
+    /// If none is signed, `name` must be in an unsigned zone.
+    fn denial_records(
+        &mut self,
+        name: &DomainName,
+        response: &Message,
+    ) -> Step<Vec<AnswerSection>> {
+        let mut records = vec![];
+        let mut is_signed = false;
+
+        for rrset in rrsets(&response.authorities) {
+            let rr_type = rrset[0].rr_type;
+            let is_denial = matches!(
+                rr_type,
+                ResourceRecordType::NSEC | ResourceRecordType::NSEC3
+            );
+            let has_rrsig = response.authorities.iter().any(|x| {
+                x.name == rrset[0].name
+                    && matches!(x.data, RData::RRSIG { type_covered, .. } if type_covered == rr_type)
+            });
+            if !has_rrsig || !(is_denial || rr_type == ResourceRecordType::SOA) {
+                continue;
+            }
+
+            self.validate_rrset(&rrset, &response.authorities)?;
+            is_signed = true;
+            if is_denial {
+                records.extend(rrset);
+            }
+        }
+
+        if !is_signed {
+            return Err(match self.unsigned(name) {
+                Stop::Status(ValidationStatus::Bogus(_)) => DnssecError::MissingDenial.into(),
+                other => other,
+            });
+        }
+        Ok(records)
+    }
+
+    /// Status of unsigned data: insecure if there is an unsigned delegation
+    /// between the trust anchor and `name`, bogus otherwise.
+    fn unsigned(&mut self, name: &DomainName) -> Stop {
+        if !name.is_subdomain_of(&self.anchor.zone) {
+            return Stop::Status(ValidationStatus::Insecure);
+        }
+
+        // Walk down from the trust anchor.
+        let mut candidates: Vec<DomainName> = std::iter::once(name.clone())
+            .chain(name.parents())
+            .take_while(|x| x.label_count() > self.anchor.zone.label_count())
+            .collect();
+        candidates.reverse();
+
+        for candidate in candidates {
+            if let Err(stop) = self.delegation_signer(&candidate) {
+                return stop;
+            }
+        }
+        DnssecError::MissingSignature.into()
+    }
+
+    fn query(&mut self, name: &DomainName, rr_type: ResourceRecordType) -> Step<Message> {
+        let question = QuestionSection::new(name.clone(), rr_type);
+        let response = self.client.send_query(DnsClient::new_query(&question))?;
+
+        match response.response_code() {
+            ResponseCode::NoError | ResponseCode::NonExistentDomain => Ok(response),
+            code => Err(DnsError::ErrorResponse(code).into()),
+        }
+    }
+}
+
+/// Group records by owner, class and type, dropping RRSIG and OPT records.
+fn rrsets(records: &[AnswerSection]) -> Vec<Vec<AnswerSection>> {
+    let mut rrsets: Vec<Vec<AnswerSection>> = vec![];
+    for record in records {
+        if matches!(
+            record.rr_type,
+            ResourceRecordType::RRSIG | ResourceRecordType::OPT
+        ) {
+            continue;
+        }
+
+        match rrsets.iter_mut().find(|x| {
+            x[0].name == record.name
+                && x[0].rr_type == record.rr_type
+                && x[0].rr_class == record.rr_class
+        }) {
+            Some(rrset) => rrset.push(record.clone()),
+            None => rrsets.push(vec![record.clone()]),
+        }
+    }
+    rrsets
+}
+
+/// Closest encloser of a wildcard expanded name, from RRSIG labels field.
+fn closest_encloser(name: &DomainName, labels: u8) -> Option<DomainName> {
+    let is_wildcard = name.labels().first().is_some_and(|x| x == b"*");
+    let count = name.label_count() - is_wildcard as usize;
+    if labels as usize >= count {
+        return None;
+    }
+    name.parents().find(|x| x.label_count() == labels as usize)
+}
+
+fn check_proof(proof: Proof) -> Step<()> {
+    match proof {
+        Proof::Proven => Ok(()),
+        Proof::OptOut | Proof::Insecure => Err(Stop::Status(ValidationStatus::Insecure)),
+        Proof::Missing => Err(DnssecError::MissingDenial.into()),
+    }
+}
@@ -1,30 +1,265 @@
-use std::io;
+use std::{fmt, io};
 
+use nom::error::{Error, ErrorKind};
 use thiserror::Error;
 
+use crate::message::{ResourceRecordType, ResponseCode};
+
 #[derive(Debug, Error)]
 pub enum DnsError {
     #[error("I/O: {0}")]
     Io(String),
 
     #[error("Parse error: {0}")]
-    Parse(String),
+    Parse(#[from] ParseError),
 
     #[error("Empty response")]
     EmptyResponse,
 
+    #[error("Timed out")]
+    Timeout,
+
     #[error("Invalid response: {0}")]
     InvalidResponse(&'static str),
+
+    #[error("Error response: {0:?}")]
+    ErrorResponse(ResponseCode),
+
+    #[error("Invalid domain name: {0}")]
+    DomainName(#[from] DomainNameError),
+
+    #[error("TSIG: {0}")]
+    Tsig(#[from] TsigError),
 }
 
-impl From<io::Error> for DnsError {
-    fn from(value: io::Error) -> Self {
-        Self::Io(value.to_string())
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Error)]
+pub enum DomainNameError {
+    #[error("empty label")]
+    EmptyLabel,
+
+    #[error("label is {0} bytes long (max 63)")]
+    LabelTooLong(usize),
+
+    #[error("name is longer than 255 bytes")]
+    NameTooLong,
+
+    #[error("bad escape sequence")]
+    BadEscape,
+
+    #[error("invalid internationalized label")]
+    InvalidIdna,
+}
+
+/// Error met while parsing presentation (zone file) format.
+#[derive(Debug, PartialEq, Eq, Clone, Error)]
+pub enum PresentationError {
+    #[error("missing {0}")]
+    MissingField(&'static str),
+
+    #[error("invalid {0}: {1:?}")]
+    InvalidField(&'static str, String),
+
+    #[error("unexpected data: {0:?}")]
+    TrailingData(String),
+
+    #[error("unterminated quoted string")]
+    UnterminatedQuote,
+
+    #[error("{0} RDATA is only supported in generic format")]
+    GenericFormatRequired(ResourceRecordType),
+
+    #[error("invalid domain name: {0}")]
+    DomainName(#[from] DomainNameError),
+}
+
+/// Error met while reading the JSON form of a message (RFC 8427).
+#[derive(Debug, PartialEq, Eq, Clone, Error)]
+pub enum JsonError {
+    #[error("JSON syntax error at offset {0}")]
+    Syntax(usize),
+
+    #[error("missing member {0:?}")]
+    MissingMember(&'static str),
+
+    #[error("invalid member {0:?}")]
+    InvalidMember(&'static str),
+
+    #[error("invalid message octets: {0}")]
+    Message(#[from] ParseError),
+
+    #[error("invalid presentation data: {0}")]
+    Presentation(#[from] PresentationError),
+}
+
+/// Reason why DNSSEC data could not be validated.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Error)]
+pub enum DnssecError {
+    #[error("unsupported algorithm {0}")]
+    UnsupportedAlgorithm(u8),
+
+    #[error("unsupported digest type {0}")]
+    UnsupportedDigest(u8),
+
+    #[error("signature does not match RRset")]
+    BadSignature,
+
+    #[error("signature expired")]
+    SignatureExpired,
+
+    #[error("signature not yet valid")]
+    SignatureNotYetValid,
+
+    #[error("RRSIG does not cover RRset")]
+    RrsigMismatch,
+
+    #[error("DNSKEY does not match RRSIG")]
+    KeyMismatch,
+
+    #[error("missing signature")]
+    MissingSignature,
+
+    #[error("no DNSKEY matches trusted DS records")]
+    NoTrustedKey,
+
+    #[error("missing proof of non-existence")]
+    MissingDenial,
+}
+
+/// Reason why a TSIG signed message was rejected (RFC 8945 section 5.2).
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Error)]
+pub enum TsigError {
+    #[error("message is not signed")]
+    Unsigned,
+
+    #[error("malformed TSIG record")]
+    Malformed,
+
+    #[error("unknown key or algorithm (BADKEY)")]
+    BadKey,
+
+    #[error("MAC does not match (BADSIG)")]
+    BadSig,
+
+    #[error("time signed is out of the fudge window (BADTIME)")]
+    BadTime,
+
+    #[error("MAC is shorter than allowed (BADTRUNC)")]
+    BadTrunc,
+}
+
+/// Error met while parsing a message, located in the input.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Error)]
+#[error("{kind} in {section} section (item {index}) at offset {offset}")]
+pub struct ParseError {
+    pub section: MessageSection,
+    /// Index of the item in its section (0 for the header).
+    pub index: usize,
+    /// Offset from message start.
+    pub offset: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum MessageSection {
+    Header,
+    Question,
+    Answer,
+    Authority,
+    Additional,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Error)]
+pub enum ParseErrorKind {
+    #[error("truncated header")]
+    TruncatedHeader,
+
+    #[error("unexpected end of message")]
+    Truncated,
+
+    #[error("section count exceeds message content")]
+    CountMismatch,
+
+    #[error("bad label length")]
+    BadLabelLength,
+
+    #[error("bad compression pointer")]
+    BadPointer,
+
+    #[error("name is longer than 255 bytes")]
+    NameTooLong,
+
+    #[error("RDLENGTH overruns message")]
+    RdLengthOverrun,
+
+    #[error("malformed RDATA")]
+    BadRData,
+}
+
+impl ParseError {
+    /// Locate a nom error from the message parsers.
+    ///
+    /// `msg_input` is the full message, the error input must be a slice of it.
+    pub(crate) fn new(
+        section: MessageSection,
+        index: usize,
+        msg_input: &[u8],
+        error: nom::Err<Error<&[u8]>>,
+    ) -> Self {
+        let (offset, kind) = match error {
+            nom::Err::Error(e) | nom::Err::Failure(e) => {
+                let offset = (e.input.as_ptr() as usize)
+                    .checked_sub(msg_input.as_ptr() as usize)
+                    .map_or(msg_input.len(), |x| x.min(msg_input.len()));
+                (offset, ParseErrorKind::from(e.code))
+            }
+            nom::Err::Incomplete(_) => (msg_input.len(), ParseErrorKind::Truncated),
+        };
+
+        let kind = match (section, kind) {
+            (MessageSection::Header, _) => ParseErrorKind::TruncatedHeader,
+            (_, kind) => kind,
+        };
+
+        Self {
+            section,
+            index,
+            offset,
+            kind,
+        }
     }
 }
 
-impl<E> From<nom::Err<E>> for DnsError {
-    fn from(_value: nom::Err<E>) -> Self {
-        Self::Parse("unknown error".to_string())
+impl From<ErrorKind> for ParseErrorKind {
+    /// Map error kinds used by the message parsers.
+    fn from(value: ErrorKind) -> Self {
+        match value {
+            ErrorKind::Eof => Self::Truncated,
+            ErrorKind::Tag => Self::BadLabelLength,
+            ErrorKind::Verify => Self::BadPointer,
+            ErrorKind::TooLarge => Self::NameTooLong,
+            ErrorKind::LengthValue => Self::RdLengthOverrun,
+            _ => Self::BadRData,
+        }
+    }
+}
+
+impl fmt::Display for MessageSection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Header => "header",
+            Self::Question => "question",
+            Self::Answer => "answer",
+            Self::Authority => "authority",
+            Self::Additional => "additional",
+        })
+    }
+}
+
+impl From<io::Error> for DnsError {
+    fn from(value: io::Error) -> Self {
+        match value.kind() {
+            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => Self::Timeout,
+            _ => Self::Io(value.to_string()),
+        }
     }
 }
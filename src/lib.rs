@@ -1,6 +1,13 @@
 mod error;
 
+pub mod cache;
 pub mod client;
+pub mod cookie;
+pub mod dnssec;
 pub mod message;
+pub mod tsig;
 
-pub use error::DnsError;
+pub use error::{
+    DnsError, DnssecError, DomainNameError, JsonError, MessageSection, ParseError, ParseErrorKind,
+    PresentationError, TsigError,
+};
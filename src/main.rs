@@ -1,24 +1,63 @@
 use std::{
     env, io,
-    net::{SocketAddr, UdpSocket},
+    net::{IpAddr, SocketAddr, UdpSocket},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
-use dns_starter_rust::{client::DnsClient, message::*};
+use dns_starter_rust::{
+    cache::Cache,
+    client::DnsClient,
+    cookie::{CookieCheck, CookieSecrets},
+    dnssec::{TrustAnchor, ValidationStatus},
+    message::*,
+    tsig::{self, KeyStore},
+    DnsError, DomainNameError, PresentationError,
+};
+
+/// Largest UDP payload the server accepts and advertises with EDNS.
+const MAX_UDP_PAYLOAD_SIZE: u16 = 4096;
+
+/// How long to wait for upstream responses.
+const UPSTREAM_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a server cookie secret is used, at least one hour (RFC 9018 section 4.3).
+const COOKIE_SECRET_LIFETIME: Duration = Duration::from_secs(24 * 3600);
 
 fn main() -> io::Result<()> {
     let resolver_addr = parse_cli_resolver().expect("Missing or bad '--resolver' argument");
     println!("Using resolver: {resolver_addr:?}");
+    let keys = parse_cli_tsig_keys().expect("Bad '--tsig-key' argument");
+    let ecs_policy = parse_cli_ecs_policy().expect("Bad '--ecs' argument");
+    let blocked_names = parse_cli_blocked_names().expect("Bad '--block' argument");
+    let trust_anchor = parse_cli_trust_anchor().expect("Bad '--trust-anchor' argument");
 
     let mut dns_client = DnsClient::connect("0.0.0.0:2054", resolver_addr)?;
+    dns_client.set_timeout(Some(UPSTREAM_TIMEOUT))?;
+    dns_client.set_trust_anchor(trust_anchor);
+
+    let mut server = Server {
+        dns_client,
+        keys,
+        cookie_secrets: CookieSecrets::new(new_cookie_secret()),
+        cookie_secret_time: Instant::now(),
+        ecs_policy,
+        cache: Cache::new(),
+        blocked_names,
+    };
     let udp_socket = UdpSocket::bind("127.0.0.1:2053")?;
-    let mut buf = [0; 512];
+    let mut buf = [0; MAX_UDP_PAYLOAD_SIZE as usize];
 
     loop {
+        server.rotate_cookie_secret();
+
         match udp_socket.recv_from(&mut buf) {
-            Ok((size, source)) => {
-                let response = handle_query(&buf[..size], &mut dns_client);
-                send_response(&udp_socket, &source, &response)?;
-            }
+            Ok((size, source)) => match server.handle_query(&buf[..size], source.ip()) {
+                Ok(Some((response, max_size))) => {
+                    send_response(&udp_socket, &source, &response, max_size)?
+                }
+                Ok(None) => {}
+                Err(e) => eprintln!("Error handling query from {source}: {e}"),
+            },
             Err(e) => {
                 eprintln!("Error receiving data: {}", e);
                 break;
@@ -35,54 +74,349 @@ fn parse_cli_resolver() -> Option<SocketAddr> {
     addr.parse().ok()
 }
 
-fn handle_query(input: &[u8], dns_client: &mut DnsClient) -> Message {
-    let (_, query) = Message::parse(input).expect("Invalid DNS query");
-    println!("query: {query:?}");
-
-    Message {
-        header: Header {
-            id: query.header.id,
-            flags: HeaderFlags {
-                qr: QrFlag::Reply,
-                opcode: query.header.flags.opcode,
-                is_authoritative_answer: false,
-                is_truncation: false,
-                is_recursion_desired: query.header.flags.is_recursion_desired,
-                is_recursion_available: false,
-                response_code: if query.header.flags.opcode == OpCode::Query {
-                    ResponseCode::NoError
-                } else {
-                    ResponseCode::NotImplemented
-                },
+/// TSIG keys of clients, from `--tsig-key [algorithm:]name:secret` arguments.
+fn parse_cli_tsig_keys() -> Result<KeyStore, PresentationError> {
+    let args: Vec<String> = env::args().collect();
+    args.windows(2)
+        .filter(|x| x[0] == "--tsig-key")
+        .map(|x| x[1].parse())
+        .collect()
+}
+
+/// Client subnet policy, from `--ecs strip|pass|synthesize` argument (strip by default).
+fn parse_cli_ecs_policy() -> Option<EcsPolicy> {
+    let Some(index) = env::args().position(|x| x == "--ecs") else {
+        return Some(EcsPolicy::Strip);
+    };
+    match env::args().nth(index + 1)?.as_str() {
+        "strip" => Some(EcsPolicy::Strip),
+        "pass" => Some(EcsPolicy::PassThrough),
+        "synthesize" => Some(EcsPolicy::Synthesize),
+        _ => None,
+    }
+}
+
+/// Names blocked along with their subdomains, from `--block name` arguments.
+fn parse_cli_blocked_names() -> Result<Vec<DomainName>, DomainNameError> {
+    let args: Vec<String> = env::args().collect();
+    args.windows(2)
+        .filter(|x| x[0] == "--block")
+        .map(|x| DomainName::from_unicode(&x[1]))
+        .collect()
+}
+
+/// DNSSEC trust anchor, from `--trust-anchor record` arguments: DS or
+/// DNSKEY records of a single zone, in presentation format.
+fn parse_cli_trust_anchor() -> Result<Option<TrustAnchor>, PresentationError> {
+    let args: Vec<String> = env::args().collect();
+    let records = args
+        .windows(2)
+        .filter(|x| x[0] == "--trust-anchor")
+        .map(|x| x[1].parse())
+        .collect::<Result<Vec<AnswerSection>, _>>()?;
+
+    let Some(zone) = records.first().map(|x| x.name.clone()) else {
+        return Ok(None);
+    };
+    let records = records.into_iter().map(|x| x.data).collect();
+    Ok(Some(TrustAnchor::new(zone, records)))
+}
+
+fn new_cookie_secret() -> Vec<u8> {
+    rand::random::<[u8; 16]>().to_vec()
+}
+
+/// Client subnet sent upstream (RFC 7871 section 7.1).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EcsPolicy {
+    /// Never send a client subnet.
+    Strip,
+    /// Forward the client subnet of queries.
+    PassThrough,
+    /// Send the subnet of the query source address, unless the client opted out.
+    Synthesize,
+}
+
+struct Server {
+    dns_client: DnsClient,
+    keys: KeyStore,
+    cookie_secrets: CookieSecrets,
+    cookie_secret_time: Instant,
+    ecs_policy: EcsPolicy,
+    cache: Cache,
+    blocked_names: Vec<DomainName>,
+}
+
+/// Response code and extended errors (RFC 8914) of a failed question.
+type Failure = (ResponseCode, Vec<ExtendedError>);
+
+impl Server {
+    fn rotate_cookie_secret(&mut self) {
+        if self.cookie_secret_time.elapsed() >= COOKIE_SECRET_LIFETIME {
+            self.cookie_secrets.rotate(new_cookie_secret());
+            self.cookie_secret_time = Instant::now();
+        }
+    }
+
+    /// Build response to a query, along with the maximum response size the client accepts.
+    ///
+    /// Responses sent to the server are dropped, without being decoded.
+    fn handle_query(
+        &mut self,
+        input: &[u8],
+        client_ip: IpAddr,
+    ) -> Result<Option<(Message, usize)>, DnsError> {
+        // Log query without decoding it all.
+        let query_ref = MessageRef::parse(input)?;
+        let id = query_ref.header().id;
+        if query_ref.header().flags.qr == QrFlag::Reply {
+            eprintln!("Dropped response {id}");
+            return Ok(None);
+        }
+        for question in query_ref.questions() {
+            let question = question?;
+            println!(
+                "query {id}: {} {} {}",
+                question.name, question.rr_class, question.rr_type
+            );
+        }
+
+        let query = query_ref.to_message()?;
+
+        // Signed queries get signed responses (RFC 8945 section 5.3).
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |x| x.as_secs());
+        let signature = match self.keys.verify(input, now) {
+            Ok(signature) => signature,
+            Err(e) => {
+                eprintln!("Rejected query signature: {e}");
+                let mut response = MessageBuilder::reply_to(&query).build();
+                tsig::reject(&mut response, &query, e, &self.keys, now);
+                return Ok(Some((response, MIN_UDP_PAYLOAD_SIZE as usize)));
+            }
+        };
+
+        // Honour client EDNS buffer size, up to ours.
+        let query_edns = query.edns();
+        let max_size = query_edns.as_ref().map_or(MIN_UDP_PAYLOAD_SIZE, |edns| {
+            edns.udp_payload_size
+                .clamp(MIN_UDP_PAYLOAD_SIZE, MAX_UDP_PAYLOAD_SIZE)
+        });
+
+        // Only EDNS version 0 is supported (RFC 6891 section 6.1.3).
+        let is_bad_version = query_edns.as_ref().is_some_and(|edns| edns.version > 0);
+
+        // Client subnet options must be well-formed (RFC 7871 section 7.1.2).
+        let client_subnet = query_edns.as_ref().and_then(|edns| edns.client_subnet());
+        let is_bad_subnet = query_edns.as_ref().is_some_and(|edns| {
+            matches!(
+                edns.option(EdnsOption::CLIENT_SUBNET),
+                Some(EdnsOption::Unknown(..))
+            )
+        });
+
+        // Unsigned queries with a bad server cookie get a new one, and no answer
+        // (RFC 7873 section 5.2.3).
+        let cookie = self.cookie_secrets.check(&query, client_ip, now);
+        let error = match cookie {
+            CookieCheck::Malformed => Some(ResponseCode::FormatError),
+            _ if is_bad_subnet => Some(ResponseCode::FormatError),
+            _ if is_bad_version => Some(ResponseCode::BadVersion),
+            CookieCheck::Invalid(_) if signature.is_none() => Some(ResponseCode::BadCookie),
+            _ => None,
+        };
+
+        // A source prefix of 0 asks not to use the client address (RFC 7871 section 7.1.2).
+        let upstream_subnet = match self.ecs_policy {
+            EcsPolicy::Strip => None,
+            EcsPolicy::PassThrough => client_subnet,
+            EcsPolicy::Synthesize => match client_subnet {
+                Some(subnet) if subnet.source_prefix() == 0 => Some(subnet),
+                _ => Some(ClientSubnet::for_client(client_ip)),
             },
-            question_count: query.questions.len() as u16,
-            answer_count: query.questions.len() as u16,
-            authority_resource_record_count: 0,
-            additional_resource_record_count: 0,
-        },
-        questions: query
-            .questions
-            .iter()
-            .map(|question| QuestionSection {
-                labels: question.labels.clone(),
-                rr_type: ResourceRecordType::A,
-                rr_class: ResourceRecordClass::IN,
+        };
+        self.dns_client.set_client_subnet(upstream_subnet);
+
+        // Answers are authentic only if all upstream responses are.
+        let mut is_authentic_data = error.is_none();
+        // Narrowest scope of upstream answers.
+        let mut scope_prefix = 0;
+
+        // First failed question fails the whole query.
+        let mut answers = vec![];
+        let mut authorities = vec![];
+        let mut failure = None;
+        let mut name_error = None;
+        for question in query.questions.iter().filter(|_| error.is_none()) {
+            let upstream_response =
+                match self.resolve(&query, question, upstream_subnet.as_ref(), now) {
+                    Ok(response) => response,
+                    Err(e) => {
+                        answers.clear();
+                        authorities.clear();
+                        failure = Some(e);
+                        break;
+                    }
+                };
+            is_authentic_data &= upstream_response.header.flags.is_authentic_data;
+            if let Some(subnet) = upstream_response
+                .edns()
+                .and_then(|edns| edns.client_subnet())
+            {
+                scope_prefix = scope_prefix.max(subnet.scope_prefix());
+            }
+
+            // Forwarded as is, with CNAME chains and upstream TTLs. NXDOMAIN
+            // and NODATA responses keep their SOA record (RFC 2308 section 2).
+            let is_name_error =
+                upstream_response.response_code() == ResponseCode::NonExistentDomain;
+            if is_name_error {
+                name_error = Some((
+                    ResponseCode::NonExistentDomain,
+                    upstream_response.extended_errors(),
+                ));
+            }
+            if is_name_error || upstream_response.answers.is_empty() {
+                authorities.extend(upstream_response.authorities);
+            }
+            answers.extend(upstream_response.answers);
+        }
+        is_authentic_data &= failure.is_none();
+        let (failure_code, extended_errors) = failure.or(name_error).unzip();
+
+        // Only set AD if the client asked for it (RFC 6840 section 5.7).
+        let is_dnssec_ok = query_edns.as_ref().is_some_and(|edns| edns.dnssec_ok);
+        is_authentic_data &= query.header.flags.is_authentic_data || is_dnssec_ok;
+
+        let mut response = MessageBuilder::reply_to(&query)
+            .authentic_data(is_authentic_data)
+            .checking_disabled(query.header.flags.is_checking_disabled)
+            .response_code(if let Some(code) = error.or(failure_code) {
+                code
+            } else if query.header.flags.opcode == OpCode::Query {
+                ResponseCode::NoError
+            } else {
+                ResponseCode::NotImplemented
             })
-            .collect(),
-        answers: query
-            .questions
+            .answers(answers)
+            .authorities(authorities);
+
+        // Only answer with EDNS to EDNS queries (RFC 6891 section 7).
+        if query_edns.is_some() {
+            let mut edns = Edns::new(MAX_UDP_PAYLOAD_SIZE);
+            // Client subnet is echoed, with the upstream scope if it was forwarded
+            // (RFC 7871 section 7.2.1).
+            if let Some(subnet) = client_subnet {
+                let scope = if upstream_subnet == Some(subnet) {
+                    scope_prefix
+                } else {
+                    0
+                };
+                edns.set_option(EdnsOption::ClientSubnet(subnet.with_scope_prefix(scope)));
+            }
+            if let Some(option) = self.cookie_secrets.response_option(&cookie, client_ip, now) {
+                edns.set_option(option);
+            }
+            edns.options.extend(
+                extended_errors
+                    .into_iter()
+                    .flatten()
+                    .map(EdnsOption::ExtendedError),
+            );
+            response = response.edns(edns);
+        }
+
+        let mut response = response.build();
+
+        // Sign the message as sent: once truncated, leaving room for the signature.
+        if let Some(signature) = &signature {
+            response.truncate((max_size as usize).saturating_sub(signature.key.record_len()));
+            signature.sign_response(&mut response, now);
+        }
+        println!("{response}");
+
+        Ok(Some((response, max_size as usize)))
+    }
+
+    /// Upstream response to a question: answers, or a negative response
+    /// (NXDOMAIN or NODATA, RFC 2308 section 2).
+    ///
+    /// Responses are validated if a trust anchor is set, unless the client
+    /// disabled checking (RFC 6840 section 5.9). Responses fetched with checking
+    /// disabled may not be valid: they are neither cached nor served from the cache.
+    fn resolve(
+        &mut self,
+        query: &Message,
+        question: &QuestionSection,
+        subnet: Option<&ClientSubnet>,
+        now: u64,
+    ) -> Result<Message, Failure> {
+        if self
+            .blocked_names
             .iter()
-            .map(|question| AnswerSection {
-                labels: question.labels.clone(),
-                rr_type: ResourceRecordType::A,
-                rr_class: ResourceRecordClass::IN,
-                ttl: 60,
-                data: dns_client
-                    .query(question)
-                    .expect("Fail to query resolver")
-                    .data,
-            })
-            .collect(),
+            .any(|name| question.name.is_subdomain_of(name))
+        {
+            let blocked = ExtendedError::new(ExtendedErrorCode::Blocked, "");
+            return Err((ResponseCode::Refused, vec![blocked]));
+        }
+        let is_checking_disabled = query.header.flags.is_checking_disabled;
+        if !is_checking_disabled {
+            if let Some(response) = self.cache.get(question, subnet, now) {
+                return Ok(response);
+            }
+        }
+
+        let response = if self.dns_client.trust_anchor().is_some() && !is_checking_disabled {
+            let mut validated = self
+                .dns_client
+                .query_validated(question)
+                .map_err(|e| self.upstream_failure(&e))?;
+            if let ValidationStatus::Bogus(e) = &validated.status {
+                return Err((ResponseCode::ServerFail, vec![e.into()]));
+            }
+            validated.response.header.flags.is_authentic_data =
+                validated.status == ValidationStatus::Secure;
+            validated.response
+        } else {
+            // Forward DNSSEC bits to upstream (RFC 6840 sections 5.7 and 5.9).
+            let mut upstream_query = DnsClient::new_query(question);
+            upstream_query.header.flags.is_authentic_data = query.header.flags.is_authentic_data;
+            upstream_query.header.flags.is_checking_disabled = is_checking_disabled;
+            self.dns_client
+                .send_query(upstream_query)
+                .map_err(|e| self.upstream_failure(&e))?
+        };
+
+        match response.response_code() {
+            ResponseCode::NoError | ResponseCode::NonExistentDomain => {}
+            code => return Err((code, response.extended_errors())),
+        }
+        if !is_checking_disabled {
+            self.cache.insert(question.clone(), response.clone(), now);
+        }
+
+        Ok(response)
+    }
+
+    /// Failure of an upstream query, explained with an extended error (RFC 8914 section 4).
+    fn upstream_failure(&self, error: &DnsError) -> Failure {
+        let code = match error {
+            // Error responses are forwarded, with their own explanations.
+            DnsError::ErrorResponse(code) => {
+                return (*code, self.dns_client.extended_errors().to_vec())
+            }
+            DnsError::Timeout => ExtendedErrorCode::NoReachableAuthority,
+            DnsError::Io(_) => ExtendedErrorCode::NetworkError,
+            DnsError::Parse(_) | DnsError::EmptyResponse | DnsError::InvalidResponse(_) => {
+                ExtendedErrorCode::InvalidData
+            }
+            _ => ExtendedErrorCode::Other,
+        };
+        eprintln!("Upstream query failed: {error}");
+
+        let extended_error = ExtendedError::new(code, error.to_string());
+        (ResponseCode::ServerFail, vec![extended_error])
     }
 }
 
@@ -90,10 +424,28 @@ fn send_response(
     udp_socket: &UdpSocket,
     source: &SocketAddr,
     response: &Message,
+    max_size: usize,
 ) -> io::Result<()> {
-    let mut buffer = Vec::with_capacity(4096);
+    let mut buffer = Vec::with_capacity(max_size);
+    if let Err(e) = response.encode_with_limit(&mut buffer, max_size) {
+        if e.kind() != io::ErrorKind::InvalidInput {
+            return Err(e);
+        }
+        // Questions do not fit: send the header alone, with SERVFAIL.
+        eprintln!("Response {} too large: {e}", response.header.id);
+        let mut minimal = Message {
+            header: response.header,
+            questions: vec![],
+            answers: vec![],
+            authorities: vec![],
+            additionals: vec![],
+        };
+        minimal.set_response_code(ResponseCode::ServerFail);
+        minimal.update_counts();
+        buffer.clear();
+        minimal.encode(&mut buffer)?;
+    }
 
-    response.encode(&mut buffer).expect("Fail to write buffer");
     udp_socket.send_to(&buffer, source)?;
 
     Ok(())
@@ -0,0 +1,171 @@
+use super::{
+    AnswerSection, Edns, Header, HeaderFlags, Message, OpCode, QrFlag, QuestionSection,
+    ResponseCode,
+};
+
+/// Fluent builder for messages.
+///
+/// Header counts are derived from sections on build.
+#[derive(Debug, Clone)]
+pub struct MessageBuilder {
+    message: Message,
+    response_code: ResponseCode,
+}
+
+impl MessageBuilder {
+    /// Start a standard query.
+    pub fn query() -> Self {
+        Self::new(QrFlag::Query)
+    }
+
+    /// Start a response.
+    pub fn response() -> Self {
+        Self::new(QrFlag::Reply)
+    }
+
+    /// Start a response to `query`, copying its ID, opcode, RD bit and questions.
+    pub fn reply_to(query: &Message) -> Self {
+        Self::response()
+            .id(query.header.id)
+            .opcode(query.header.flags.opcode)
+            .recursion_desired(query.header.flags.is_recursion_desired)
+            .questions(query.questions.iter().cloned())
+    }
+
+    fn new(qr: QrFlag) -> Self {
+        Self {
+            message: Message {
+                header: Header {
+                    id: 0,
+                    flags: HeaderFlags {
+                        qr,
+                        opcode: OpCode::Query,
+                        is_authoritative_answer: false,
+                        is_truncation: false,
+                        is_recursion_desired: false,
+                        is_recursion_available: false,
+                        z: false,
+                        is_authentic_data: false,
+                        is_checking_disabled: false,
+                        response_code: ResponseCode::NoError,
+                    },
+                    question_count: 0,
+                    answer_count: 0,
+                    authority_resource_record_count: 0,
+                    additional_resource_record_count: 0,
+                },
+                questions: vec![],
+                answers: vec![],
+                authorities: vec![],
+                additionals: vec![],
+            },
+            response_code: ResponseCode::NoError,
+        }
+    }
+
+    pub fn id(mut self, id: u16) -> Self {
+        self.message.header.id = id;
+        self
+    }
+
+    pub fn opcode(mut self, opcode: OpCode) -> Self {
+        self.message.header.flags.opcode = opcode;
+        self
+    }
+
+    pub fn authoritative_answer(mut self, value: bool) -> Self {
+        self.message.header.flags.is_authoritative_answer = value;
+        self
+    }
+
+    pub fn truncation(mut self, value: bool) -> Self {
+        self.message.header.flags.is_truncation = value;
+        self
+    }
+
+    pub fn recursion_desired(mut self, value: bool) -> Self {
+        self.message.header.flags.is_recursion_desired = value;
+        self
+    }
+
+    pub fn recursion_available(mut self, value: bool) -> Self {
+        self.message.header.flags.is_recursion_available = value;
+        self
+    }
+
+    pub fn authentic_data(mut self, value: bool) -> Self {
+        self.message.header.flags.is_authentic_data = value;
+        self
+    }
+
+    pub fn checking_disabled(mut self, value: bool) -> Self {
+        self.message.header.flags.is_checking_disabled = value;
+        self
+    }
+
+    /// Set response code, extended codes add an OPT record on build.
+    pub fn response_code(mut self, code: ResponseCode) -> Self {
+        self.response_code = code;
+        self
+    }
+
+    /// Set EDNS data (OPT record of the additional section).
+    pub fn edns(mut self, edns: Edns) -> Self {
+        self.message.set_edns(Some(edns));
+        self
+    }
+
+    pub fn question(mut self, question: QuestionSection) -> Self {
+        self.message.questions.push(question);
+        self
+    }
+
+    pub fn questions<I: IntoIterator<Item = QuestionSection>>(mut self, questions: I) -> Self {
+        self.message.questions.extend(questions);
+        self
+    }
+
+    pub fn answer(mut self, record: AnswerSection) -> Self {
+        self.message.answers.push(record);
+        self
+    }
+
+    pub fn answers<I: IntoIterator<Item = AnswerSection>>(mut self, records: I) -> Self {
+        self.message.answers.extend(records);
+        self
+    }
+
+    pub fn authority(mut self, record: AnswerSection) -> Self {
+        self.message.authorities.push(record);
+        self
+    }
+
+    pub fn authorities<I: IntoIterator<Item = AnswerSection>>(mut self, records: I) -> Self {
+        self.message.authorities.extend(records);
+        self
+    }
+
+    pub fn additional(mut self, record: AnswerSection) -> Self {
+        self.message.additionals.push(record);
+        self
+    }
+
+    pub fn additionals<I: IntoIterator<Item = AnswerSection>>(mut self, records: I) -> Self {
+        self.message.additionals.extend(records);
+        self
+    }
+
+    pub fn build(self) -> Message {
+        let mut message = self.message;
+        message.set_response_code(self.response_code);
+        message.update_counts();
+        message
+    }
+}
+
+impl Message {
+    /// Build a response to `query`, see [`MessageBuilder::reply_to`].
+    pub fn reply_to(query: &Message) -> Self {
+        MessageBuilder::reply_to(query).build()
+    }
+}
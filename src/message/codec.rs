@@ -0,0 +1,117 @@
+//! Binary to text encodings used by presentation formats.
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Extended hex alphabet (RFC 4648 section 7), as used by NSEC3.
+const BASE32HEX_ALPHABET: &[u8; 32] = b"0123456789abcdefghijklmnopqrstuv";
+
+/// Lower-case hexadecimal form of `data`.
+pub(crate) fn encode_hex(data: &[u8]) -> String {
+    data.iter().map(|x| format!("{x:02x}")).collect()
+}
+
+/// Decode hexadecimal text (either case).
+pub(crate) fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    let digit = |x: u8| (x as char).to_digit(16).map(|x| x as u8);
+    hex.as_bytes()
+        .chunks(2)
+        .map(|x| match x {
+            [high, low] => Some(digit(*high)? << 4 | digit(*low)?),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Padded base64 form of `data` (RFC 4648 section 4).
+pub(crate) fn encode_base64(data: &[u8]) -> String {
+    let mut output = String::with_capacity(data.len() / 3 * 4 + 4);
+    for chunk in data.chunks(3) {
+        let mut block = [0; 3];
+        block[..chunk.len()].copy_from_slice(chunk);
+        let value = u32::from_be_bytes([0, block[0], block[1], block[2]]);
+
+        for idx in 0..4 {
+            if idx <= chunk.len() {
+                let digit = (value >> (18 - 6 * idx)) & 0x3F;
+                output.push(BASE64_ALPHABET[digit as usize] as char);
+            } else {
+                output.push('=');
+            }
+        }
+    }
+    output
+}
+
+/// Decode padded base64 text.
+pub(crate) fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let chunks = text.as_bytes().chunks_exact(4);
+    if !chunks.remainder().is_empty() {
+        return None;
+    }
+
+    let count = chunks.len();
+    let mut output = Vec::with_capacity(count * 3);
+    for (idx, chunk) in chunks.enumerate() {
+        let is_last = idx + 1 == count;
+        let padding = chunk.iter().rev().take_while(|x| **x == b'=').count();
+        if padding > 2 || (padding > 0 && !is_last) {
+            return None;
+        }
+
+        let mut value = 0_u32;
+        for c in &chunk[..4 - padding] {
+            let digit = BASE64_ALPHABET.iter().position(|x| x == c)?;
+            value = (value << 6) | digit as u32;
+        }
+        value <<= 6 * padding;
+
+        output.extend(&value.to_be_bytes()[1..4 - padding]);
+    }
+    Some(output)
+}
+
+/// Unpadded base32hex form of `data` (RFC 5155 section 3.3).
+pub(crate) fn encode_base32hex(data: &[u8]) -> String {
+    let mut output = String::with_capacity(data.len() * 8 / 5 + 1);
+    let mut buffer = 0_u16;
+    let mut bits = 0;
+
+    for c in data {
+        buffer = (buffer << 8) | *c as u16;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            output.push(BASE32HEX_ALPHABET[((buffer >> bits) & 0x1F) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        output.push(BASE32HEX_ALPHABET[((buffer << (5 - bits)) & 0x1F) as usize] as char);
+    }
+    output
+}
+
+/// Decode unpadded base32hex text (either case).
+pub(crate) fn decode_base32hex(text: &str) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(text.len() * 5 / 8);
+    let mut buffer = 0_u16;
+    let mut bits = 0;
+
+    for c in text.bytes() {
+        let digit = BASE32HEX_ALPHABET
+            .iter()
+            .position(|x| *x == c.to_ascii_lowercase())?;
+        buffer = (buffer << 5) | digit as u16;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+        }
+    }
+
+    // Remaining bits must be padding.
+    if bits >= 5 || buffer & ((1 << bits) - 1) != 0 {
+        return None;
+    }
+    Some(output)
+}
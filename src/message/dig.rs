@@ -0,0 +1,92 @@
+use std::fmt;
+
+use super::{AnswerSection, Message, QrFlag, ResourceRecordType};
+
+impl fmt::Display for Message {
+    /// Format message as `dig` does: header, OPT pseudo-section, then
+    /// each section in presentation format.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flags = &self.header.flags;
+        writeln!(
+            f,
+            ";; ->>HEADER<<- opcode: {}, status: {}, id: {}",
+            flags.opcode,
+            self.response_code(),
+            self.header.id
+        )?;
+
+        f.write_str(";; flags:")?;
+        for (is_set, name) in [
+            (flags.qr == QrFlag::Reply, "qr"),
+            (flags.is_authoritative_answer, "aa"),
+            (flags.is_truncation, "tc"),
+            (flags.is_recursion_desired, "rd"),
+            (flags.is_recursion_available, "ra"),
+            (flags.is_authentic_data, "ad"),
+            (flags.is_checking_disabled, "cd"),
+        ] {
+            if is_set {
+                write!(f, " {name}")?;
+            }
+        }
+        writeln!(
+            f,
+            "; QUERY: {}, ANSWER: {}, AUTHORITY: {}, ADDITIONAL: {}",
+            self.questions.len(),
+            self.answers.len(),
+            self.authorities.len(),
+            self.additionals.len()
+        )?;
+
+        if let Some(edns) = self.edns() {
+            writeln!(f, "\n;; OPT PSEUDOSECTION:")?;
+            write!(f, "; EDNS: version: {}, flags:", edns.version)?;
+            if edns.dnssec_ok {
+                f.write_str(" do")?;
+            }
+            if edns.z != 0 {
+                write!(f, "; MBZ: {:#06x}", edns.z)?;
+            }
+            writeln!(f, "; udp: {}", edns.udp_payload_size)?;
+            for option in &edns.options {
+                writeln!(f, "; {option}")?;
+            }
+        }
+
+        if !self.questions.is_empty() {
+            writeln!(f, "\n;; QUESTION SECTION:")?;
+            for question in &self.questions {
+                writeln!(
+                    f,
+                    ";{}\t\t{}\t{}",
+                    question.name, question.rr_class, question.rr_type
+                )?;
+            }
+        }
+
+        fmt_section(f, "ANSWER", &self.answers)?;
+        fmt_section(f, "AUTHORITY", &self.authorities)?;
+        fmt_section(f, "ADDITIONAL", &self.additionals)
+    }
+}
+
+/// Format records of a section, OPT pseudo-records excluded.
+fn fmt_section(f: &mut fmt::Formatter<'_>, title: &str, records: &[AnswerSection]) -> fmt::Result {
+    let mut records = records
+        .iter()
+        .filter(|x| x.rr_type != ResourceRecordType::OPT)
+        .peekable();
+    if records.peek().is_none() {
+        return Ok(());
+    }
+
+    writeln!(f, "\n;; {title} SECTION:")?;
+    for record in records {
+        writeln!(
+            f,
+            "{}\t{}\t{}\t{}\t{}",
+            record.name, record.ttl, record.rr_class, record.rr_type, record.data
+        )?;
+    }
+    Ok(())
+}
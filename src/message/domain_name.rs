@@ -0,0 +1,396 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+};
+
+use crate::DomainNameError;
+
+use super::idna;
+
+/// Maximum length of a label (RFC 1035 section 2.3.4).
+pub const MAX_LABEL_LEN: usize = 63;
+
+/// Maximum wire length of a name, including length bytes (RFC 1035 section 2.3.4).
+pub const MAX_NAME_LEN: usize = 255;
+
+/// Validated domain name.
+///
+/// Labels are stored as raw bytes from the most specific one to the top level
+/// domain; the root label is implicit. Comparison and hashing are ASCII case
+/// insensitive, ordering follows DNSSEC canonical order (RFC 4034 section 6.1).
+#[derive(Clone, Default)]
+pub struct DomainName {
+    labels: Vec<Vec<u8>>,
+}
+
+impl DomainName {
+    /// The root name (`.`).
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    /// Build a name from its labels, checking label and name lengths.
+    pub fn from_labels<I, L>(labels: I) -> Result<Self, DomainNameError>
+    where
+        I: IntoIterator<Item = L>,
+        L: Into<Vec<u8>>,
+    {
+        let mut name = Self::root();
+        for label in labels {
+            name.push_label(label.into())?;
+        }
+        Ok(name)
+    }
+
+    pub fn labels(&self) -> &[Vec<u8>] {
+        &self.labels
+    }
+
+    pub fn label_count(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// Length of the uncompressed name on the wire.
+    pub fn wire_len(&self) -> usize {
+        self.labels.iter().map(|x| x.len() + 1).sum::<usize>() + 1
+    }
+
+    /// Name without its first label (`None` for the root).
+    pub fn parent(&self) -> Option<Self> {
+        (!self.is_root()).then(|| Self {
+            labels: self.labels[1..].to_vec(),
+        })
+    }
+
+    /// Iterate over each parent name, up to the root.
+    pub fn parents(&self) -> impl Iterator<Item = Self> + '_ {
+        (1..=self.labels.len()).map(|idx| Self {
+            labels: self.labels[idx..].to_vec(),
+        })
+    }
+
+    /// Check if name is equal to or below `other`.
+    pub fn is_subdomain_of(&self, other: &Self) -> bool {
+        self.labels.len() >= other.labels.len()
+            && self
+                .labels
+                .iter()
+                .rev()
+                .zip(other.labels.iter().rev())
+                .all(|(x, y)| x.eq_ignore_ascii_case(y))
+    }
+
+    /// Prepend a label to the name (ex: `www` to `example.com`).
+    pub fn prepend_label<L: Into<Vec<u8>>>(&self, label: L) -> Result<Self, DomainNameError> {
+        let mut name = Self::from_labels([label])?;
+        for label in &self.labels {
+            name.push_label(label.clone())?;
+        }
+        Ok(name)
+    }
+
+    /// Name with all ASCII letters lower-cased (ex: DNSSEC canonical form).
+    pub fn to_lowercase(&self) -> Self {
+        Self {
+            labels: self.labels.iter().map(|x| x.to_ascii_lowercase()).collect(),
+        }
+    }
+
+    /// Reverse lookup name of an address: `in-addr.arpa` (RFC 1035 section 3.5)
+    /// or `ip6.arpa` (RFC 3596 section 2.5).
+    pub fn reverse(addr: IpAddr) -> Self {
+        let labels: Vec<String> = match addr {
+            IpAddr::V4(addr) => addr.octets().iter().rev().map(u8::to_string).collect(),
+            IpAddr::V6(addr) => addr
+                .octets()
+                .iter()
+                .rev()
+                .flat_map(|x| [x & 0xF, x >> 4])
+                .map(|x| format!("{x:x}"))
+                .collect(),
+        };
+        let zone: &[&str] = match addr {
+            IpAddr::V4(_) => &["in-addr", "arpa"],
+            IpAddr::V6(_) => &["ip6", "arpa"],
+        };
+        Self::from_labels(
+            labels
+                .iter()
+                .map(String::as_str)
+                .chain(zone.iter().copied()),
+        )
+        .expect("Reverse names are valid")
+    }
+
+    /// Address and prefix length of a reverse lookup name, inverse of `reverse`.
+    ///
+    /// Names of networks (ex: `2.0.192.in-addr.arpa`) give the network address
+    /// and prefix (`192.0.2.0`, 24). `None` if this is not a reverse name.
+    pub fn reverse_address(&self) -> Option<(IpAddr, u8)> {
+        let (labels, zone) = self.labels.split_at(self.labels.len().checked_sub(2)?);
+        let zone = zone
+            .iter()
+            .map(|x| x.to_ascii_lowercase())
+            .collect::<Vec<_>>();
+
+        if zone == [&b"in-addr"[..], b"arpa"] && labels.len() <= 4 {
+            let mut octets = [0; 4];
+            for (octet, label) in octets.iter_mut().zip(labels.iter().rev()) {
+                // Decimal, without leading zeros
+                let text = std::str::from_utf8(label).ok()?;
+                if !text.bytes().all(|x| x.is_ascii_digit())
+                    || (text.len() > 1 && text.starts_with('0'))
+                {
+                    return None;
+                }
+                *octet = text.parse().ok()?;
+            }
+            Some((Ipv4Addr::from(octets).into(), labels.len() as u8 * 8))
+        } else if zone == [&b"ip6"[..], b"arpa"] && labels.len() <= 32 {
+            let mut value = 0_u128;
+            for (idx, label) in labels.iter().rev().enumerate() {
+                let nibble = match label.as_slice() {
+                    [x] => (*x as char).to_digit(16)?,
+                    _ => return None,
+                };
+                value |= (nibble as u128) << (124 - 4 * idx);
+            }
+            Some((Ipv6Addr::from(value).into(), labels.len() as u8 * 4))
+        } else {
+            None
+        }
+    }
+
+    /// Parse a name which may contain Unicode labels (IDNA ToASCII).
+    ///
+    /// Labels with non ASCII characters are lower-cased and converted to
+    /// `xn--` A-labels; ASCII labels are parsed as by `from_str`, escapes included.
+    pub fn from_unicode(s: &str) -> Result<Self, DomainNameError> {
+        if s.is_ascii() {
+            return s.parse();
+        }
+
+        let mut name = Self::root();
+        let mut label = String::new();
+        let mut chars = s.chars();
+        let push = |name: &mut Self, label: &str| {
+            let label = match label.is_ascii() {
+                true => {
+                    let mut bytes = label.bytes();
+                    let mut output = vec![];
+                    while let Some(c) = bytes.next() {
+                        match c {
+                            b'\\' => output
+                                .push(parse_escape(&mut bytes).ok_or(DomainNameError::BadEscape)?),
+                            _ => output.push(c),
+                        }
+                    }
+                    output
+                }
+                false => idna::to_ascii(label)
+                    .ok_or(DomainNameError::InvalidIdna)?
+                    .into_bytes(),
+            };
+            name.push_label(label)
+        };
+
+        while let Some(c) = chars.next() {
+            match c {
+                c if idna::DOTS.contains(&c) => push(&mut name, &std::mem::take(&mut label))?,
+                '\\' => {
+                    label.push(c);
+                    label.extend(chars.next());
+                }
+                _ => label.push(c),
+            }
+        }
+
+        // Name was not terminated by a dot.
+        if !label.is_empty() {
+            push(&mut name, &label)?;
+        }
+
+        Ok(name)
+    }
+
+    /// Format the name with A-labels shown as Unicode (IDNA ToUnicode).
+    ///
+    /// Other labels are escaped as by `Display`, so that `from_unicode` reads
+    /// the same name back.
+    pub fn to_unicode(&self) -> String {
+        if self.is_root() {
+            return ".".to_string();
+        }
+
+        let mut output = String::new();
+        for label in &self.labels {
+            match idna::to_unicode(label) {
+                Some(unicode) => output.push_str(&unicode),
+                None => output.push_str(&Label(label).to_string()),
+            }
+            output.push('.');
+        }
+        output
+    }
+
+    fn push_label(&mut self, label: Vec<u8>) -> Result<(), DomainNameError> {
+        if label.is_empty() {
+            return Err(DomainNameError::EmptyLabel);
+        }
+        if label.len() > MAX_LABEL_LEN {
+            return Err(DomainNameError::LabelTooLong(label.len()));
+        }
+        if self.wire_len() + label.len() + 1 > MAX_NAME_LEN {
+            return Err(DomainNameError::NameTooLong);
+        }
+
+        self.labels.push(label);
+        Ok(())
+    }
+}
+
+impl FromStr for DomainName {
+    type Err = DomainNameError;
+
+    /// Parse a name from presentation format.
+    ///
+    /// Trailing dot is optional, `\X` and `\DDD` escapes are supported.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "." {
+            return Ok(Self::root());
+        }
+        if s.is_empty() {
+            return Err(DomainNameError::EmptyLabel);
+        }
+
+        let mut name = Self::root();
+        let mut label = Vec::new();
+        let mut bytes = s.bytes();
+
+        while let Some(c) = bytes.next() {
+            match c {
+                b'.' => name.push_label(std::mem::take(&mut label))?,
+                b'\\' => label.push(parse_escape(&mut bytes).ok_or(DomainNameError::BadEscape)?),
+                _ => label.push(c),
+            }
+        }
+
+        // Name was not terminated by a dot.
+        if !label.is_empty() {
+            name.push_label(label)?;
+        }
+
+        Ok(name)
+    }
+}
+
+impl fmt::Display for DomainName {
+    /// Format the name as an absolute name in presentation format.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_root() {
+            return f.write_str(".");
+        }
+
+        for label in &self.labels {
+            fmt_label(label, f)?;
+            f.write_str(".")?;
+        }
+        Ok(())
+    }
+}
+
+/// Label in presentation format.
+struct Label<'a>(&'a [u8]);
+
+impl fmt::Display for Label<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_label(self.0, f)
+    }
+}
+
+/// Decode an escape sequence (`\X` or `\DDD`), following a backslash.
+pub(crate) fn parse_escape<I: Iterator<Item = u8>>(bytes: &mut I) -> Option<u8> {
+    match bytes.next()? {
+        d if d.is_ascii_digit() => {
+            let digits = [Some(d), bytes.next(), bytes.next()];
+            let value = digits.iter().try_fold(0_u16, |acc, x| match x {
+                Some(x) if x.is_ascii_digit() => Some(acc * 10 + (x - b'0') as u16),
+                _ => None,
+            })?;
+            u8::try_from(value).ok()
+        }
+        x => Some(x),
+    }
+}
+
+/// Format a label in presentation format, escaping special and non printable bytes.
+pub(crate) fn fmt_label(label: &[u8], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for c in label {
+        match c {
+            b'.' | b';' | b'\\' | b'(' | b')' | b'"' | b'@' | b'$' => {
+                write!(f, "\\{}", *c as char)?
+            }
+            0x21..=0x7E => write!(f, "{}", *c as char)?,
+            _ => write!(f, "\\{:03}", c)?,
+        }
+    }
+    Ok(())
+}
+
+impl fmt::Debug for DomainName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("DomainName")
+            .field(&self.to_string())
+            .finish()
+    }
+}
+
+impl PartialEq for DomainName {
+    fn eq(&self, other: &Self) -> bool {
+        self.labels.len() == other.labels.len()
+            && self
+                .labels
+                .iter()
+                .zip(&other.labels)
+                .all(|(x, y)| x.eq_ignore_ascii_case(y))
+    }
+}
+
+impl Eq for DomainName {}
+
+impl Hash for DomainName {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.labels.len());
+        for label in &self.labels {
+            state.write_usize(label.len());
+            for c in label {
+                state.write_u8(c.to_ascii_lowercase());
+            }
+        }
+    }
+}
+
+impl Ord for DomainName {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Compare labels from the right, as lower-cased byte strings.
+        let lower = |x: &Vec<u8>| x.to_ascii_lowercase();
+        self.labels
+            .iter()
+            .rev()
+            .map(lower)
+            .cmp(other.labels.iter().rev().map(lower))
+    }
+}
+
+impl PartialOrd for DomainName {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
@@ -0,0 +1,380 @@
+use std::{
+    fmt,
+    io::{self, Write},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+
+use nom::{multi::length_data, number::complete::be_u16, IResult};
+
+use super::{
+    codec::encode_hex, AnswerSection, DomainName, ExtendedError, Message, RData,
+    ResourceRecordType, ResponseCode,
+};
+
+/// Smallest payload size a requestor can advertise (RFC 6891 section 6.2.5).
+pub const MIN_UDP_PAYLOAD_SIZE: u16 = 512;
+
+/// Extension mechanisms for DNS, carried by the OPT pseudo-record.
+///
+/// Check: https://www.rfc-editor.org/rfc/rfc6891#section-6.1
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Edns {
+    /// Largest UDP payload the sender can reassemble (OPT record CLASS).
+    pub udp_payload_size: u16,
+    /// Upper 8 bits of the 12 bits response code.
+    pub extended_rcode: u8,
+    pub version: u8,
+    /// DNSSEC answer OK (RFC 3225).
+    pub dnssec_ok: bool,
+    /// Remaining flags bits (must be zero).
+    pub z: u16,
+    pub options: Vec<EdnsOption>,
+}
+
+impl Edns {
+    pub fn new(udp_payload_size: u16) -> Self {
+        Self {
+            udp_payload_size,
+            extended_rcode: 0,
+            version: 0,
+            dnssec_ok: false,
+            z: 0,
+            options: vec![],
+        }
+    }
+
+    /// Read EDNS data from an OPT record (`None` for other records).
+    pub fn from_record(record: &AnswerSection) -> Option<Self> {
+        let RData::Opt(options) = &record.data else {
+            return None;
+        };
+
+        Some(Self {
+            udp_payload_size: record.rr_class.into(),
+            extended_rcode: (record.ttl >> 24) as u8,
+            version: (record.ttl >> 16) as u8,
+            dnssec_ok: record.ttl & 0x8000 != 0,
+            z: (record.ttl & 0x7FFF) as u16,
+            options: options.clone(),
+        })
+    }
+
+    /// First option with the given code.
+    pub fn option(&self, code: u16) -> Option<&EdnsOption> {
+        self.options.iter().find(|option| option.code() == code)
+    }
+
+    /// Client subnet option, if any and well-formed.
+    pub fn client_subnet(&self) -> Option<ClientSubnet> {
+        match self.option(EdnsOption::CLIENT_SUBNET) {
+            Some(EdnsOption::ClientSubnet(subnet)) => Some(*subnet),
+            _ => None,
+        }
+    }
+
+    /// Extended DNS errors, a response may have several.
+    pub fn extended_errors(&self) -> impl Iterator<Item = &ExtendedError> {
+        self.options.iter().filter_map(|option| match option {
+            EdnsOption::ExtendedError(error) => Some(error),
+            _ => None,
+        })
+    }
+
+    /// Add an option, replacing any option with the same code.
+    pub fn set_option(&mut self, option: EdnsOption) {
+        self.options.retain(|x| x.code() != option.code());
+        self.options.push(option);
+    }
+
+    pub fn to_record(&self) -> AnswerSection {
+        let mut ttl = (self.extended_rcode as u32) << 24;
+        ttl |= (self.version as u32) << 16;
+        ttl |= (self.dnssec_ok as u32) << 15;
+        ttl |= (self.z & 0x7FFF) as u32;
+
+        AnswerSection {
+            name: DomainName::root(),
+            rr_type: ResourceRecordType::OPT,
+            rr_class: self.udp_payload_size.into(),
+            ttl,
+            data: RData::Opt(self.options.clone()),
+        }
+    }
+}
+
+/// EDNS option, stored in OPT record RDATA.
+///
+/// Check: https://www.iana.org/assignments/dns-parameters/dns-parameters.xhtml#dns-parameters-11
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum EdnsOption {
+    /// Client subnet (RFC 7871 section 6).
+    ClientSubnet(ClientSubnet),
+    /// DNS cookie (RFC 7873 section 4): client cookie, and server cookie
+    /// (empty until the server sent one).
+    Cookie { client: [u8; 8], server: Vec<u8> },
+    /// Extended DNS error (RFC 8914 section 2).
+    ExtendedError(ExtendedError),
+    /// Any option: code + raw data.
+    ///
+    /// Known options with malformed data are kept as unknown.
+    Unknown(u16, Vec<u8>),
+}
+
+impl EdnsOption {
+    pub const CLIENT_SUBNET: u16 = 8;
+    pub const COOKIE: u16 = 10;
+    pub const EXTENDED_ERROR: u16 = 15;
+
+    pub fn parse(input: &[u8]) -> IResult<&[u8], Self> {
+        let (input, code) = be_u16(input)?;
+        let (input, data) = length_data(be_u16)(input)?;
+
+        let option = match (code, data.len()) {
+            (Self::CLIENT_SUBNET, _) => ClientSubnet::parse(data)
+                .map_or_else(|| Self::Unknown(code, data.to_vec()), Self::ClientSubnet),
+            (Self::COOKIE, 8) | (Self::COOKIE, 16..=40) => Self::Cookie {
+                client: data[..8].try_into().unwrap(),
+                server: data[8..].to_vec(),
+            },
+            (Self::EXTENDED_ERROR, _) => ExtendedError::parse(data)
+                .map_or_else(|| Self::Unknown(code, data.to_vec()), Self::ExtendedError),
+            _ => Self::Unknown(code, data.to_vec()),
+        };
+        Ok((input, option))
+    }
+
+    pub fn code(&self) -> u16 {
+        match self {
+            Self::ClientSubnet(_) => Self::CLIENT_SUBNET,
+            Self::Cookie { .. } => Self::COOKIE,
+            Self::ExtendedError(_) => Self::EXTENDED_ERROR,
+            Self::Unknown(code, _) => *code,
+        }
+    }
+
+    pub fn encode<W: Write>(&self, buf: &mut W) -> io::Result<()> {
+        let data = match self {
+            Self::ClientSubnet(subnet) => subnet.encode(),
+            Self::Cookie { client, server } => [client.as_slice(), server].concat(),
+            Self::ExtendedError(error) => error.encode(),
+            Self::Unknown(_, data) => data.clone(),
+        };
+
+        assert!(data.len() <= 0xFFFF, "Option is too long");
+        buf.write_all(&self.code().to_be_bytes())?;
+        buf.write_all(&(data.len() as u16).to_be_bytes())?;
+        buf.write_all(&data)
+    }
+}
+
+impl fmt::Display for EdnsOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ClientSubnet(subnet) => write!(f, "CLIENT-SUBNET: {subnet}"),
+            Self::Cookie { client, server } => {
+                write!(f, "COOKIE: {}{}", encode_hex(client), encode_hex(server))
+            }
+            Self::ExtendedError(error) => write!(f, "EDE: {error}"),
+            Self::Unknown(code, data) => write!(f, "OPT={code}: {}", encode_hex(data)),
+        }
+    }
+}
+
+/// Network of the client a query is sent for, so that the answer suits its
+/// location (RFC 7871).
+///
+/// Address bits beyond the source prefix are always zero.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct ClientSubnet {
+    address: IpAddr,
+    source_prefix: u8,
+    /// Prefix the answer is valid for, set in responses.
+    scope_prefix: u8,
+}
+
+impl ClientSubnet {
+    /// Subnet of `address`, keeping its first `source_prefix` bits.
+    pub fn new(address: IpAddr, source_prefix: u8) -> Self {
+        let source_prefix = source_prefix.min(max_prefix(address));
+        Self {
+            address: mask(address, source_prefix),
+            source_prefix,
+            scope_prefix: 0,
+        }
+    }
+
+    /// Subnet of a client address, with the recommended prefix lengths:
+    /// 24 bits for IPv4, 56 bits for IPv6 (RFC 7871 section 11.1).
+    pub fn for_client(address: IpAddr) -> Self {
+        match address {
+            IpAddr::V4(_) => Self::new(address, 24),
+            IpAddr::V6(_) => Self::new(address, 56),
+        }
+    }
+
+    /// Address family number: 1 for IPv4, 2 for IPv6.
+    pub fn family(&self) -> u16 {
+        match self.address {
+            IpAddr::V4(_) => 1,
+            IpAddr::V6(_) => 2,
+        }
+    }
+
+    pub fn address(&self) -> IpAddr {
+        self.address
+    }
+
+    pub fn source_prefix(&self) -> u8 {
+        self.source_prefix
+    }
+
+    pub fn scope_prefix(&self) -> u8 {
+        self.scope_prefix
+    }
+
+    /// Set the scope prefix, as servers do in responses.
+    pub fn with_scope_prefix(mut self, scope_prefix: u8) -> Self {
+        self.scope_prefix = scope_prefix.min(max_prefix(self.address));
+        self
+    }
+
+    /// Parse option data, `None` if malformed (RFC 7871 section 7.1.2).
+    fn parse(data: &[u8]) -> Option<Self> {
+        let (header, bytes) = (data.get(..4)?, &data[4..]);
+        let family = u16::from_be_bytes([header[0], header[1]]);
+        let (source_prefix, scope_prefix) = (header[2], header[3]);
+
+        let address = match family {
+            1 if bytes.len() <= 4 => {
+                let mut octets = [0; 4];
+                octets[..bytes.len()].copy_from_slice(bytes);
+                IpAddr::V4(Ipv4Addr::from(octets))
+            }
+            2 if bytes.len() <= 16 => {
+                let mut octets = [0; 16];
+                octets[..bytes.len()].copy_from_slice(bytes);
+                IpAddr::V6(Ipv6Addr::from(octets))
+            }
+            _ => return None,
+        };
+
+        // Address is truncated to the source prefix, with zero padding bits.
+        let max_prefix = max_prefix(address);
+        if source_prefix > max_prefix || scope_prefix > max_prefix {
+            return None;
+        }
+        if bytes.len() != address_len(source_prefix) || mask(address, source_prefix) != address {
+            return None;
+        }
+
+        Some(Self {
+            address,
+            source_prefix,
+            scope_prefix,
+        })
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut data = self.family().to_be_bytes().to_vec();
+        data.extend([self.source_prefix, self.scope_prefix]);
+        let len = address_len(self.source_prefix);
+        match self.address {
+            IpAddr::V4(address) => data.extend(&address.octets()[..len]),
+            IpAddr::V6(address) => data.extend(&address.octets()[..len]),
+        }
+        data
+    }
+}
+
+impl fmt::Display for ClientSubnet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            address,
+            source_prefix,
+            scope_prefix,
+        } = self;
+        write!(f, "{address}/{source_prefix}/{scope_prefix}")
+    }
+}
+
+/// Address length in bits.
+fn max_prefix(address: IpAddr) -> u8 {
+    match address {
+        IpAddr::V4(_) => 32,
+        IpAddr::V6(_) => 128,
+    }
+}
+
+/// Bytes needed for the first `prefix` bits of an address.
+fn address_len(prefix: u8) -> usize {
+    (prefix as usize + 7) >> 3
+}
+
+/// Keep the first `prefix` bits of an address.
+fn mask(address: IpAddr, prefix: u8) -> IpAddr {
+    match address {
+        IpAddr::V4(address) => {
+            let mask = u32::MAX
+                .checked_shl(32 - prefix.min(32) as u32)
+                .unwrap_or(0);
+            IpAddr::V4((u32::from(address) & mask).into())
+        }
+        IpAddr::V6(address) => {
+            let mask = u128::MAX
+                .checked_shl(128 - prefix.min(128) as u32)
+                .unwrap_or(0);
+            IpAddr::V6((u128::from(address) & mask).into())
+        }
+    }
+}
+
+impl Message {
+    /// EDNS data of the message, from the first OPT record of the additional section.
+    pub fn edns(&self) -> Option<Edns> {
+        self.additionals.iter().find_map(Edns::from_record)
+    }
+
+    /// Extended DNS errors of the message.
+    pub fn extended_errors(&self) -> Vec<ExtendedError> {
+        self.edns()
+            .map(|edns| edns.extended_errors().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Replace or remove the OPT record of the additional section.
+    pub fn set_edns(&mut self, edns: Option<Edns>) {
+        let position = self
+            .additionals
+            .iter()
+            .position(|record| record.rr_type == ResourceRecordType::OPT);
+        self.additionals
+            .retain(|record| record.rr_type != ResourceRecordType::OPT);
+
+        if let Some(edns) = edns {
+            let position = position.unwrap_or(self.additionals.len());
+            self.additionals.insert(position, edns.to_record());
+        }
+        self.update_counts();
+    }
+
+    /// Full response code, combining header and OPT record bits.
+    pub fn response_code(&self) -> ResponseCode {
+        let extended_bits = self.edns().map_or(0, |edns| edns.extended_rcode);
+        ResponseCode::from_parts(self.header.flags.response_code.header_bits(), extended_bits)
+    }
+
+    /// Set response code in header and OPT record.
+    ///
+    /// Extended codes need an OPT record: one is added if missing.
+    pub fn set_response_code(&mut self, code: ResponseCode) {
+        self.header.flags.response_code = ResponseCode::from(code.header_bits() as u16);
+
+        let mut edns = match self.edns() {
+            Some(edns) => edns,
+            None if code.extended_bits() == 0 => return,
+            None => Edns::new(MIN_UDP_PAYLOAD_SIZE),
+        };
+        edns.extended_rcode = code.extended_bits();
+        self.set_edns(Some(edns));
+    }
+}
@@ -0,0 +1,111 @@
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+use super::DomainName;
+
+/// Largest offset a compression pointer can target (14 bits).
+const MAX_POINTER_OFFSET: usize = 0x3FFF;
+
+/// Message encoder.
+///
+/// Buffer message bytes and remember where each name suffix was written,
+/// so following names can be replaced by a pointer.
+///
+/// Check: https://www.rfc-editor.org/rfc/rfc1035#section-4.1.4
+#[derive(Debug, Default)]
+pub struct Encoder {
+    buf: Vec<u8>,
+    /// Name suffix => offset. `None` if compression is disabled.
+    names: Option<HashMap<DomainName, u16>>,
+}
+
+impl Encoder {
+    /// Create an encoder with name compression enabled.
+    pub fn new() -> Self {
+        Self {
+            buf: Vec::with_capacity(512),
+            names: Some(HashMap::new()),
+        }
+    }
+
+    /// Create an encoder writing all names in full (ex: for canonical-form output).
+    pub fn without_compression() -> Self {
+        Self {
+            buf: Vec::with_capacity(512),
+            names: None,
+        }
+    }
+
+    pub fn is_compression_enabled(&self) -> bool {
+        self.names.is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buf
+    }
+
+    /// Write a name, using a pointer to a previous one if allowed and possible.
+    ///
+    /// `compress` must be `false` for names in RDATA of types that are not
+    /// well known (RFC 3597 section 4).
+    pub fn encode_name(&mut self, name: &DomainName, compress: bool) -> io::Result<()> {
+        let suffixes = std::iter::once(name.clone()).chain(name.parents());
+        for (label, suffix) in name.labels().iter().zip(suffixes) {
+            if let Some(names) = &mut self.names {
+                if let Some(offset) = names.get(&suffix).filter(|_| compress) {
+                    self.buf.write_all(&(0xC000 | offset).to_be_bytes())?;
+                    return Ok(());
+                }
+                if self.buf.len() <= MAX_POINTER_OFFSET {
+                    names.entry(suffix).or_insert(self.buf.len() as u16);
+                }
+            }
+
+            // Write label len
+            self.buf.write_all(&[label.len() as u8])?;
+
+            // Write label
+            self.buf.write_all(label)?;
+        }
+        self.buf.write_all(&[0x00])
+    }
+
+    /// Write data produced by `f` prefixed by its length on 2 bytes (ex: RDATA).
+    pub fn encode_length_prefixed<F>(&mut self, f: F) -> io::Result<()>
+    where
+        F: FnOnce(&mut Self) -> io::Result<()>,
+    {
+        let start = self.buf.len();
+        self.buf.write_all(&[0, 0])?;
+        f(self)?;
+
+        let len = self.buf.len() - start - 2;
+        assert!(len <= 0xFFFF, "Data is too long");
+        self.buf[start..start + 2].copy_from_slice(&(len as u16).to_be_bytes());
+        Ok(())
+    }
+}
+
+impl Write for Encoder {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
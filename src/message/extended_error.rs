@@ -0,0 +1,151 @@
+use std::fmt;
+
+use crate::DnssecError;
+
+macro_rules! extended_error_codes {
+    ($($(#[$doc:meta])* $name:ident = $value:literal, $purpose:literal,)*) => {
+        /// Extended DNS error INFO-CODE.
+        ///
+        /// Check: https://www.iana.org/assignments/dns-parameters/dns-parameters.xhtml#extended-dns-error-codes
+        #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+        pub enum ExtendedErrorCode {
+            $($(#[$doc])* $name,)*
+
+            /// Any value not listed above (unassigned, reserved or private use).
+            Unknown(u16),
+        }
+
+        impl ExtendedErrorCode {
+            /// Purpose of the code, as named in the IANA registry.
+            pub fn purpose(self) -> Option<&'static str> {
+                match self {
+                    $(Self::$name => Some($purpose),)*
+                    Self::Unknown(_) => None,
+                }
+            }
+        }
+
+        impl From<u16> for ExtendedErrorCode {
+            fn from(value: u16) -> Self {
+                match value {
+                    $($value => Self::$name,)*
+                    _ => Self::Unknown(value),
+                }
+            }
+        }
+
+        impl From<ExtendedErrorCode> for u16 {
+            fn from(value: ExtendedErrorCode) -> Self {
+                match value {
+                    $(ExtendedErrorCode::$name => $value,)*
+                    ExtendedErrorCode::Unknown(value) => value,
+                }
+            }
+        }
+    };
+}
+
+extended_error_codes! {
+    /// Error not covered by other codes, see extra text.
+    Other = 0, "Other Error",
+    UnsupportedDnskeyAlgorithm = 1, "Unsupported DNSKEY Algorithm",
+    UnsupportedDsDigestType = 2, "Unsupported DS Digest Type",
+    /// Answer served from cache, after its TTL expired.
+    StaleAnswer = 3, "Stale Answer",
+    /// Answer changed by policy.
+    ForgedAnswer = 4, "Forged Answer",
+    DnssecIndeterminate = 5, "DNSSEC Indeterminate",
+    DnssecBogus = 6, "DNSSEC Bogus",
+    SignatureExpired = 7, "Signature Expired",
+    SignatureNotYetValid = 8, "Signature Not Yet Valid",
+    /// No DNSKEY matches the DS records of the zone.
+    DnskeyMissing = 9, "DNSKEY Missing",
+    RrsigsMissing = 10, "RRSIGs Missing",
+    NoZoneKeyBitSet = 11, "No Zone Key Bit Set",
+    NsecMissing = 12, "NSEC Missing",
+    /// Error response served from cache.
+    CachedError = 13, "Cached Error",
+    NotReady = 14, "Not Ready",
+    /// Name blocked by a policy of the server operator.
+    Blocked = 15, "Blocked",
+    /// Name blocked on request of an external entity.
+    Censored = 16, "Censored",
+    /// Name blocked on request of the client.
+    Filtered = 17, "Filtered",
+    /// Client is not allowed to query this server.
+    Prohibited = 18, "Prohibited",
+    StaleNxdomainAnswer = 19, "Stale NXDOMAIN Answer",
+    NotAuthoritative = 20, "Not Authoritative",
+    NotSupported = 21, "Not Supported",
+    /// No upstream or authoritative server could be reached.
+    NoReachableAuthority = 22, "No Reachable Authority",
+    NetworkError = 23, "Network Error",
+    /// Data received from upstream or authoritative servers is invalid.
+    InvalidData = 24, "Invalid Data",
+}
+
+impl From<&DnssecError> for ExtendedErrorCode {
+    fn from(value: &DnssecError) -> Self {
+        match value {
+            DnssecError::UnsupportedAlgorithm(_) => Self::UnsupportedDnskeyAlgorithm,
+            DnssecError::UnsupportedDigest(_) => Self::UnsupportedDsDigestType,
+            DnssecError::SignatureExpired => Self::SignatureExpired,
+            DnssecError::SignatureNotYetValid => Self::SignatureNotYetValid,
+            DnssecError::MissingSignature => Self::RrsigsMissing,
+            DnssecError::NoTrustedKey => Self::DnskeyMissing,
+            DnssecError::MissingDenial => Self::NsecMissing,
+            _ => Self::DnssecBogus,
+        }
+    }
+}
+
+/// Extended DNS error (RFC 8914): why a response failed, or how it was altered.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ExtendedError {
+    pub code: ExtendedErrorCode,
+    /// Details for humans, may be empty.
+    pub extra_text: String,
+}
+
+impl ExtendedError {
+    pub fn new(code: ExtendedErrorCode, extra_text: impl Into<String>) -> Self {
+        Self {
+            code,
+            extra_text: extra_text.into(),
+        }
+    }
+
+    /// Parse option data, `None` if malformed.
+    pub(crate) fn parse(data: &[u8]) -> Option<Self> {
+        let code = u16::from_be_bytes(data.get(..2)?.try_into().unwrap());
+        let extra_text = std::str::from_utf8(&data[2..]).ok()?;
+
+        Some(Self::new(code.into(), extra_text))
+    }
+
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        let mut data = u16::from(self.code).to_be_bytes().to_vec();
+        data.extend(self.extra_text.as_bytes());
+        data
+    }
+}
+
+impl From<&DnssecError> for ExtendedError {
+    fn from(value: &DnssecError) -> Self {
+        Self::new(value.into(), value.to_string())
+    }
+}
+
+impl fmt::Display for ExtendedError {
+    /// Format as `dig` does: `6 (DNSSEC Bogus): (extra text)`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", u16::from(self.code))?;
+        if let Some(purpose) = self.code.purpose() {
+            write!(f, " ({purpose})")?;
+        }
+        if !self.extra_text.is_empty() {
+            write!(f, ": ({})", self.extra_text)?;
+        }
+        Ok(())
+    }
+}
@@ -1,4 +1,7 @@
-use std::io::{self, Write};
+use std::{
+    fmt,
+    io::{self, Write},
+};
 
 use nom::{
     bits::{bits, streaming::take},
@@ -48,7 +51,10 @@ impl Header {
         flags |= (self.flags.is_truncation as u16) << 9;
         flags |= (self.flags.is_recursion_desired as u16) << 8;
         flags |= (self.flags.is_recursion_available as u16) << 7;
-        flags |= self.flags.response_code as u16;
+        flags |= (self.flags.z as u16) << 6;
+        flags |= (self.flags.is_authentic_data as u16) << 5;
+        flags |= (self.flags.is_checking_disabled as u16) << 4;
+        flags |= self.flags.response_code.header_bits() as u16;
 
         buf.write_all(&self.id.to_be_bytes())?;
         buf.write_all(&flags.to_be_bytes())?;
@@ -69,12 +75,18 @@ pub struct HeaderFlags {
     pub is_truncation: bool,
     pub is_recursion_desired: bool,
     pub is_recursion_available: bool,
+    /// Reserved bit, must be zero.
+    pub z: bool,
+    /// DNSSEC Authentic Data (RFC 4035 section 3.2.3).
+    pub is_authentic_data: bool,
+    /// DNSSEC Checking Disabled (RFC 4035 section 3.2.2).
+    pub is_checking_disabled: bool,
     pub response_code: ResponseCode,
 }
 
 impl HeaderFlags {
     pub fn parse(input: &[u8]) -> IResult<&[u8], Self> {
-        type ParsedFlags = (u8, u8, u8, u8, u8, u8, u8, u8);
+        type ParsedFlags = (u8, u8, u8, u8, u8, u8, u8, u8, u8, u8);
 
         let (
             input,
@@ -85,7 +97,9 @@ impl HeaderFlags {
                 truncation,
                 recursion_desired,
                 recursion_available,
-                _unused,
+                z,
+                authentic_data,
+                checking_disabled,
                 response_code,
             ),
         ) = bits::<_, ParsedFlags, Error<(&[u8], usize)>, _, _>(tuple((
@@ -95,7 +109,9 @@ impl HeaderFlags {
             take(1_usize),
             take(1_usize),
             take(1_usize),
-            take(3_usize),
+            take(1_usize),
+            take(1_usize),
+            take(1_usize),
             take(4_usize),
         )))(input)?;
 
@@ -108,7 +124,10 @@ impl HeaderFlags {
                 is_truncation: truncation != 0,
                 is_recursion_desired: recursion_desired != 0,
                 is_recursion_available: recursion_available != 0,
-                response_code: response_code.into(),
+                z: z != 0,
+                is_authentic_data: authentic_data != 0,
+                is_checking_disabled: checking_disabled != 0,
+                response_code: ResponseCode::from(response_code as u16),
             },
         ))
     }
@@ -175,27 +194,174 @@ impl From<u8> for OpCode {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
-#[repr(u8)]
+impl fmt::Display for OpCode {
+    /// Format opcode mnemonic, as `dig` does.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Query => f.write_str("QUERY"),
+            Self::InverseQuery => f.write_str("IQUERY"),
+            Self::Status => f.write_str("STATUS"),
+            Self::Reserved4 => f.write_str("NOTIFY"),
+            Self::Reserved5 => f.write_str("UPDATE"),
+            Self::Reserved6 => f.write_str("DSO"),
+            _ => write!(f, "RESERVED{}", *self as u8),
+        }
+    }
+}
+
+/// Response code.
+///
+/// Header only holds the lower 4 bits: codes above 15 are extended codes,
+/// whose upper 8 bits are stored in the OPT record (RFC 6891 section 6.1.3).
+///
+/// Check: https://www.iana.org/assignments/dns-parameters/dns-parameters.xhtml#dns-parameters-6
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
 pub enum ResponseCode {
+    /// No Error (NOERROR).
     #[default]
-    NoError = 0,
-    FormatError = 1,
-    ServerFail = 2,
-    NonExistentDomain = 3,
-    NotImplemented = 4,
-    Invalid = 0x0F, // Encoded on 4 bytes
+    NoError,
+    /// Format Error (FORMERR).
+    FormatError,
+    /// Server Failure (SERVFAIL).
+    ServerFail,
+    /// Non-Existent Domain (NXDOMAIN).
+    NonExistentDomain,
+    /// Not Implemented (NOTIMP).
+    NotImplemented,
+    /// Query Refused (REFUSED).
+    Refused,
+    /// Name Exists when it should not (YXDOMAIN).
+    NameExists,
+    /// RR Set Exists when it should not (YXRRSET).
+    RrSetExists,
+    /// RR Set that should exist does not (NXRRSET).
+    RrSetNotExists,
+    /// Server Not Authoritative for zone / Not Authorized (NOTAUTH).
+    NotAuthoritative,
+    /// Name not contained in zone (NOTZONE).
+    NotInZone,
+    /// DSO-TYPE Not Implemented (DSOTYPENI).
+    DsoTypeNotImplemented,
+    /// Bad OPT Version (BADVERS) or TSIG Signature Failure (BADSIG).
+    BadVersion,
+    /// Key not recognized (BADKEY).
+    BadKey,
+    /// Signature out of time window (BADTIME).
+    BadTime,
+    /// Bad TKEY Mode (BADMODE).
+    BadMode,
+    /// Duplicate key name (BADNAME).
+    BadName,
+    /// Algorithm not supported (BADALG).
+    BadAlgorithm,
+    /// Bad Truncation (BADTRUNC).
+    BadTruncation,
+    /// Bad/missing Server Cookie (BADCOOKIE).
+    BadCookie,
+
+    /// Any value not listed above (unassigned, reserved or private use).
+    Unknown(u16),
 }
 
-impl From<u8> for ResponseCode {
-    fn from(value: u8) -> Self {
+impl ResponseCode {
+    /// Lower 4 bits of the code, as stored in the header.
+    pub fn header_bits(self) -> u8 {
+        (u16::from(self) & 0x0F) as u8
+    }
+
+    /// Upper 8 bits of the code, as stored in the OPT record.
+    pub fn extended_bits(self) -> u8 {
+        (u16::from(self) >> 4) as u8
+    }
+
+    /// Build code from header and OPT record parts.
+    pub fn from_parts(header_bits: u8, extended_bits: u8) -> Self {
+        (((extended_bits as u16) << 4) | (header_bits & 0x0F) as u16).into()
+    }
+}
+
+impl From<u16> for ResponseCode {
+    fn from(value: u16) -> Self {
         match value {
             0 => Self::NoError,
             1 => Self::FormatError,
             2 => Self::ServerFail,
             3 => Self::NonExistentDomain,
             4 => Self::NotImplemented,
-            _ => Self::Invalid,
+            5 => Self::Refused,
+            6 => Self::NameExists,
+            7 => Self::RrSetExists,
+            8 => Self::RrSetNotExists,
+            9 => Self::NotAuthoritative,
+            10 => Self::NotInZone,
+            11 => Self::DsoTypeNotImplemented,
+            16 => Self::BadVersion,
+            17 => Self::BadKey,
+            18 => Self::BadTime,
+            19 => Self::BadMode,
+            20 => Self::BadName,
+            21 => Self::BadAlgorithm,
+            22 => Self::BadTruncation,
+            23 => Self::BadCookie,
+            _ => Self::Unknown(value),
+        }
+    }
+}
+
+impl From<ResponseCode> for u16 {
+    fn from(value: ResponseCode) -> Self {
+        match value {
+            ResponseCode::NoError => 0,
+            ResponseCode::FormatError => 1,
+            ResponseCode::ServerFail => 2,
+            ResponseCode::NonExistentDomain => 3,
+            ResponseCode::NotImplemented => 4,
+            ResponseCode::Refused => 5,
+            ResponseCode::NameExists => 6,
+            ResponseCode::RrSetExists => 7,
+            ResponseCode::RrSetNotExists => 8,
+            ResponseCode::NotAuthoritative => 9,
+            ResponseCode::NotInZone => 10,
+            ResponseCode::DsoTypeNotImplemented => 11,
+            ResponseCode::BadVersion => 16,
+            ResponseCode::BadKey => 17,
+            ResponseCode::BadTime => 18,
+            ResponseCode::BadMode => 19,
+            ResponseCode::BadName => 20,
+            ResponseCode::BadAlgorithm => 21,
+            ResponseCode::BadTruncation => 22,
+            ResponseCode::BadCookie => 23,
+            ResponseCode::Unknown(value) => value,
         }
     }
 }
+
+impl fmt::Display for ResponseCode {
+    /// Format code mnemonic, as `dig` does.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mnemonic = match self {
+            Self::NoError => "NOERROR",
+            Self::FormatError => "FORMERR",
+            Self::ServerFail => "SERVFAIL",
+            Self::NonExistentDomain => "NXDOMAIN",
+            Self::NotImplemented => "NOTIMP",
+            Self::Refused => "REFUSED",
+            Self::NameExists => "YXDOMAIN",
+            Self::RrSetExists => "YXRRSET",
+            Self::RrSetNotExists => "NXRRSET",
+            Self::NotAuthoritative => "NOTAUTH",
+            Self::NotInZone => "NOTZONE",
+            Self::DsoTypeNotImplemented => "DSOTYPENI",
+            Self::BadVersion => "BADVERS",
+            Self::BadKey => "BADKEY",
+            Self::BadTime => "BADTIME",
+            Self::BadMode => "BADMODE",
+            Self::BadName => "BADNAME",
+            Self::BadAlgorithm => "BADALG",
+            Self::BadTruncation => "BADTRUNC",
+            Self::BadCookie => "BADCOOKIE",
+            Self::Unknown(value) => return write!(f, "RCODE{value}"),
+        };
+        f.write_str(mnemonic)
+    }
+}
@@ -0,0 +1,165 @@
+//! Internationalized labels: Punycode (RFC 3492) and IDNA conversions.
+//!
+//! Mapping follows UTS #46 as far as it can be done without its data tables:
+//! labels are lower-cased, but not NFC normalized.
+
+const BASE: u32 = 36;
+const T_MIN: u32 = 1;
+const T_MAX: u32 = 26;
+const SKEW: u32 = 38;
+const DAMP: u32 = 700;
+const INITIAL_BIAS: u32 = 72;
+const INITIAL_N: u32 = 0x80;
+
+/// Prefix of A-labels (RFC 5890 section 2.3.2.5).
+const ACE_PREFIX: &str = "xn--";
+
+/// Dots recognized as label separators (UTS #46 section 2.3).
+pub(crate) const DOTS: [char; 4] = ['.', '\u{3002}', '\u{FF0E}', '\u{FF61}'];
+
+/// Convert a label with non ASCII characters to its A-label (`xn--...`).
+pub(crate) fn to_ascii(label: &str) -> Option<String> {
+    let label: Vec<char> = label.chars().flat_map(char::to_lowercase).collect();
+    if !is_valid(&label) {
+        return None;
+    }
+    Some(format!("{ACE_PREFIX}{}", encode(&label)?))
+}
+
+/// Convert an A-label to its U-label, `None` if `label` is not a valid A-label.
+pub(crate) fn to_unicode(label: &[u8]) -> Option<String> {
+    let label = std::str::from_utf8(label).ok()?.to_ascii_lowercase();
+    let chars = decode(label.strip_prefix(ACE_PREFIX)?)?;
+
+    // Must convert back to the same A-label.
+    let unicode: String = chars.into_iter().collect();
+    (to_ascii(&unicode)? == label).then_some(unicode)
+}
+
+/// Check a lower-cased label: some non ASCII characters, letters, digits and
+/// hyphens for ASCII ones, no leading or trailing hyphen (UTS #46 section 4.1).
+fn is_valid(label: &[char]) -> bool {
+    !label.is_empty()
+        && !label.iter().all(char::is_ascii)
+        && label.first() != Some(&'-')
+        && label.last() != Some(&'-')
+        && label.get(2..4) != Some(&['-', '-'][..])
+        && label.iter().all(|c| match c {
+            'a'..='z' | '0'..='9' | '-' => true,
+            c => !c.is_ascii() && !c.is_control() && !c.is_whitespace() && !DOTS.contains(c),
+        })
+}
+
+/// Punycode encoding (RFC 3492 section 6.3).
+fn encode(input: &[char]) -> Option<String> {
+    let mut output: String = input.iter().filter(|x| x.is_ascii()).collect();
+    let basic_len = output.len() as u32;
+    if basic_len > 0 {
+        output.push('-');
+    }
+
+    let (mut n, mut delta, mut bias) = (INITIAL_N, 0_u32, INITIAL_BIAS);
+    let mut handled = basic_len;
+    while (handled as usize) < input.len() {
+        let m = input.iter().map(|&x| x as u32).filter(|&x| x >= n).min()?;
+        delta = delta.checked_add((m - n).checked_mul(handled + 1)?)?;
+        n = m;
+
+        for c in input.iter().map(|&x| x as u32) {
+            if c < n {
+                delta = delta.checked_add(1)?;
+            }
+            if c == n {
+                let mut q = delta;
+                let mut k = BASE;
+                loop {
+                    let t = threshold(k, bias);
+                    if q < t {
+                        break;
+                    }
+                    output.push(digit(t + (q - t) % (BASE - t)));
+                    q = (q - t) / (BASE - t);
+                    k += BASE;
+                }
+                output.push(digit(q));
+                bias = adapt(delta, handled + 1, handled == basic_len);
+                delta = 0;
+                handled += 1;
+            }
+        }
+        delta = delta.checked_add(1)?;
+        n += 1;
+    }
+    Some(output)
+}
+
+/// Punycode decoding (RFC 3492 section 6.2).
+fn decode(input: &str) -> Option<Vec<char>> {
+    let (basic, digits) = match input.rfind('-') {
+        Some(idx) => (&input[..idx], &input[idx + 1..]),
+        None => ("", input),
+    };
+    if !basic.is_ascii() {
+        return None;
+    }
+
+    let mut output: Vec<char> = basic.chars().collect();
+    let (mut n, mut i, mut bias) = (INITIAL_N, 0_u32, INITIAL_BIAS);
+    let mut digits = digits.bytes().peekable();
+    while digits.peek().is_some() {
+        let old_i = i;
+        let mut w = 1_u32;
+        let mut k = BASE;
+        loop {
+            let d = digit_value(digits.next()?)?;
+            i = i.checked_add(d.checked_mul(w)?)?;
+            let t = threshold(k, bias);
+            if d < t {
+                break;
+            }
+            w = w.checked_mul(BASE - t)?;
+            k += BASE;
+        }
+
+        let len = output.len() as u32 + 1;
+        bias = adapt(i - old_i, len, old_i == 0);
+        n = n.checked_add(i / len)?;
+        i %= len;
+        output.insert(i as usize, char::from_u32(n)?);
+        i += 1;
+    }
+    Some(output)
+}
+
+fn threshold(k: u32, bias: u32) -> u32 {
+    k.saturating_sub(bias).clamp(T_MIN, T_MAX)
+}
+
+/// Bias adaptation (RFC 3492 section 6.1).
+fn adapt(delta: u32, points: u32, is_first: bool) -> u32 {
+    let mut delta = if is_first { delta / DAMP } else { delta / 2 };
+    delta += delta / points;
+
+    let mut k = 0;
+    while delta > ((BASE - T_MIN) * T_MAX) / 2 {
+        delta /= BASE - T_MIN;
+        k += BASE;
+    }
+    k + (BASE - T_MIN + 1) * delta / (delta + SKEW)
+}
+
+fn digit(value: u32) -> char {
+    match value {
+        0..=25 => (b'a' + value as u8) as char,
+        _ => (b'0' + (value - 26) as u8) as char,
+    }
+}
+
+fn digit_value(c: u8) -> Option<u32> {
+    match c {
+        b'a'..=b'z' => Some((c - b'a') as u32),
+        b'A'..=b'Z' => Some((c - b'A') as u32),
+        b'0'..=b'9' => Some((c - b'0') as u32 + 26),
+        _ => None,
+    }
+}
@@ -0,0 +1,634 @@
+//! JSON representation of messages.
+//!
+//! Check: https://www.rfc-editor.org/rfc/rfc8427
+
+use std::fmt::{self, Write as _};
+
+use crate::JsonError;
+
+use super::{
+    codec::{decode_hex, encode_hex},
+    AnswerSection, DomainName, Encoder, Header, HeaderFlags, Message, QuestionSection, RData,
+    ResourceRecordClass, ResourceRecordType, ResponseCode,
+};
+
+/// JSON value, with object members kept in order.
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(i64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl From<bool> for JsonValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<u8> for JsonValue {
+    fn from(value: u8) -> Self {
+        Self::Number(value as i64)
+    }
+}
+
+impl From<u16> for JsonValue {
+    fn from(value: u16) -> Self {
+        Self::Number(value as i64)
+    }
+}
+
+impl From<u32> for JsonValue {
+    fn from(value: u32) -> Self {
+        Self::Number(value as i64)
+    }
+}
+
+impl From<String> for JsonValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl fmt::Display for JsonValue {
+    /// Format compact JSON.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Null => f.write_str("null"),
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::Number(value) => write!(f, "{value}"),
+            Self::String(value) => fmt_string(value, f),
+            Self::Array(values) => {
+                f.write_char('[')?;
+                for (idx, value) in values.iter().enumerate() {
+                    if idx > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                f.write_char(']')
+            }
+            Self::Object(members) => {
+                f.write_char('{')?;
+                for (idx, (key, value)) in members.iter().enumerate() {
+                    if idx > 0 {
+                        f.write_char(',')?;
+                    }
+                    fmt_string(key, f)?;
+                    write!(f, ":{value}")?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+fn fmt_string(value: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_char('"')?;
+    for c in value.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+/// Deepest nesting of arrays and objects accepted, bounding parser recursion.
+const MAX_DEPTH: usize = 64;
+
+/// Recursive descent JSON parser.
+struct JsonParser<'a> {
+    input: &'a [u8],
+    pos: usize,
+    /// Arrays and objects being parsed.
+    depth: usize,
+}
+
+impl<'a> JsonParser<'a> {
+    /// Parse a single JSON document.
+    fn parse(s: &'a str) -> Result<JsonValue, JsonError> {
+        let mut parser = Self {
+            input: s.as_bytes(),
+            pos: 0,
+            depth: 0,
+        };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        match parser.pos == parser.input.len() {
+            true => Ok(value),
+            false => Err(parser.error()),
+        }
+    }
+
+    fn error(&self) -> JsonError {
+        JsonError::Syntax(self.pos)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let c = self.peek()?;
+        self.pos += 1;
+        Some(c)
+    }
+
+    fn expect(&mut self, c: u8) -> Result<(), JsonError> {
+        match self.next() {
+            Some(x) if x == c => Ok(()),
+            _ => Err(JsonError::Syntax(self.pos.saturating_sub(1))),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn value(&mut self) -> Result<JsonValue, JsonError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'{' | b'[') => self.nested(),
+            Some(b'"') => self.string().map(JsonValue::String),
+            Some(b't') => self.literal("true", JsonValue::Bool(true)),
+            Some(b'f') => self.literal("false", JsonValue::Bool(false)),
+            Some(b'n') => self.literal("null", JsonValue::Null),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            _ => Err(self.error()),
+        }
+    }
+
+    fn literal(&mut self, text: &str, value: JsonValue) -> Result<JsonValue, JsonError> {
+        if !self.input[self.pos..].starts_with(text.as_bytes()) {
+            return Err(self.error());
+        }
+        self.pos += text.len();
+        Ok(value)
+    }
+
+    /// Parse an integer: other numbers have no use in messages.
+    fn number(&mut self) -> Result<JsonValue, JsonError> {
+        let start = self.pos;
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+        while self.peek().is_some_and(|x| x.is_ascii_digit()) {
+            self.pos += 1;
+        }
+
+        std::str::from_utf8(&self.input[start..self.pos])
+            .ok()
+            .and_then(|x| x.parse().ok())
+            .map(JsonValue::Number)
+            .ok_or(JsonError::Syntax(start))
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        self.expect(b'"')?;
+        let mut output = vec![];
+
+        loop {
+            match self.next().ok_or_else(|| self.error())? {
+                b'"' => break,
+                b'\\' => {
+                    let c = match self.next().ok_or_else(|| self.error())? {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.unicode_escape()?,
+                        _ => return Err(JsonError::Syntax(self.pos - 1)),
+                    };
+                    let mut buf = [0; 4];
+                    output.extend(c.encode_utf8(&mut buf).as_bytes());
+                }
+                c if c < 0x20 => return Err(JsonError::Syntax(self.pos - 1)),
+                c => output.push(c),
+            }
+        }
+
+        // Input is a string and escapes are valid chars.
+        String::from_utf8(output).map_err(|_| self.error())
+    }
+
+    /// Decode `\uXXXX`, combining surrogate pairs.
+    fn unicode_escape(&mut self) -> Result<char, JsonError> {
+        let high = self.hex4()?;
+        let code = match high {
+            0xD800..=0xDBFF => {
+                self.expect(b'\\')?;
+                self.expect(b'u')?;
+                let low = self.hex4()?;
+                if !(0xDC00..=0xDFFF).contains(&low) {
+                    return Err(self.error());
+                }
+                0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+            }
+            _ => high,
+        };
+        char::from_u32(code).ok_or_else(|| self.error())
+    }
+
+    fn hex4(&mut self) -> Result<u32, JsonError> {
+        let digits = self
+            .input
+            .get(self.pos..self.pos + 4)
+            .and_then(|x| std::str::from_utf8(x).ok())
+            .filter(|x| x.bytes().all(|c| c.is_ascii_hexdigit()))
+            .ok_or_else(|| self.error())?;
+        let value = u32::from_str_radix(digits, 16).map_err(|_| self.error())?;
+        self.pos += 4;
+        Ok(value)
+    }
+
+    /// Parse an array or object, up to `MAX_DEPTH` levels deep.
+    fn nested(&mut self) -> Result<JsonValue, JsonError> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error());
+        }
+        self.depth += 1;
+        let value = match self.peek() {
+            Some(b'{') => self.object(),
+            _ => self.array(),
+        };
+        self.depth -= 1;
+        value
+    }
+
+    fn array(&mut self) -> Result<JsonValue, JsonError> {
+        self.expect(b'[')?;
+        let mut values = vec![];
+
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(JsonValue::Array(values));
+        }
+
+        loop {
+            values.push(self.value()?);
+            self.skip_whitespace();
+            match self.next() {
+                Some(b',') => continue,
+                Some(b']') => return Ok(JsonValue::Array(values)),
+                _ => return Err(JsonError::Syntax(self.pos.saturating_sub(1))),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<JsonValue, JsonError> {
+        self.expect(b'{')?;
+        let mut members = vec![];
+
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(JsonValue::Object(members));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(b':')?;
+            members.push((key, self.value()?));
+
+            self.skip_whitespace();
+            match self.next() {
+                Some(b',') => continue,
+                Some(b'}') => return Ok(JsonValue::Object(members)),
+                _ => return Err(JsonError::Syntax(self.pos.saturating_sub(1))),
+            }
+        }
+    }
+}
+
+/// Typed access to the members of a JSON object.
+struct Members<'a>(&'a [(String, JsonValue)]);
+
+impl<'a> Members<'a> {
+    fn new(value: &'a JsonValue) -> Result<Self, JsonError> {
+        match value {
+            JsonValue::Object(members) => Ok(Self(members)),
+            _ => Err(JsonError::InvalidMember("object")),
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<&'a JsonValue> {
+        self.0.iter().find(|(x, _)| x == key).map(|(_, x)| x)
+    }
+
+    fn int<T: TryFrom<i64>>(&self, key: &'static str) -> Result<Option<T>, JsonError> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(JsonValue::Number(x)) => T::try_from(*x)
+                .map(Some)
+                .map_err(|_| JsonError::InvalidMember(key)),
+            Some(_) => Err(JsonError::InvalidMember(key)),
+        }
+    }
+
+    /// Read a flag, given as a boolean or as 0 / 1.
+    fn flag(&self, key: &'static str) -> Result<bool, JsonError> {
+        match self.get(key) {
+            None => Ok(false),
+            Some(JsonValue::Bool(x)) => Ok(*x),
+            Some(JsonValue::Number(x @ (0 | 1))) => Ok(*x == 1),
+            Some(_) => Err(JsonError::InvalidMember(key)),
+        }
+    }
+
+    fn str(&self, key: &'static str) -> Result<Option<&'a str>, JsonError> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(JsonValue::String(x)) => Ok(Some(x)),
+            Some(_) => Err(JsonError::InvalidMember(key)),
+        }
+    }
+
+    fn array(&self, key: &'static str) -> Result<&'a [JsonValue], JsonError> {
+        match self.get(key) {
+            None => Ok(&[]),
+            Some(JsonValue::Array(x)) => Ok(x),
+            Some(_) => Err(JsonError::InvalidMember(key)),
+        }
+    }
+
+    fn rr_type(&self) -> Result<ResourceRecordType, JsonError> {
+        match (self.int::<u16>("TYPE")?, self.str("TYPEname")?) {
+            (Some(value), _) => Ok(value.into()),
+            (None, Some(name)) => name
+                .parse()
+                .map_err(|_| JsonError::InvalidMember("TYPEname")),
+            (None, None) => Err(JsonError::MissingMember("TYPE")),
+        }
+    }
+
+    fn rr_class(&self) -> Result<ResourceRecordClass, JsonError> {
+        match (self.int::<u16>("CLASS")?, self.str("CLASSname")?) {
+            (Some(value), _) => Ok(value.into()),
+            (None, Some(name)) => name
+                .parse()
+                .map_err(|_| JsonError::InvalidMember("CLASSname")),
+            (None, None) => Ok(ResourceRecordClass::IN),
+        }
+    }
+}
+
+impl Header {
+    pub fn to_json(&self) -> String {
+        JsonValue::Object(self.json_members()).to_string()
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, JsonError> {
+        Self::from_json_members(&Members::new(&JsonParser::parse(s)?)?)
+    }
+
+    fn json_members(&self) -> Vec<(String, JsonValue)> {
+        let flags = &self.flags;
+        vec![
+            ("ID".to_string(), self.id.into()),
+            ("QR".to_string(), (flags.qr as u8 == 1).into()),
+            ("Opcode".to_string(), (flags.opcode as u8).into()),
+            ("AA".to_string(), flags.is_authoritative_answer.into()),
+            ("TC".to_string(), flags.is_truncation.into()),
+            ("RD".to_string(), flags.is_recursion_desired.into()),
+            ("RA".to_string(), flags.is_recursion_available.into()),
+            ("Z".to_string(), flags.z.into()),
+            ("AD".to_string(), flags.is_authentic_data.into()),
+            ("CD".to_string(), flags.is_checking_disabled.into()),
+            (
+                "RCODE".to_string(),
+                flags.response_code.header_bits().into(),
+            ),
+            ("QDCOUNT".to_string(), self.question_count.into()),
+            ("ANCOUNT".to_string(), self.answer_count.into()),
+            (
+                "NSCOUNT".to_string(),
+                self.authority_resource_record_count.into(),
+            ),
+            (
+                "ARCOUNT".to_string(),
+                self.additional_resource_record_count.into(),
+            ),
+        ]
+    }
+
+    fn from_json_members(members: &Members) -> Result<Self, JsonError> {
+        let opcode = members.int::<u8>("Opcode")?.unwrap_or(0);
+        let rcode = members.int::<u8>("RCODE")?.unwrap_or(0);
+        if opcode > 0xF {
+            return Err(JsonError::InvalidMember("Opcode"));
+        }
+        if rcode > 0xF {
+            return Err(JsonError::InvalidMember("RCODE"));
+        }
+
+        Ok(Self {
+            id: members.int("ID")?.ok_or(JsonError::MissingMember("ID"))?,
+            flags: HeaderFlags {
+                qr: (members.flag("QR")? as u8).into(),
+                opcode: opcode.into(),
+                is_authoritative_answer: members.flag("AA")?,
+                is_truncation: members.flag("TC")?,
+                is_recursion_desired: members.flag("RD")?,
+                is_recursion_available: members.flag("RA")?,
+                z: members.flag("Z")?,
+                is_authentic_data: members.flag("AD")?,
+                is_checking_disabled: members.flag("CD")?,
+                response_code: ResponseCode::from(rcode as u16),
+            },
+            question_count: members.int("QDCOUNT")?.unwrap_or(0),
+            answer_count: members.int("ANCOUNT")?.unwrap_or(0),
+            authority_resource_record_count: members.int("NSCOUNT")?.unwrap_or(0),
+            additional_resource_record_count: members.int("ARCOUNT")?.unwrap_or(0),
+        })
+    }
+}
+
+impl QuestionSection {
+    pub fn to_json(&self) -> String {
+        self.to_json_value().to_string()
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, JsonError> {
+        Self::from_json_value(&JsonParser::parse(s)?)
+    }
+
+    fn to_json_value(&self) -> JsonValue {
+        JsonValue::Object(vec![
+            ("NAME".to_string(), self.name.to_string().into()),
+            ("TYPE".to_string(), u16::from(self.rr_type).into()),
+            ("TYPEname".to_string(), self.rr_type.to_string().into()),
+            ("CLASS".to_string(), u16::from(self.rr_class).into()),
+            ("CLASSname".to_string(), self.rr_class.to_string().into()),
+        ])
+    }
+
+    fn from_json_value(value: &JsonValue) -> Result<Self, JsonError> {
+        let members = Members::new(value)?;
+        Ok(Self {
+            name: json_name(&members)?,
+            rr_type: members.rr_type()?,
+            rr_class: members.rr_class()?,
+        })
+    }
+}
+
+impl AnswerSection {
+    /// Format the record as JSON, RDATA both as hexadecimal and presentation format.
+    pub fn to_json(&self) -> String {
+        self.to_json_value().to_string()
+    }
+
+    /// Read a record from JSON, using `RDATAHEX` if present, or the `rdata<TYPE>` member.
+    pub fn from_json(s: &str) -> Result<Self, JsonError> {
+        Self::from_json_value(&JsonParser::parse(s)?)
+    }
+
+    fn to_json_value(&self) -> JsonValue {
+        let mut encoder = Encoder::without_compression();
+        self.data
+            .encode_into(&mut encoder)
+            .expect("Encoding to memory cannot fail");
+        let rdata = encoder.as_bytes();
+
+        let mut members = vec![
+            ("NAME".to_string(), self.name.to_string().into()),
+            ("TYPE".to_string(), u16::from(self.rr_type).into()),
+            ("TYPEname".to_string(), self.rr_type.to_string().into()),
+            ("CLASS".to_string(), u16::from(self.rr_class).into()),
+            ("CLASSname".to_string(), self.rr_class.to_string().into()),
+            ("TTL".to_string(), self.ttl.into()),
+            ("RDLENGTH".to_string(), (rdata.len() as u32).into()),
+            (
+                "RDATAHEX".to_string(),
+                encode_hex(rdata).to_uppercase().into(),
+            ),
+        ];
+
+        // Types without a specific presentation format only have the hexadecimal form.
+        if !matches!(self.data, RData::Opt(_) | RData::Unknown(..)) {
+            members.push((
+                format!("rdata{}", self.rr_type),
+                self.data.to_string().into(),
+            ));
+        }
+
+        JsonValue::Object(members)
+    }
+
+    fn from_json_value(value: &JsonValue) -> Result<Self, JsonError> {
+        let members = Members::new(value)?;
+        let rr_type = members.rr_type()?;
+
+        let data = match members.str("RDATAHEX")? {
+            Some(hex) => {
+                let data = decode_hex(hex).ok_or(JsonError::InvalidMember("RDATAHEX"))?;
+                let (_, rdata) = RData::parse(&data, rr_type, &data)
+                    .map_err(|_| JsonError::InvalidMember("RDATAHEX"))?;
+                rdata
+            }
+            None => match members.get(&format!("rdata{rr_type}")) {
+                Some(JsonValue::String(text)) => RData::from_presentation(rr_type, text)?,
+                Some(_) => return Err(JsonError::InvalidMember("rdata")),
+                None => return Err(JsonError::MissingMember("RDATAHEX")),
+            },
+        };
+
+        Ok(Self {
+            name: json_name(&members)?,
+            rr_type,
+            rr_class: members.rr_class()?,
+            ttl: members.int("TTL")?.ok_or(JsonError::MissingMember("TTL"))?,
+            data,
+        })
+    }
+}
+
+impl Message {
+    /// Format the message as a JSON object: header fields and one array per section.
+    pub fn to_json(&self) -> String {
+        let records = |x: &[AnswerSection]| {
+            JsonValue::Array(x.iter().map(AnswerSection::to_json_value).collect())
+        };
+
+        let mut members = self.counted_header().json_members();
+        members.extend([
+            (
+                "questionRRs".to_string(),
+                JsonValue::Array(
+                    self.questions
+                        .iter()
+                        .map(QuestionSection::to_json_value)
+                        .collect(),
+                ),
+            ),
+            ("answerRRs".to_string(), records(&self.answers)),
+            ("authorityRRs".to_string(), records(&self.authorities)),
+            ("additionalRRs".to_string(), records(&self.additionals)),
+        ]);
+
+        JsonValue::Object(members).to_string()
+    }
+
+    /// Read a message from JSON.
+    ///
+    /// `messageOctetsHEX` is decoded if present, otherwise the message is
+    /// built from header fields and sections (counts follow sections).
+    pub fn from_json(s: &str) -> Result<Self, JsonError> {
+        let value = JsonParser::parse(s)?;
+        let members = Members::new(&value)?;
+
+        if let Some(hex) = members.str("messageOctetsHEX")? {
+            let data = decode_hex(hex).ok_or(JsonError::InvalidMember("messageOctetsHEX"))?;
+            let (_, message) = Message::parse(&data)?;
+            return Ok(message);
+        }
+
+        let records = |key| {
+            members
+                .array(key)?
+                .iter()
+                .map(AnswerSection::from_json_value)
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        let mut message = Self {
+            header: Header::from_json_members(&members)?,
+            questions: members
+                .array("questionRRs")?
+                .iter()
+                .map(QuestionSection::from_json_value)
+                .collect::<Result<_, _>>()?,
+            answers: records("answerRRs")?,
+            authorities: records("authorityRRs")?,
+            additionals: records("additionalRRs")?,
+        };
+        message.update_counts();
+        Ok(message)
+    }
+}
+
+fn json_name(members: &Members) -> Result<DomainName, JsonError> {
+    members
+        .str("NAME")?
+        .ok_or(JsonError::MissingMember("NAME"))?
+        .parse()
+        .map_err(|_| JsonError::InvalidMember("NAME"))
+}
@@ -3,22 +3,28 @@ use nom::{
     branch::alt,
     bytes::complete::tag,
     combinator::{success, value, verify},
-    error::Error,
+    error::{Error, ErrorKind},
     multi::{length_data, many_till},
     number::complete::be_u8,
     sequence::tuple,
     IResult, Parser,
 };
 
+use super::{
+    domain_name::{MAX_LABEL_LEN, MAX_NAME_LEN},
+    DomainName,
+};
+
 /// Parse label output.
 ///
-/// List of URL segment + optional offset in original message if data is compressed.
-pub type ParseLabelOutput = (Vec<String>, Option<u16>);
+/// Name segments + optional offset in original message if data is compressed.
+pub type ParseLabelOutput = (DomainName, Option<u16>);
 
 pub fn parse(input: &[u8]) -> IResult<&[u8], ParseLabelOutput> {
+    let start = input;
     let (input, (names, (_, offset))) = many_till(
-        // Map each "segment" into a string.
-        length_data(be_u8).map(data_to_string),
+        // Map each "segment" into a label.
+        length_data(verify(be_u8, |len| *len as usize <= MAX_LABEL_LEN)).map(<[u8]>::to_vec),
         // Until:
         alt((
             // EOT.
@@ -42,24 +48,89 @@ pub fn parse(input: &[u8]) -> IResult<&[u8], ParseLabelOutput> {
         )),
     )(input)?;
 
-    Ok((input, (names, offset)))
+    let name = DomainName::from_labels(names)
+        .map_err(|_| nom::Err::Error(Error::new(start, ErrorKind::TooLarge)))?;
+
+    Ok((input, (name, offset)))
 }
 
-fn data_to_string(input: &[u8]) -> String {
-    String::from_utf8_lossy(input).to_string()
+/// Read the full name starting at `input`, following compression pointers.
+///
+/// `input` must be a slice of `msg_input`.
+pub fn resolve<'a>(
+    msg_input: &'a [u8],
+    input: &'a [u8],
+) -> Result<DomainName, nom::Err<Error<&'a [u8]>>> {
+    let mut labels = vec![];
+    walk(msg_input, input, |label| labels.push(label))?;
+
+    DomainName::from_labels(labels)
+        .map_err(|_| nom::Err::Error(Error::new(input, ErrorKind::TooLarge)))
 }
 
-pub fn resolve_offsets(input: &[u8], offset: Option<u16>) -> IResult<&[u8], Vec<String>> {
-    match offset {
-        None => Ok((input, vec![])),
-        Some(idx) => {
-            let (input, (next_labels, next_offset)) = parse(&input[idx as usize..])?;
-            assert!(
-                next_offset.is_none(),
-                "Chaining offset in compressed message is not supported"
-            );
-
-            Ok((input, next_labels))
+/// Visit each label of the name starting at `input`, following compression pointers.
+///
+/// `input` must be a slice of `msg_input`. Pointers must target a prior
+/// occurrence, which rejects both forward pointers and loops.
+///
+/// Return input following the name (local part only).
+pub fn walk<'a, F>(msg_input: &'a [u8], input: &'a [u8], mut f: F) -> IResult<&'a [u8], ()>
+where
+    F: FnMut(&'a [u8]),
+{
+    let fail = |at: &'a [u8], kind| nom::Err::Error(Error::new(at, kind));
+
+    let mut name_len = 1;
+    let mut pos = position(msg_input, input).ok_or_else(|| fail(input, ErrorKind::Verify))?;
+    // Pointers must strictly go backward from here.
+    let mut limit = pos;
+    // End of the local part of the name, once known.
+    let mut end = None;
+
+    loop {
+        let len = *msg_input
+            .get(pos)
+            .ok_or_else(|| fail(&msg_input[pos..], ErrorKind::Eof))?;
+
+        match len {
+            0 => {
+                let end = end.unwrap_or(pos + 1);
+                return Ok((&msg_input[end..], ()));
+            }
+            1..=0x3F => {
+                let label = msg_input
+                    .get(pos + 1..pos + 1 + len as usize)
+                    .ok_or_else(|| fail(&msg_input[pos..], ErrorKind::Eof))?;
+
+                name_len += label.len() + 1;
+                if name_len > MAX_NAME_LEN {
+                    return Err(fail(&msg_input[pos..], ErrorKind::TooLarge));
+                }
+
+                f(label);
+                pos += label.len() + 1;
+            }
+            0xC0..=0xFF => {
+                let low = *msg_input
+                    .get(pos + 1)
+                    .ok_or_else(|| fail(&msg_input[pos..], ErrorKind::Eof))?;
+
+                let target = (((len & 0x3F) as usize) << 8) | low as usize;
+                if target >= limit {
+                    return Err(fail(&msg_input[pos..], ErrorKind::Verify));
+                }
+
+                end = end.or(Some(pos + 2));
+                limit = target;
+                pos = target;
+            }
+            // 0b01 and 0b10 prefixes are extended / reserved label types.
+            _ => return Err(fail(&msg_input[pos..], ErrorKind::Tag)),
         }
     }
 }
+
+fn position(msg_input: &[u8], input: &[u8]) -> Option<usize> {
+    let pos = (input.as_ptr() as usize).checked_sub(msg_input.as_ptr() as usize)?;
+    (pos <= msg_input.len()).then_some(pos)
+}
@@ -0,0 +1,361 @@
+use std::fmt;
+
+use nom::{
+    number::complete::{be_u16, be_u32},
+    IResult,
+};
+
+use crate::{MessageSection, ParseError, ParseErrorKind};
+
+use super::{
+    domain_name::fmt_label, labels, rdata, AnswerSection, DomainName, Header, Message,
+    QuestionSection, RData, ResourceRecordClass, ResourceRecordType,
+};
+
+/// Parse an item from `input`, given full message and item location.
+type ItemParser<'a, T> = fn(&'a [u8], &'a [u8], MessageSection, usize) -> IResult<&'a [u8], T>;
+
+/// Size of the message header.
+const HEADER_LEN: usize = 12;
+
+/// Borrowed view over an encoded message.
+///
+/// Only the header is decoded upfront: sections are iterated and names are
+/// decoded on demand, without allocation.
+#[derive(Debug, Clone, Copy)]
+pub struct MessageRef<'a> {
+    input: &'a [u8],
+    header: Header,
+}
+
+impl<'a> MessageRef<'a> {
+    pub fn parse(input: &'a [u8]) -> Result<Self, ParseError> {
+        let (_, header) = Header::parse(input)
+            .map_err(|e| ParseError::new(MessageSection::Header, 0, input, e))?;
+        Ok(Self { input, header })
+    }
+
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.input
+    }
+
+    pub fn questions(&self) -> Questions<'a> {
+        Section {
+            msg_input: self.input,
+            pos: Ok(HEADER_LEN),
+            section: MessageSection::Question,
+            index: 0,
+            remaining: self.header.question_count,
+            // Location is only kept by records: errors get it from the iterator.
+            parser: |msg_input, input, _, _| QuestionRef::parse(msg_input, input),
+        }
+    }
+
+    pub fn answers(&self) -> Records<'a> {
+        let pos = self.questions().end();
+        self.records(pos, MessageSection::Answer, self.header.answer_count)
+    }
+
+    pub fn authorities(&self) -> Records<'a> {
+        let pos = self.answers().end();
+        self.records(
+            pos,
+            MessageSection::Authority,
+            self.header.authority_resource_record_count,
+        )
+    }
+
+    pub fn additionals(&self) -> Records<'a> {
+        let pos = self.authorities().end();
+        self.records(
+            pos,
+            MessageSection::Additional,
+            self.header.additional_resource_record_count,
+        )
+    }
+
+    /// Decode the full message.
+    pub fn to_message(&self) -> Result<Message, ParseError> {
+        Ok(Message {
+            header: self.header,
+            questions: self
+                .questions()
+                .map(|x| x.map(|x| x.to_question()))
+                .collect::<Result<_, _>>()?,
+            answers: collect_records(self.answers())?,
+            authorities: collect_records(self.authorities())?,
+            additionals: collect_records(self.additionals())?,
+        })
+    }
+
+    fn records(
+        &self,
+        pos: Result<usize, ParseError>,
+        section: MessageSection,
+        count: u16,
+    ) -> Records<'a> {
+        Section {
+            msg_input: self.input,
+            pos,
+            section,
+            index: 0,
+            remaining: count,
+            parser: RecordRef::parse,
+        }
+    }
+}
+
+fn collect_records(records: Records<'_>) -> Result<Vec<AnswerSection>, ParseError> {
+    records.map(|x| x?.to_record()).collect()
+}
+
+/// Borrowed name, possibly compressed.
+#[derive(Clone, Copy)]
+pub struct NameRef<'a> {
+    msg_input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> NameRef<'a> {
+    /// Check name at `input` and return a view over it.
+    fn parse(msg_input: &'a [u8], input: &'a [u8]) -> IResult<&'a [u8], Self> {
+        let (next, ()) = labels::walk(msg_input, input, |_| {})?;
+        let pos = msg_input.len() - input.len();
+        Ok((next, Self { msg_input, pos }))
+    }
+
+    /// Iterate over name labels, following compression pointers.
+    pub fn labels(&self) -> impl Iterator<Item = &'a [u8]> {
+        let msg_input = self.msg_input;
+        let mut pos = self.pos;
+
+        // Name has already been checked: stop on anything unexpected.
+        std::iter::from_fn(move || loop {
+            let len = *msg_input.get(pos)? as usize;
+            match len {
+                0 => return None,
+                1..=0x3F => {
+                    let label = msg_input.get(pos + 1..pos + 1 + len)?;
+                    pos += len + 1;
+                    return Some(label);
+                }
+                _ => pos = ((len & 0x3F) << 8) | *msg_input.get(pos + 1)? as usize,
+            }
+        })
+    }
+
+    pub fn to_name(&self) -> DomainName {
+        DomainName::from_labels(self.labels()).expect("Name is checked on parse")
+    }
+}
+
+impl PartialEq<DomainName> for NameRef<'_> {
+    fn eq(&self, other: &DomainName) -> bool {
+        let mut labels = self.labels();
+        other
+            .labels()
+            .iter()
+            .all(|x| labels.next().is_some_and(|y| x.eq_ignore_ascii_case(y)))
+            && labels.next().is_none()
+    }
+}
+
+impl fmt::Display for NameRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut is_root = true;
+        for label in self.labels() {
+            fmt_label(label, f)?;
+            f.write_str(".")?;
+            is_root = false;
+        }
+
+        if is_root {
+            f.write_str(".")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for NameRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("NameRef").field(&self.to_string()).finish()
+    }
+}
+
+/// Borrowed question.
+#[derive(Debug, Clone, Copy)]
+pub struct QuestionRef<'a> {
+    pub name: NameRef<'a>,
+    pub rr_type: ResourceRecordType,
+    pub rr_class: ResourceRecordClass,
+}
+
+impl<'a> QuestionRef<'a> {
+    fn parse(msg_input: &'a [u8], input: &'a [u8]) -> IResult<&'a [u8], Self> {
+        let (input, name) = NameRef::parse(msg_input, input)?;
+        let (input, rr_type_val) = be_u16(input)?;
+        let (input, rr_class_val) = be_u16(input)?;
+
+        Ok((
+            input,
+            Self {
+                name,
+                rr_type: rr_type_val.into(),
+                rr_class: rr_class_val.into(),
+            },
+        ))
+    }
+
+    pub fn to_question(&self) -> QuestionSection {
+        QuestionSection {
+            name: self.name.to_name(),
+            rr_type: self.rr_type,
+            rr_class: self.rr_class,
+        }
+    }
+}
+
+/// Borrowed resource record, with undecoded RDATA.
+#[derive(Debug, Clone, Copy)]
+pub struct RecordRef<'a> {
+    msg_input: &'a [u8],
+    section: MessageSection,
+    index: usize,
+    pub name: NameRef<'a>,
+    pub rr_type: ResourceRecordType,
+    pub rr_class: ResourceRecordClass,
+    pub ttl: u32,
+    pub rdata: &'a [u8],
+}
+
+impl<'a> RecordRef<'a> {
+    fn parse(
+        msg_input: &'a [u8],
+        input: &'a [u8],
+        section: MessageSection,
+        index: usize,
+    ) -> IResult<&'a [u8], Self> {
+        let (input, name) = NameRef::parse(msg_input, input)?;
+        let (input, rr_type_val) = be_u16(input)?;
+        let (input, rr_class_val) = be_u16(input)?;
+        let (input, ttl) = be_u32(input)?;
+        let (input, rdata) = rdata::length_prefixed(input)?;
+
+        Ok((
+            input,
+            Self {
+                msg_input,
+                section,
+                index,
+                name,
+                rr_type: rr_type_val.into(),
+                rr_class: rr_class_val.into(),
+                ttl,
+                rdata,
+            },
+        ))
+    }
+
+    /// Decode RDATA.
+    pub fn data(&self) -> Result<RData, ParseError> {
+        let (_, data) = RData::parse(self.msg_input, self.rr_type, self.rdata)
+            .map_err(|e| ParseError::new(self.section, self.index, self.msg_input, e))?;
+        Ok(data)
+    }
+
+    /// Offset of the record from message start.
+    pub(crate) fn offset(&self) -> usize {
+        self.name.pos
+    }
+
+    pub fn to_record(&self) -> Result<AnswerSection, ParseError> {
+        Ok(AnswerSection {
+            name: self.name.to_name(),
+            rr_type: self.rr_type,
+            rr_class: self.rr_class,
+            ttl: self.ttl,
+            data: self.data()?,
+        })
+    }
+}
+
+/// Iterator over borrowed items of a section.
+#[derive(Debug, Clone)]
+pub struct Section<'a, T> {
+    msg_input: &'a [u8],
+    /// Position of next item, or error met while looking for it.
+    pos: Result<usize, ParseError>,
+    section: MessageSection,
+    /// Index of next item.
+    index: usize,
+    remaining: u16,
+    parser: ItemParser<'a, T>,
+}
+
+pub type Questions<'a> = Section<'a, QuestionRef<'a>>;
+pub type Records<'a> = Section<'a, RecordRef<'a>>;
+
+impl<'a, T> Section<'a, T> {
+    /// Position following the section.
+    fn end(mut self) -> Result<usize, ParseError> {
+        for item in &mut self {
+            item?;
+        }
+        self.pos
+    }
+}
+
+impl<'a, T> Iterator for Section<'a, T> {
+    type Item = Result<T, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let pos = match self.pos {
+            Ok(pos) if pos < self.msg_input.len() => pos,
+            Ok(_) => {
+                self.remaining = 0;
+                return Some(Err(ParseError {
+                    section: self.section,
+                    index: self.index,
+                    offset: self.msg_input.len(),
+                    kind: ParseErrorKind::CountMismatch,
+                }));
+            }
+            Err(e) => {
+                self.remaining = 0;
+                return Some(Err(e));
+            }
+        };
+
+        let item = (self.parser)(
+            self.msg_input,
+            &self.msg_input[pos..],
+            self.section,
+            self.index,
+        );
+        match item {
+            Ok((input, item)) => {
+                self.pos = Ok(self.msg_input.len() - input.len());
+                self.index += 1;
+                self.remaining -= 1;
+                Some(Ok(item))
+            }
+            Err(e) => {
+                self.remaining = 0;
+                Some(Err(ParseError::new(
+                    self.section,
+                    self.index,
+                    self.msg_input,
+                    e,
+                )))
+            }
+        }
+    }
+}
@@ -1,50 +1,95 @@
 use std::io::{self, Write};
 
-use nom::{multi::count, IResult};
+use nom::IResult;
+
+use crate::{MessageSection, ParseError, ParseErrorKind};
 
 mod answer;
+mod builder;
+pub(crate) mod codec;
+mod dig;
+mod domain_name;
+mod edns;
+mod encoder;
+mod extended_error;
 mod header;
+mod idna;
+mod json;
 mod labels;
+mod message_ref;
+mod presentation;
 mod question;
+mod rdata;
 mod resource_record_class;
 mod resource_record_type;
+mod svcb;
+mod truncation;
 
 pub use answer::AnswerSection;
+pub use builder::MessageBuilder;
+pub use domain_name::DomainName;
+pub use edns::{ClientSubnet, Edns, EdnsOption, MIN_UDP_PAYLOAD_SIZE};
+pub use encoder::Encoder;
+pub use extended_error::{ExtendedError, ExtendedErrorCode};
 pub use header::*;
+pub use message_ref::{MessageRef, NameRef, QuestionRef, Questions, RecordRef, Records, Section};
 pub use question::QuestionSection;
+pub use rdata::RData;
 pub use resource_record_class::ResourceRecordClass;
 pub use resource_record_type::ResourceRecordType;
+pub use svcb::SvcParam;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Message {
     pub header: Header,
     pub questions: Vec<QuestionSection>,
     pub answers: Vec<AnswerSection>,
+    pub authorities: Vec<AnswerSection>,
+    pub additionals: Vec<AnswerSection>,
 }
 
 impl Message {
-    pub fn parse(msg_input: &[u8]) -> IResult<&[u8], Self> {
-        // Parse msg
-        let (input, header) = Header::parse(msg_input)?;
-        let (input, questions_unresolved) =
-            count(QuestionSection::parse, header.question_count as usize)(input)?;
-        let (input, answers_unresolved) =
-            count(AnswerSection::parse, header.answer_count as usize)(input)?;
-
-        // Resolve compressed row
-        let mut questions = Vec::with_capacity(questions_unresolved.len());
-        for (mut question, offset) in questions_unresolved {
-            let (_, next_labels) = labels::resolve_offsets(msg_input, offset)?;
-            question.labels.extend(next_labels);
-            questions.push(question);
-        }
+    /// Parse a full message.
+    ///
+    /// Errors are located by section, item index and offset in `msg_input`.
+    pub fn parse(msg_input: &[u8]) -> Result<(&[u8], Self), ParseError> {
+        let (input, header) = Header::parse(msg_input)
+            .map_err(|e| ParseError::new(MessageSection::Header, 0, msg_input, e))?;
 
-        let mut answers = Vec::with_capacity(answers_unresolved.len());
-        for (mut answer, offset) in answers_unresolved {
-            let (_, next_labels) = labels::resolve_offsets(msg_input, offset)?;
-            answer.labels.extend(next_labels);
-            answers.push(answer);
-        }
+        // Parse sections
+        let (input, questions) = parse_section(
+            msg_input,
+            input,
+            MessageSection::Question,
+            header.question_count,
+            |i| {
+                let name = labels::resolve(msg_input, i)?;
+                let (next, (mut question, _)) = QuestionSection::parse(i)?;
+                question.name = name;
+                Ok((next, question))
+            },
+        )?;
+        let (input, answers) = parse_section(
+            msg_input,
+            input,
+            MessageSection::Answer,
+            header.answer_count,
+            |i| AnswerSection::parse(msg_input, i),
+        )?;
+        let (input, authorities) = parse_section(
+            msg_input,
+            input,
+            MessageSection::Authority,
+            header.authority_resource_record_count,
+            |i| AnswerSection::parse(msg_input, i),
+        )?;
+        let (input, additionals) = parse_section(
+            msg_input,
+            input,
+            MessageSection::Additional,
+            header.additional_resource_record_count,
+            |i| AnswerSection::parse(msg_input, i),
+        )?;
 
         // Build response
         Ok((
@@ -53,19 +98,84 @@ impl Message {
                 header,
                 questions,
                 answers,
+                authorities,
+                additionals,
             },
         ))
     }
 
+    /// Set header counts from section lengths.
+    pub fn update_counts(&mut self) {
+        self.header = self.counted_header();
+    }
+
+    fn counted_header(&self) -> Header {
+        Header {
+            question_count: section_count(self.questions.len()),
+            answer_count: section_count(self.answers.len()),
+            authority_resource_record_count: section_count(self.authorities.len()),
+            additional_resource_record_count: section_count(self.additionals.len()),
+            ..self.header
+        }
+    }
+
+    /// Encode message with name compression.
     pub fn encode<W: Write>(&self, buf: &mut W) -> io::Result<()> {
-        self.header.encode(buf)?;
+        let mut encoder = Encoder::new();
+        self.encode_into(&mut encoder)?;
+        buf.write_all(encoder.as_bytes())
+    }
+
+    /// Encode message, with header counts derived from sections.
+    pub fn encode_into(&self, encoder: &mut Encoder) -> io::Result<()> {
+        self.counted_header().encode(encoder)?;
         for question in &self.questions {
-            question.encode(buf)?;
+            question.encode_into(encoder)?;
         }
-        for answer in &self.answers {
-            answer.encode(buf)?;
+        for answer in self
+            .answers
+            .iter()
+            .chain(&self.authorities)
+            .chain(&self.additionals)
+        {
+            answer.encode_into(encoder)?;
         }
 
         Ok(())
     }
 }
+
+/// Parse `count` items of a section, locating any error.
+fn parse_section<'a, T, F>(
+    msg_input: &'a [u8],
+    mut input: &'a [u8],
+    section: MessageSection,
+    count: u16,
+    mut parser: F,
+) -> Result<(&'a [u8], Vec<T>), ParseError>
+where
+    F: FnMut(&'a [u8]) -> IResult<&'a [u8], T>,
+{
+    let mut items = Vec::with_capacity(count as usize);
+    for index in 0..count as usize {
+        if input.is_empty() {
+            return Err(ParseError {
+                section,
+                index,
+                offset: msg_input.len(),
+                kind: ParseErrorKind::CountMismatch,
+            });
+        }
+
+        let (next, item) =
+            parser(input).map_err(|e| ParseError::new(section, index, msg_input, e))?;
+        items.push(item);
+        input = next;
+    }
+    Ok((input, items))
+}
+
+fn section_count(len: usize) -> u16 {
+    assert!(len <= 0xFFFF, "Section has too many items");
+    len as u16
+}
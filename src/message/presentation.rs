@@ -0,0 +1,740 @@
+//! Presentation (zone file) format of records and questions.
+//!
+//! Check: https://www.rfc-editor.org/rfc/rfc1035#section-5.1
+//! and https://www.rfc-editor.org/rfc/rfc3597#section-5
+
+use std::{
+    fmt::{self, Write as _},
+    iter::Peekable,
+    slice,
+    str::FromStr,
+};
+
+use crate::PresentationError;
+
+use super::{
+    codec::{
+        decode_base32hex, decode_base64, decode_hex, encode_base32hex, encode_base64, encode_hex,
+    },
+    domain_name::parse_escape,
+    svcb::check_mandatory,
+    AnswerSection, DomainName, Encoder, QuestionSection, RData, ResourceRecordClass,
+    ResourceRecordType, ResponseCode, SvcParam,
+};
+
+/// Field of a presentation format line.
+#[derive(Debug)]
+struct Token {
+    /// Raw text, escape sequences included.
+    text: String,
+    quoted: bool,
+}
+
+/// Split text into fields, dropping comments and parentheses.
+fn tokenize(s: &str) -> Result<Vec<Token>, PresentationError> {
+    let mut tokens = vec![];
+    let mut chars = s.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            '(' | ')' => {
+                chars.next();
+            }
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            ';' => while chars.next().is_some_and(|x| x != '\n') {},
+            '"' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => {
+                            text.push('\\');
+                            text.push(chars.next().ok_or(PresentationError::UnterminatedQuote)?);
+                        }
+                        Some(x) => text.push(x),
+                        None => return Err(PresentationError::UnterminatedQuote),
+                    }
+                }
+                tokens.push(Token { text, quoted: true });
+            }
+            _ => {
+                let mut text = String::new();
+                while let Some(&x) = chars.peek() {
+                    if x.is_whitespace() || matches!(x, '(' | ')' | ';' | '"') {
+                        break;
+                    }
+                    chars.next();
+                    text.push(x);
+                    if x == '\\' {
+                        text.extend(chars.next());
+                    }
+                }
+                tokens.push(Token {
+                    text,
+                    quoted: false,
+                });
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Cursor over the fields of a line.
+struct Fields<'a> {
+    tokens: Peekable<slice::Iter<'a, Token>>,
+}
+
+impl<'a> Fields<'a> {
+    fn new(tokens: &'a [Token]) -> Self {
+        Self {
+            tokens: tokens.iter().peekable(),
+        }
+    }
+
+    fn next(&mut self, field: &'static str) -> Result<&'a Token, PresentationError> {
+        self.tokens
+            .next()
+            .ok_or(PresentationError::MissingField(field))
+    }
+
+    fn peek(&mut self) -> Option<&'a Token> {
+        self.tokens.peek().copied()
+    }
+
+    fn parse<T: FromStr>(&mut self, field: &'static str) -> Result<T, PresentationError> {
+        let token = self.next(field)?;
+        token
+            .text
+            .parse()
+            .map_err(|_| PresentationError::InvalidField(field, token.text.clone()))
+    }
+
+    fn name(&mut self, field: &'static str) -> Result<DomainName, PresentationError> {
+        Ok(self.next(field)?.text.parse()?)
+    }
+
+    fn character_string(&mut self, field: &'static str) -> Result<Vec<u8>, PresentationError> {
+        let token = self.next(field)?;
+        unescape(&token.text)
+            .filter(|x| x.len() <= 0xFF)
+            .ok_or_else(|| PresentationError::InvalidField(field, token.text.clone()))
+    }
+
+    /// Parse remaining fields as SvcParams: `key`, `key=value` or `key="value"`.
+    fn svc_params(&mut self) -> Result<Vec<SvcParam>, PresentationError> {
+        let mut params = vec![];
+        while let Some(token) = self.tokens.next() {
+            let (key, mut value) = match token.text.split_once('=') {
+                Some((key, value)) => (key, Some(value)),
+                None => (token.text.as_str(), None),
+            };
+            // Quoted values are tokens of their own.
+            if value == Some("") && self.peek().is_some_and(|x| x.quoted) {
+                value = Some(&self.next("SvcParam")?.text);
+            }
+
+            let value = match value {
+                Some(text) => Some(unescape(text).ok_or_else(|| {
+                    PresentationError::InvalidField("SvcParam", token.text.clone())
+                })?),
+                None => None,
+            };
+            params.push(SvcParam::from_presentation(key, value.as_deref())?);
+        }
+
+        params.sort_by_key(SvcParam::key);
+        let is_unique = params.windows(2).all(|x| x[0].key() != x[1].key());
+        if !is_unique || !check_mandatory(&params) {
+            let params = params.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+            return Err(PresentationError::InvalidField(
+                "SvcParam",
+                params.join(" "),
+            ));
+        }
+        Ok(params)
+    }
+
+    /// Concatenate all remaining fields and decode them (ex: base64 split on several lines).
+    fn decode_rest(
+        &mut self,
+        field: &'static str,
+        decode: fn(&str) -> Option<Vec<u8>>,
+    ) -> Result<Vec<u8>, PresentationError> {
+        let mut text = self.next(field)?.text.clone();
+        for token in self.tokens.by_ref() {
+            text.push_str(&token.text);
+        }
+        decode(&text).ok_or(PresentationError::InvalidField(field, text))
+    }
+
+    /// Parse NSEC3 salt: hexadecimal or `-` when empty.
+    fn salt(&mut self) -> Result<Vec<u8>, PresentationError> {
+        let token = self.next("salt")?;
+        match token.text.as_str() {
+            "-" => Ok(vec![]),
+            text => decode_hex(text)
+                .filter(|x| !x.is_empty() && x.len() <= 0xFF)
+                .ok_or_else(|| PresentationError::InvalidField("salt", text.to_string())),
+        }
+    }
+
+    /// Parse RRSIG time: `YYYYMMDDHHmmSS` or seconds since epoch.
+    fn timestamp(&mut self, field: &'static str) -> Result<u32, PresentationError> {
+        let token = self.next(field)?;
+        let invalid = || PresentationError::InvalidField(field, token.text.clone());
+
+        if token.text.len() != 14 {
+            return token.text.parse().map_err(|_| invalid());
+        }
+        if !token.text.bytes().all(|x| x.is_ascii_digit()) {
+            return Err(invalid());
+        }
+
+        let part = |range: std::ops::Range<usize>| token.text[range].parse::<u32>().unwrap();
+        let (year, month, day) = (part(0..4), part(4..6), part(6..8));
+        let (hour, minute, second) = (part(8..10), part(10..12), part(12..14));
+        if !(1970..=2106).contains(&year)
+            || !(1..=12).contains(&month)
+            || !(1..=days_in_month(year, month)).contains(&day)
+            || hour > 23
+            || minute > 59
+            || second > 59
+        {
+            return Err(invalid());
+        }
+
+        let seconds = days_from_civil(year, month, day) * 86400
+            + (hour as u64) * 3600
+            + (minute as u64) * 60
+            + second as u64;
+        u32::try_from(seconds).map_err(|_| invalid())
+    }
+
+    /// Parse remaining fields as a list of record types.
+    fn types(&mut self) -> Result<Vec<ResourceRecordType>, PresentationError> {
+        let mut types = vec![];
+        while self.peek().is_some() {
+            types.push(self.parse("type")?);
+        }
+        Ok(types)
+    }
+
+    /// Parse record owner fields: optional TTL and class (in any order), then type.
+    fn record_header(
+        &mut self,
+        with_ttl: bool,
+    ) -> Result<(Option<u32>, ResourceRecordClass, ResourceRecordType), PresentationError> {
+        let mut ttl = None;
+        let mut rr_class = None;
+
+        loop {
+            let token = self.next("type")?;
+            if with_ttl && ttl.is_none() {
+                if let Ok(value) = token.text.parse() {
+                    ttl = Some(value);
+                    continue;
+                }
+            }
+
+            // Some mnemonics (ex: ANY) are both a class and a type.
+            let is_class = self.peek().is_some_and(|x| {
+                x.text.parse::<ResourceRecordType>().is_ok()
+                    || (with_ttl && ttl.is_none() && x.text.parse::<u32>().is_ok())
+            });
+            if rr_class.is_none() && is_class {
+                if let Ok(value) = token.text.parse() {
+                    rr_class = Some(value);
+                    continue;
+                }
+            }
+
+            let rr_type = token
+                .text
+                .parse()
+                .map_err(|_| PresentationError::InvalidField("type", token.text.clone()))?;
+            return Ok((ttl, rr_class.unwrap_or(ResourceRecordClass::IN), rr_type));
+        }
+    }
+
+    fn finish(mut self) -> Result<(), PresentationError> {
+        match self.tokens.next() {
+            Some(token) => Err(PresentationError::TrailingData(token.text.clone())),
+            None => Ok(()),
+        }
+    }
+}
+
+impl RData {
+    /// Parse RDATA of given type from presentation format.
+    ///
+    /// Generic format (`\# <length> <hex>`) is supported for all types.
+    pub fn from_presentation(
+        rr_type: ResourceRecordType,
+        s: &str,
+    ) -> Result<Self, PresentationError> {
+        let tokens = tokenize(s)?;
+        let mut fields = Fields::new(&tokens);
+        let data = Self::parse_fields(rr_type, &mut fields)?;
+        fields.finish()?;
+        Ok(data)
+    }
+
+    fn parse_fields(
+        rr_type: ResourceRecordType,
+        fields: &mut Fields,
+    ) -> Result<Self, PresentationError> {
+        if fields.peek().is_some_and(|x| !x.quoted && x.text == "\\#") {
+            fields.next("RDATA")?;
+            return Self::parse_generic(rr_type, fields);
+        }
+
+        Ok(match rr_type {
+            ResourceRecordType::A => Self::A(fields.parse("address")?),
+            ResourceRecordType::AAAA => Self::AAAA(fields.parse("address")?),
+            ResourceRecordType::NS => Self::NS(fields.name("name server")?),
+            ResourceRecordType::CNAME => Self::CNAME(fields.name("canonical name")?),
+            ResourceRecordType::PTR => Self::PTR(fields.name("pointer")?),
+            ResourceRecordType::MX => Self::MX {
+                preference: fields.parse("preference")?,
+                exchange: fields.name("exchange")?,
+            },
+            ResourceRecordType::SOA => Self::SOA {
+                mname: fields.name("MNAME")?,
+                rname: fields.name("RNAME")?,
+                serial: fields.parse("serial")?,
+                refresh: fields.parse("refresh")?,
+                retry: fields.parse("retry")?,
+                expire: fields.parse("expire")?,
+                minimum: fields.parse("minimum")?,
+            },
+            ResourceRecordType::TXT => {
+                let mut texts = vec![];
+                while fields.peek().is_some() {
+                    texts.push(fields.character_string("text")?);
+                }
+                Self::TXT(texts)
+            }
+            ResourceRecordType::SRV => Self::SRV {
+                priority: fields.parse("priority")?,
+                weight: fields.parse("weight")?,
+                port: fields.parse("port")?,
+                target: fields.name("target")?,
+            },
+            ResourceRecordType::HINFO => Self::HINFO {
+                cpu: fields.character_string("CPU")?,
+                os: fields.character_string("OS")?,
+            },
+            ResourceRecordType::DS => Self::DS {
+                key_tag: fields.parse("key tag")?,
+                algorithm: fields.parse("algorithm")?,
+                digest_type: fields.parse("digest type")?,
+                digest: fields.decode_rest("digest", decode_hex)?,
+            },
+            ResourceRecordType::RRSIG => Self::RRSIG {
+                type_covered: fields.parse("type covered")?,
+                algorithm: fields.parse("algorithm")?,
+                labels: fields.parse("labels")?,
+                original_ttl: fields.parse("original TTL")?,
+                expiration: fields.timestamp("signature expiration")?,
+                inception: fields.timestamp("signature inception")?,
+                key_tag: fields.parse("key tag")?,
+                signer_name: fields.name("signer name")?,
+                signature: fields.decode_rest("signature", decode_base64)?,
+            },
+            ResourceRecordType::NSEC => Self::NSEC {
+                next_domain_name: fields.name("next domain name")?,
+                types: fields.types()?,
+            },
+            ResourceRecordType::DNSKEY => Self::DNSKEY {
+                flags: fields.parse("flags")?,
+                protocol: fields.parse("protocol")?,
+                algorithm: fields.parse("algorithm")?,
+                public_key: fields.decode_rest("public key", decode_base64)?,
+            },
+            ResourceRecordType::NSEC3 => Self::NSEC3 {
+                hash_algorithm: fields.parse("hash algorithm")?,
+                flags: fields.parse("flags")?,
+                iterations: fields.parse("iterations")?,
+                salt: fields.salt()?,
+                next_hashed_owner_name: {
+                    let token = fields.next("next hashed owner name")?;
+                    decode_base32hex(&token.text)
+                        .filter(|x| !x.is_empty() && x.len() <= 0xFF)
+                        .ok_or_else(|| {
+                            PresentationError::InvalidField(
+                                "next hashed owner name",
+                                token.text.clone(),
+                            )
+                        })?
+                },
+                types: fields.types()?,
+            },
+            ResourceRecordType::NSEC3PARAM => Self::NSEC3PARAM {
+                hash_algorithm: fields.parse("hash algorithm")?,
+                flags: fields.parse("flags")?,
+                iterations: fields.parse("iterations")?,
+                salt: fields.salt()?,
+            },
+            ResourceRecordType::SVCB => Self::SVCB {
+                priority: fields.parse("priority")?,
+                target: fields.name("target")?,
+                params: fields.svc_params()?,
+            },
+            ResourceRecordType::HTTPS => Self::HTTPS {
+                priority: fields.parse("priority")?,
+                target: fields.name("target")?,
+                params: fields.svc_params()?,
+            },
+            _ => return Err(PresentationError::GenericFormatRequired(rr_type)),
+        })
+    }
+
+    /// Parse RFC 3597 generic RDATA, following the `\#` token.
+    fn parse_generic(
+        rr_type: ResourceRecordType,
+        fields: &mut Fields,
+    ) -> Result<Self, PresentationError> {
+        let len: u16 = fields.parse("RDATA length")?;
+
+        let mut hex = String::new();
+        while let Some(token) = fields.peek() {
+            hex.push_str(&token.text);
+            fields.next("RDATA")?;
+        }
+
+        let invalid = || PresentationError::InvalidField("RDATA", hex.clone());
+        let data = decode_hex(&hex).ok_or_else(invalid)?;
+        if data.len() != len as usize {
+            return Err(invalid());
+        }
+
+        let (_, rdata) = Self::parse(&data, rr_type, &data).map_err(|_| invalid())?;
+        Ok(rdata)
+    }
+}
+
+impl fmt::Display for RData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::A(addr) => write!(f, "{addr}"),
+            Self::AAAA(addr) => write!(f, "{addr}"),
+            Self::NS(name) | Self::CNAME(name) | Self::PTR(name) => write!(f, "{name}"),
+            Self::MX {
+                preference,
+                exchange,
+            } => write!(f, "{preference} {exchange}"),
+            Self::SOA {
+                mname,
+                rname,
+                serial,
+                refresh,
+                retry,
+                expire,
+                minimum,
+            } => write!(
+                f,
+                "{mname} {rname} {serial} {refresh} {retry} {expire} {minimum}"
+            ),
+            Self::TXT(texts) => {
+                for (idx, text) in texts.iter().enumerate() {
+                    if idx > 0 {
+                        f.write_char(' ')?;
+                    }
+                    fmt_character_string(text, f)?;
+                }
+                Ok(())
+            }
+            Self::SRV {
+                priority,
+                weight,
+                port,
+                target,
+            } => write!(f, "{priority} {weight} {port} {target}"),
+            Self::HINFO { cpu, os } => {
+                fmt_character_string(cpu, f)?;
+                f.write_char(' ')?;
+                fmt_character_string(os, f)
+            }
+            Self::DS {
+                key_tag,
+                algorithm,
+                digest_type,
+                digest,
+            } => write!(
+                f,
+                "{key_tag} {algorithm} {digest_type} {}",
+                encode_hex(digest).to_uppercase()
+            ),
+            Self::RRSIG {
+                type_covered,
+                algorithm,
+                labels,
+                original_ttl,
+                expiration,
+                inception,
+                key_tag,
+                signer_name,
+                signature,
+            } => {
+                write!(f, "{type_covered} {algorithm} {labels} {original_ttl} ")?;
+                fmt_timestamp(*expiration, f)?;
+                f.write_char(' ')?;
+                fmt_timestamp(*inception, f)?;
+                write!(f, " {key_tag} {signer_name} {}", encode_base64(signature))
+            }
+            Self::NSEC {
+                next_domain_name,
+                types,
+            } => {
+                write!(f, "{next_domain_name}")?;
+                fmt_types(types, f)
+            }
+            Self::DNSKEY {
+                flags,
+                protocol,
+                algorithm,
+                public_key,
+            } => write!(
+                f,
+                "{flags} {protocol} {algorithm} {}",
+                encode_base64(public_key)
+            ),
+            Self::NSEC3 {
+                hash_algorithm,
+                flags,
+                iterations,
+                salt,
+                next_hashed_owner_name,
+                types,
+            } => {
+                write!(f, "{hash_algorithm} {flags} {iterations} ")?;
+                fmt_salt(salt, f)?;
+                write!(f, " {}", encode_base32hex(next_hashed_owner_name))?;
+                fmt_types(types, f)
+            }
+            Self::NSEC3PARAM {
+                hash_algorithm,
+                flags,
+                iterations,
+                salt,
+            } => {
+                write!(f, "{hash_algorithm} {flags} {iterations} ")?;
+                fmt_salt(salt, f)
+            }
+            Self::TSIG {
+                algorithm,
+                time_signed,
+                fudge,
+                mac,
+                original_id,
+                error,
+                other_data,
+            } => {
+                write!(
+                    f,
+                    "{algorithm} {time_signed} {fudge} {} {} {original_id} ",
+                    mac.len(),
+                    encode_base64(mac)
+                )?;
+                // Code 16 is BADSIG in TSIG records (RFC 8945 section 3).
+                match error {
+                    ResponseCode::BadVersion => f.write_str("BADSIG")?,
+                    error => write!(f, "{error}")?,
+                }
+                write!(f, " {}", other_data.len())?;
+                if !other_data.is_empty() {
+                    write!(f, " {}", encode_base64(other_data))?;
+                }
+                Ok(())
+            }
+            Self::SVCB {
+                priority,
+                target,
+                params,
+            }
+            | Self::HTTPS {
+                priority,
+                target,
+                params,
+            } => {
+                write!(f, "{priority} {target}")?;
+                params.iter().try_for_each(|x| write!(f, " {x}"))
+            }
+            Self::Opt(_) => {
+                let mut encoder = Encoder::without_compression();
+                self.encode_into(&mut encoder).map_err(|_| fmt::Error)?;
+                fmt_generic(encoder.as_bytes(), f)
+            }
+            Self::Unknown(_, data) => fmt_generic(data, f),
+        }
+    }
+}
+
+/// Decode escape sequences of a field.
+fn unescape(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = text.bytes();
+    let mut output = vec![];
+    while let Some(c) = bytes.next() {
+        match c {
+            b'\\' => output.push(parse_escape(&mut bytes)?),
+            _ => output.push(c),
+        }
+    }
+    Some(output)
+}
+
+/// Format a quoted character string, escaping special and non printable bytes.
+pub(super) fn fmt_character_string(text: &[u8], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_char('"')?;
+    for c in text {
+        match c {
+            b'"' | b'\\' => write!(f, "\\{}", *c as char)?,
+            0x20..=0x7E => f.write_char(*c as char)?,
+            _ => write!(f, "\\{:03}", c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+/// Format RRSIG time as `YYYYMMDDHHmmSS` (UTC).
+fn fmt_timestamp(seconds: u32, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let (year, month, day) = civil_from_days(seconds as u64 / 86400);
+    let time = seconds % 86400;
+    write!(
+        f,
+        "{year:04}{month:02}{day:02}{:02}{:02}{:02}",
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    )
+}
+
+/// Format NSEC3 salt, `-` when empty.
+fn fmt_salt(salt: &[u8], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if salt.is_empty() {
+        f.write_char('-')
+    } else {
+        f.write_str(&encode_hex(salt))
+    }
+}
+
+/// Format NSEC type bitmap as a space prefixed list of mnemonics.
+fn fmt_types(types: &[ResourceRecordType], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    types.iter().try_for_each(|x| write!(f, " {x}"))
+}
+
+fn is_leap_year(year: u32) -> bool {
+    matches!((year % 4, year % 100, year % 400), (0, 1.., _) | (_, _, 0))
+}
+
+fn days_in_month(year: u32, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 of a date, for years since 1970.
+fn days_from_civil(year: u32, month: u32, day: u32) -> u64 {
+    let days_before_year: u64 = (1970..year)
+        .map(|x| if is_leap_year(x) { 366 } else { 365 })
+        .sum();
+    let days_before_month: u32 = (1..month).map(|x| days_in_month(year, x)).sum();
+    days_before_year + (days_before_month + day - 1) as u64
+}
+
+/// Date of given days since 1970-01-01.
+fn civil_from_days(mut days: u64) -> (u32, u32, u32) {
+    let mut year = 1970;
+    loop {
+        let len = if is_leap_year(year) { 366 } else { 365 };
+        if days < len {
+            break;
+        }
+        days -= len;
+        year += 1;
+    }
+
+    let mut month = 1;
+    while days >= days_in_month(year, month) as u64 {
+        days -= days_in_month(year, month) as u64;
+        month += 1;
+    }
+    (year, month, days as u32 + 1)
+}
+
+/// Format RFC 3597 generic RDATA.
+fn fmt_generic(data: &[u8], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "\\# {}", data.len())?;
+    if !data.is_empty() {
+        write!(f, " {}", encode_hex(data))?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for AnswerSection {
+    /// Format as `<name> <ttl> <class> <type> <rdata>`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {}",
+            self.name, self.ttl, self.rr_class, self.rr_type, self.data
+        )
+    }
+}
+
+impl FromStr for AnswerSection {
+    type Err = PresentationError;
+
+    /// Parse a record line, class defaults to `IN`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize(s)?;
+        let mut fields = Fields::new(&tokens);
+
+        let name = fields.name("name")?;
+        let (ttl, rr_class, rr_type) = fields.record_header(true)?;
+        let data = RData::parse_fields(rr_type, &mut fields)?;
+        fields.finish()?;
+
+        Ok(Self {
+            name,
+            rr_type,
+            rr_class,
+            ttl: ttl.ok_or(PresentationError::MissingField("TTL"))?,
+            data,
+        })
+    }
+}
+
+impl fmt::Display for QuestionSection {
+    /// Format as `<name> <class> <type>`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.name, self.rr_class, self.rr_type)
+    }
+}
+
+impl FromStr for QuestionSection {
+    type Err = PresentationError;
+
+    /// Parse a question line, class defaults to `IN`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize(s)?;
+        let mut fields = Fields::new(&tokens);
+
+        let name = fields.name("name")?;
+        let (_, rr_class, rr_type) = fields.record_header(false)?;
+        fields.finish()?;
+
+        Ok(Self {
+            name,
+            rr_type,
+            rr_class,
+        })
+    }
+}
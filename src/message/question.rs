@@ -1,12 +1,17 @@
-use std::io::{self, Write};
+use std::{
+    io::{self, Write},
+    net::IpAddr,
+};
 
 use nom::{number::complete::be_u16, IResult};
 
-use super::{labels, ResourceRecordClass, ResourceRecordType};
+use crate::DomainNameError;
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+use super::{labels, DomainName, Encoder, ResourceRecordClass, ResourceRecordType};
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub struct QuestionSection {
-    pub labels: Vec<String>,
+    pub name: DomainName,
     pub rr_type: ResourceRecordType,
     pub rr_class: ResourceRecordClass,
 }
@@ -15,16 +20,29 @@ pub struct QuestionSection {
 pub type ParsedQuestion = (QuestionSection, Option<u16>);
 
 impl QuestionSection {
-    pub fn new_a(url: &str) -> Self {
+    pub fn new(name: DomainName, rr_type: ResourceRecordType) -> Self {
         Self {
-            labels: url.split('.').map(|x| x.to_string()).collect(),
-            rr_type: ResourceRecordType::A,
+            name,
+            rr_type,
             rr_class: ResourceRecordClass::IN,
         }
     }
 
+    /// Question for the A records of `url`, which may contain Unicode labels.
+    pub fn new_a(url: &str) -> Result<Self, DomainNameError> {
+        Ok(Self::new(
+            DomainName::from_unicode(url)?,
+            ResourceRecordType::A,
+        ))
+    }
+
+    /// Question for the PTR records of `addr` (reverse lookup).
+    pub fn new_ptr(addr: IpAddr) -> Self {
+        Self::new(DomainName::reverse(addr), ResourceRecordType::PTR)
+    }
+
     pub fn parse(input: &[u8]) -> IResult<&[u8], ParsedQuestion> {
-        let (input, (labels, label_offset)) = labels::parse(input)?;
+        let (input, (name, name_offset)) = labels::parse(input)?;
         let (input, rr_type_val) = be_u16(input)?;
         let (input, rr_class_val) = be_u16(input)?;
 
@@ -32,30 +50,28 @@ impl QuestionSection {
             input,
             (
                 Self {
-                    labels,
+                    name,
 
                     rr_type: rr_type_val.into(),
                     rr_class: rr_class_val.into(),
                 },
-                label_offset,
+                name_offset,
             ),
         ))
     }
 
     pub fn encode<W: Write>(&self, buf: &mut W) -> io::Result<()> {
-        for label in &self.labels {
-            // Write string len
-            assert!(label.len() <= 0xFF, "Label '{}' is too long", label);
-            buf.write_all(&[label.len() as u8])?;
+        let mut encoder = Encoder::without_compression();
+        self.encode_into(&mut encoder)?;
+        buf.write_all(encoder.as_bytes())
+    }
 
-            // Write string
-            buf.write_all(label.as_bytes())?;
-        }
-        buf.write_all(&[0x00])?;
+    pub fn encode_into(&self, encoder: &mut Encoder) -> io::Result<()> {
+        encoder.encode_name(&self.name, true)?;
 
         // Write flags
-        buf.write_all(&(self.rr_type as u16).to_be_bytes())?;
-        buf.write_all(&(self.rr_class as u16).to_be_bytes())?;
+        encoder.write_all(&u16::from(self.rr_type).to_be_bytes())?;
+        encoder.write_all(&u16::from(self.rr_class).to_be_bytes())?;
 
         Ok(())
     }
@@ -0,0 +1,630 @@
+use std::{
+    io::{self, Write},
+    net::{Ipv4Addr, Ipv6Addr},
+};
+
+use nom::{
+    bytes::complete::take,
+    combinator::{all_consuming, rest},
+    error::{Error, ErrorKind},
+    multi::{length_data, many0},
+    number::complete::{be_u128, be_u16, be_u32, be_u8},
+    IResult, Parser,
+};
+
+use super::{
+    labels, svcb, DomainName, EdnsOption, Encoder, ResourceRecordType, ResponseCode, SvcParam,
+};
+
+/// Resource record data.
+///
+/// Check: https://www.rfc-editor.org/rfc/rfc1035#section-3.3
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum RData {
+    /// a host address.
+    A(Ipv4Addr),
+    /// an IPv6 host address (RFC 3596).
+    AAAA(Ipv6Addr),
+    /// an authoritative name server.
+    NS(DomainName),
+    /// the canonical name for an alias.
+    CNAME(DomainName),
+    /// a domain name pointer.
+    PTR(DomainName),
+    /// mail exchange.
+    MX {
+        preference: u16,
+        exchange: DomainName,
+    },
+    /// marks the start of a zone of authority.
+    SOA {
+        mname: DomainName,
+        rname: DomainName,
+        serial: u32,
+        refresh: u32,
+        retry: u32,
+        expire: u32,
+        minimum: u32,
+    },
+    /// text strings.
+    TXT(Vec<Vec<u8>>),
+    /// location of services (RFC 2782).
+    SRV {
+        priority: u16,
+        weight: u16,
+        port: u16,
+        target: DomainName,
+    },
+    /// host information.
+    HINFO { cpu: Vec<u8>, os: Vec<u8> },
+    /// EDNS options (RFC 6891).
+    Opt(Vec<EdnsOption>),
+    /// Delegation Signer (RFC 4034 section 5).
+    DS {
+        key_tag: u16,
+        algorithm: u8,
+        digest_type: u8,
+        digest: Vec<u8>,
+    },
+    /// RRset signature (RFC 4034 section 3).
+    RRSIG {
+        type_covered: ResourceRecordType,
+        algorithm: u8,
+        labels: u8,
+        original_ttl: u32,
+        /// Seconds since epoch (serial number arithmetic).
+        expiration: u32,
+        /// Seconds since epoch (serial number arithmetic).
+        inception: u32,
+        key_tag: u16,
+        signer_name: DomainName,
+        signature: Vec<u8>,
+    },
+    /// Next secure record (RFC 4034 section 4).
+    NSEC {
+        next_domain_name: DomainName,
+        types: Vec<ResourceRecordType>,
+    },
+    /// Zone public key (RFC 4034 section 2).
+    DNSKEY {
+        flags: u16,
+        protocol: u8,
+        algorithm: u8,
+        public_key: Vec<u8>,
+    },
+    /// Hashed next secure record (RFC 5155 section 3).
+    NSEC3 {
+        hash_algorithm: u8,
+        flags: u8,
+        iterations: u16,
+        salt: Vec<u8>,
+        next_hashed_owner_name: Vec<u8>,
+        types: Vec<ResourceRecordType>,
+    },
+    /// NSEC3 parameters of a zone (RFC 5155 section 4).
+    NSEC3PARAM {
+        hash_algorithm: u8,
+        flags: u8,
+        iterations: u16,
+        salt: Vec<u8>,
+    },
+    /// Transaction signature (RFC 8945 section 4.2).
+    TSIG {
+        algorithm: DomainName,
+        /// Seconds since epoch (48 bits).
+        time_signed: u64,
+        fudge: u16,
+        mac: Vec<u8>,
+        original_id: u16,
+        error: ResponseCode,
+        other_data: Vec<u8>,
+    },
+    /// Service binding (RFC 9460 section 2).
+    SVCB {
+        /// 0 for alias mode, otherwise service mode priority.
+        priority: u16,
+        target: DomainName,
+        params: Vec<SvcParam>,
+    },
+    /// Service binding for HTTPS origins (RFC 9460 section 9).
+    HTTPS {
+        priority: u16,
+        target: DomainName,
+        params: Vec<SvcParam>,
+    },
+    /// Any other record: kept as raw bytes.
+    Unknown(ResourceRecordType, Vec<u8>),
+}
+
+impl RData {
+    /// Parse RDATA of given type.
+    ///
+    /// `msg_input` is the full message, used to resolve compressed names.
+    pub fn parse<'a>(
+        msg_input: &'a [u8],
+        rr_type: ResourceRecordType,
+        input: &'a [u8],
+    ) -> IResult<&'a [u8], Self> {
+        let name = move |input| parse_name(msg_input, input);
+
+        all_consuming(move |input| match rr_type {
+            ResourceRecordType::A => take(4_usize)
+                .map(|x: &[u8]| Self::A(Ipv4Addr::new(x[0], x[1], x[2], x[3])))
+                .parse(input),
+            ResourceRecordType::AAAA => be_u128.map(|x| Self::AAAA(x.into())).parse(input),
+            ResourceRecordType::NS => name.map(Self::NS).parse(input),
+            ResourceRecordType::CNAME => name.map(Self::CNAME).parse(input),
+            ResourceRecordType::PTR => name.map(Self::PTR).parse(input),
+            ResourceRecordType::MX => {
+                let (input, preference) = be_u16(input)?;
+                let (input, exchange) = name(input)?;
+                Ok((
+                    input,
+                    Self::MX {
+                        preference,
+                        exchange,
+                    },
+                ))
+            }
+            ResourceRecordType::SOA => {
+                let (input, mname) = name(input)?;
+                let (input, rname) = name(input)?;
+                let (input, serial) = be_u32(input)?;
+                let (input, refresh) = be_u32(input)?;
+                let (input, retry) = be_u32(input)?;
+                let (input, expire) = be_u32(input)?;
+                let (input, minimum) = be_u32(input)?;
+                Ok((
+                    input,
+                    Self::SOA {
+                        mname,
+                        rname,
+                        serial,
+                        refresh,
+                        retry,
+                        expire,
+                        minimum,
+                    },
+                ))
+            }
+            ResourceRecordType::TXT => many0(character_string).map(Self::TXT).parse(input),
+            ResourceRecordType::SRV => {
+                let (input, priority) = be_u16(input)?;
+                let (input, weight) = be_u16(input)?;
+                let (input, port) = be_u16(input)?;
+                let (input, target) = name(input)?;
+                Ok((
+                    input,
+                    Self::SRV {
+                        priority,
+                        weight,
+                        port,
+                        target,
+                    },
+                ))
+            }
+            ResourceRecordType::HINFO => {
+                let (input, cpu) = character_string(input)?;
+                let (input, os) = character_string(input)?;
+                Ok((input, Self::HINFO { cpu, os }))
+            }
+            ResourceRecordType::OPT => many0(EdnsOption::parse).map(Self::Opt).parse(input),
+            ResourceRecordType::DS => {
+                let (input, key_tag) = be_u16(input)?;
+                let (input, algorithm) = be_u8(input)?;
+                let (input, digest_type) = be_u8(input)?;
+                let (input, digest) = rest(input)?;
+                Ok((
+                    input,
+                    Self::DS {
+                        key_tag,
+                        algorithm,
+                        digest_type,
+                        digest: digest.to_vec(),
+                    },
+                ))
+            }
+            ResourceRecordType::RRSIG => {
+                let (input, type_covered) = be_u16(input)?;
+                let (input, algorithm) = be_u8(input)?;
+                let (input, labels) = be_u8(input)?;
+                let (input, original_ttl) = be_u32(input)?;
+                let (input, expiration) = be_u32(input)?;
+                let (input, inception) = be_u32(input)?;
+                let (input, key_tag) = be_u16(input)?;
+                let (input, signer_name) = name(input)?;
+                let (input, signature) = rest(input)?;
+                Ok((
+                    input,
+                    Self::RRSIG {
+                        type_covered: type_covered.into(),
+                        algorithm,
+                        labels,
+                        original_ttl,
+                        expiration,
+                        inception,
+                        key_tag,
+                        signer_name,
+                        signature: signature.to_vec(),
+                    },
+                ))
+            }
+            ResourceRecordType::NSEC => {
+                let (input, next_domain_name) = name(input)?;
+                let (input, types) = type_bitmap(input)?;
+                Ok((
+                    input,
+                    Self::NSEC {
+                        next_domain_name,
+                        types,
+                    },
+                ))
+            }
+            ResourceRecordType::DNSKEY => {
+                let (input, flags) = be_u16(input)?;
+                let (input, protocol) = be_u8(input)?;
+                let (input, algorithm) = be_u8(input)?;
+                let (input, public_key) = rest(input)?;
+                Ok((
+                    input,
+                    Self::DNSKEY {
+                        flags,
+                        protocol,
+                        algorithm,
+                        public_key: public_key.to_vec(),
+                    },
+                ))
+            }
+            ResourceRecordType::NSEC3 => {
+                let (input, hash_algorithm) = be_u8(input)?;
+                let (input, flags) = be_u8(input)?;
+                let (input, iterations) = be_u16(input)?;
+                let (input, salt) = length_data(be_u8)(input)?;
+                let (input, next_hashed_owner_name) = length_data(be_u8)(input)?;
+                let (input, types) = type_bitmap(input)?;
+                Ok((
+                    input,
+                    Self::NSEC3 {
+                        hash_algorithm,
+                        flags,
+                        iterations,
+                        salt: salt.to_vec(),
+                        next_hashed_owner_name: next_hashed_owner_name.to_vec(),
+                        types,
+                    },
+                ))
+            }
+            ResourceRecordType::NSEC3PARAM => {
+                let (input, hash_algorithm) = be_u8(input)?;
+                let (input, flags) = be_u8(input)?;
+                let (input, iterations) = be_u16(input)?;
+                let (input, salt) = length_data(be_u8)(input)?;
+                Ok((
+                    input,
+                    Self::NSEC3PARAM {
+                        hash_algorithm,
+                        flags,
+                        iterations,
+                        salt: salt.to_vec(),
+                    },
+                ))
+            }
+            ResourceRecordType::TSIG => {
+                let (input, algorithm) = name(input)?;
+                let (input, time_high) = be_u16(input)?;
+                let (input, time_low) = be_u32(input)?;
+                let (input, fudge) = be_u16(input)?;
+                let (input, mac) = length_data(be_u16)(input)?;
+                let (input, original_id) = be_u16(input)?;
+                let (input, error) = be_u16(input)?;
+                let (input, other_data) = length_data(be_u16)(input)?;
+                Ok((
+                    input,
+                    Self::TSIG {
+                        algorithm,
+                        time_signed: ((time_high as u64) << 32) | time_low as u64,
+                        fudge,
+                        mac: mac.to_vec(),
+                        original_id,
+                        error: error.into(),
+                        other_data: other_data.to_vec(),
+                    },
+                ))
+            }
+            ResourceRecordType::SVCB | ResourceRecordType::HTTPS => {
+                let (input, priority) = be_u16(input)?;
+                let (input, target) = name(input)?;
+                let (input, params) = svcb::parse_params(input)?;
+                let data = match rr_type {
+                    ResourceRecordType::SVCB => Self::SVCB {
+                        priority,
+                        target,
+                        params,
+                    },
+                    _ => Self::HTTPS {
+                        priority,
+                        target,
+                        params,
+                    },
+                };
+                Ok((input, data))
+            }
+            _ => rest
+                .map(|x: &[u8]| Self::Unknown(rr_type, x.to_vec()))
+                .parse(input),
+        })(input)
+        .map_err(|e| e.map(malformed))
+    }
+
+    pub fn encode<W: Write>(&self, buf: &mut W) -> io::Result<()> {
+        let mut encoder = Encoder::without_compression();
+        self.encode_into(&mut encoder)?;
+        buf.write_all(encoder.as_bytes())
+    }
+
+    /// Write RDATA (without its length).
+    ///
+    /// Only names of RFC 1035 types are compressed (RFC 3597 section 4).
+    pub fn encode_into(&self, encoder: &mut Encoder) -> io::Result<()> {
+        match self {
+            Self::A(addr) => encoder.write_all(&addr.octets()),
+            Self::AAAA(addr) => encoder.write_all(&addr.octets()),
+            Self::NS(name) | Self::CNAME(name) | Self::PTR(name) => encoder.encode_name(name, true),
+            Self::MX {
+                preference,
+                exchange,
+            } => {
+                encoder.write_all(&preference.to_be_bytes())?;
+                encoder.encode_name(exchange, true)
+            }
+            Self::SOA {
+                mname,
+                rname,
+                serial,
+                refresh,
+                retry,
+                expire,
+                minimum,
+            } => {
+                encoder.encode_name(mname, true)?;
+                encoder.encode_name(rname, true)?;
+                for value in [serial, refresh, retry, expire, minimum] {
+                    encoder.write_all(&value.to_be_bytes())?;
+                }
+                Ok(())
+            }
+            Self::TXT(texts) => {
+                for text in texts {
+                    encode_character_string(text, encoder)?;
+                }
+                Ok(())
+            }
+            Self::SRV {
+                priority,
+                weight,
+                port,
+                target,
+            } => {
+                encoder.write_all(&priority.to_be_bytes())?;
+                encoder.write_all(&weight.to_be_bytes())?;
+                encoder.write_all(&port.to_be_bytes())?;
+                encoder.encode_name(target, false)
+            }
+            Self::HINFO { cpu, os } => {
+                encode_character_string(cpu, encoder)?;
+                encode_character_string(os, encoder)
+            }
+            Self::Opt(options) => {
+                for option in options {
+                    option.encode(encoder)?;
+                }
+                Ok(())
+            }
+            Self::DS {
+                key_tag,
+                algorithm,
+                digest_type,
+                digest,
+            } => {
+                encoder.write_all(&key_tag.to_be_bytes())?;
+                encoder.write_all(&[*algorithm, *digest_type])?;
+                encoder.write_all(digest)
+            }
+            Self::RRSIG {
+                type_covered,
+                algorithm,
+                labels,
+                original_ttl,
+                expiration,
+                inception,
+                key_tag,
+                signer_name,
+                signature,
+            } => {
+                encoder.write_all(&u16::from(*type_covered).to_be_bytes())?;
+                encoder.write_all(&[*algorithm, *labels])?;
+                for value in [original_ttl, expiration, inception] {
+                    encoder.write_all(&value.to_be_bytes())?;
+                }
+                encoder.write_all(&key_tag.to_be_bytes())?;
+                encoder.encode_name(signer_name, false)?;
+                encoder.write_all(signature)
+            }
+            Self::NSEC {
+                next_domain_name,
+                types,
+            } => {
+                encoder.encode_name(next_domain_name, false)?;
+                encode_type_bitmap(types, encoder)
+            }
+            Self::DNSKEY {
+                flags,
+                protocol,
+                algorithm,
+                public_key,
+            } => {
+                encoder.write_all(&flags.to_be_bytes())?;
+                encoder.write_all(&[*protocol, *algorithm])?;
+                encoder.write_all(public_key)
+            }
+            Self::NSEC3 {
+                hash_algorithm,
+                flags,
+                iterations,
+                salt,
+                next_hashed_owner_name,
+                types,
+            } => {
+                encoder.write_all(&[*hash_algorithm, *flags])?;
+                encoder.write_all(&iterations.to_be_bytes())?;
+                encode_length_prefixed_data(salt, encoder)?;
+                encode_length_prefixed_data(next_hashed_owner_name, encoder)?;
+                encode_type_bitmap(types, encoder)
+            }
+            Self::NSEC3PARAM {
+                hash_algorithm,
+                flags,
+                iterations,
+                salt,
+            } => {
+                encoder.write_all(&[*hash_algorithm, *flags])?;
+                encoder.write_all(&iterations.to_be_bytes())?;
+                encode_length_prefixed_data(salt, encoder)
+            }
+            Self::TSIG {
+                algorithm,
+                time_signed,
+                fudge,
+                mac,
+                original_id,
+                error,
+                other_data,
+            } => {
+                encoder.encode_name(algorithm, false)?;
+                encoder.write_all(&time_signed.to_be_bytes()[2..])?;
+                encoder.write_all(&fudge.to_be_bytes())?;
+                encode_u16_length_prefixed_data(mac, encoder)?;
+                encoder.write_all(&original_id.to_be_bytes())?;
+                encoder.write_all(&u16::from(*error).to_be_bytes())?;
+                encode_u16_length_prefixed_data(other_data, encoder)
+            }
+            Self::SVCB {
+                priority,
+                target,
+                params,
+            }
+            | Self::HTTPS {
+                priority,
+                target,
+                params,
+            } => {
+                encoder.write_all(&priority.to_be_bytes())?;
+                encoder.encode_name(target, false)?;
+                svcb::encode_params(params, encoder)
+            }
+            Self::Unknown(_, data) => encoder.write_all(data),
+        }
+    }
+}
+
+/// Split RDATA from `input`, prefixed by its length (RDLENGTH).
+pub(crate) fn length_prefixed(input: &[u8]) -> IResult<&[u8], &[u8]> {
+    let (data, len) = be_u16(input)?;
+    take(len)(data).map_err(|_: nom::Err<Error<&[u8]>>| {
+        nom::Err::Error(Error::new(input, ErrorKind::LengthValue))
+    })
+}
+
+/// Reaching the end of RDATA early means RDATA itself is malformed.
+fn malformed(e: Error<&[u8]>) -> Error<&[u8]> {
+    match e.code {
+        ErrorKind::Eof => Error::new(e.input, ErrorKind::Fail),
+        _ => e,
+    }
+}
+
+fn parse_name<'a>(msg_input: &'a [u8], input: &'a [u8]) -> IResult<&'a [u8], DomainName> {
+    let names = labels::resolve(msg_input, input)?;
+    let (input, _) = labels::parse(input)?;
+    Ok((input, names))
+}
+
+fn character_string(input: &[u8]) -> IResult<&[u8], Vec<u8>> {
+    length_data(be_u8).map(|x: &[u8]| x.to_vec()).parse(input)
+}
+
+fn encode_character_string<W: Write>(text: &[u8], buf: &mut W) -> io::Result<()> {
+    assert!(text.len() <= 0xFF, "Character string is too long");
+    buf.write_all(&[text.len() as u8])?;
+    buf.write_all(text)
+}
+
+pub(crate) fn encode_length_prefixed_data<W: Write>(data: &[u8], buf: &mut W) -> io::Result<()> {
+    assert!(data.len() <= 0xFF, "Data is too long");
+    buf.write_all(&[data.len() as u8])?;
+    buf.write_all(data)
+}
+
+fn encode_u16_length_prefixed_data<W: Write>(data: &[u8], buf: &mut W) -> io::Result<()> {
+    assert!(data.len() <= 0xFFFF, "Data is too long");
+    buf.write_all(&(data.len() as u16).to_be_bytes())?;
+    buf.write_all(data)
+}
+
+/// Parse NSEC type bitmap (RFC 4034 section 4.1.2), until end of input.
+fn type_bitmap(mut input: &[u8]) -> IResult<&[u8], Vec<ResourceRecordType>> {
+    let mut types = vec![];
+    let mut last_window = None;
+
+    while !input.is_empty() {
+        let start = input;
+        let (next, window) = be_u8(input)?;
+        let (next, bitmap) = length_data(be_u8)(next)?;
+
+        // Windows are sorted, and bitmaps are 1 to 32 bytes long.
+        if last_window >= Some(window) || bitmap.is_empty() || bitmap.len() > 32 {
+            return Err(nom::Err::Error(Error::new(start, ErrorKind::Fail)));
+        }
+        last_window = Some(window);
+
+        for (idx, byte) in bitmap.iter().enumerate() {
+            for bit in 0..8 {
+                if byte & (0x80 >> bit) != 0 {
+                    let value = ((window as u16) << 8) | (idx * 8 + bit) as u16;
+                    types.push(value.into());
+                }
+            }
+        }
+        input = next;
+    }
+
+    Ok((input, types))
+}
+
+/// Write NSEC type bitmap, types are sorted and deduplicated.
+fn encode_type_bitmap<W: Write>(types: &[ResourceRecordType], buf: &mut W) -> io::Result<()> {
+    let mut values: Vec<u16> = types.iter().map(|x| u16::from(*x)).collect();
+    values.sort_unstable();
+    values.dedup();
+
+    let mut remaining = &values[..];
+    while let Some(first) = remaining.first() {
+        let window = first >> 8;
+        let end = remaining
+            .iter()
+            .position(|x| x >> 8 != window)
+            .unwrap_or(remaining.len());
+
+        let mut bitmap = [0_u8; 32];
+        for value in &remaining[..end] {
+            let bit = (value & 0xFF) as usize;
+            bitmap[bit / 8] |= 0x80 >> (bit % 8);
+        }
+        let len = bitmap.iter().rposition(|x| *x != 0).map_or(0, |x| x + 1);
+
+        buf.write_all(&[window as u8, len as u8])?;
+        buf.write_all(&bitmap[..len])?;
+        remaining = &remaining[end..];
+    }
+    Ok(())
+}
@@ -1,31 +1,95 @@
+use std::{fmt, str::FromStr};
+
+use crate::PresentationError;
+
 /// Record record class.
 ///
 /// Check: https://www.rfc-editor.org/rfc/rfc1035#section-3.2.4
-#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
-#[repr(u8)]
+/// and https://www.iana.org/assignments/dns-parameters/dns-parameters.xhtml#dns-parameters-2
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum ResourceRecordClass {
     /// Invalid value.
     #[default]
-    Invalid = 0,
+    Invalid,
 
     /// the Internet
-    IN = 1,
+    IN,
     /// the CSNET class (Obsolete - used only for examples in some obsolete RFCs)
-    CS = 2,
+    CS,
     /// the CHAOS class
-    CH = 3,
+    CH,
     /// Hesiod [Dyer 87]
-    HS = 4,
+    HS,
+    /// QCLASS NONE (RFC 2136)
+    NONE,
+    /// QCLASS * (ANY)
+    ANY,
+
+    /// Any value not listed above: kept as is so it can be re-encoded.
+    Unknown(u16),
 }
 
 impl From<u16> for ResourceRecordClass {
     fn from(value: u16) -> Self {
         match value {
+            0 => Self::Invalid,
             1 => Self::IN,
             2 => Self::CS,
             3 => Self::CH,
             4 => Self::HS,
-            _ => Self::Invalid,
+            254 => Self::NONE,
+            255 => Self::ANY,
+            _ => Self::Unknown(value),
+        }
+    }
+}
+
+impl From<ResourceRecordClass> for u16 {
+    fn from(value: ResourceRecordClass) -> Self {
+        match value {
+            ResourceRecordClass::Invalid => 0,
+            ResourceRecordClass::IN => 1,
+            ResourceRecordClass::CS => 2,
+            ResourceRecordClass::CH => 3,
+            ResourceRecordClass::HS => 4,
+            ResourceRecordClass::NONE => 254,
+            ResourceRecordClass::ANY => 255,
+            ResourceRecordClass::Unknown(value) => value,
+        }
+    }
+}
+
+impl fmt::Display for ResourceRecordClass {
+    /// Format class mnemonic, or `CLASS<n>` for other values (RFC 3597 section 5).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IN => f.write_str("IN"),
+            Self::CS => f.write_str("CS"),
+            Self::CH => f.write_str("CH"),
+            Self::HS => f.write_str("HS"),
+            Self::NONE => f.write_str("NONE"),
+            Self::ANY => f.write_str("ANY"),
+            _ => write!(f, "CLASS{}", u16::from(*self)),
+        }
+    }
+}
+
+impl FromStr for ResourceRecordClass {
+    type Err = PresentationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "IN" => Ok(Self::IN),
+            "CS" => Ok(Self::CS),
+            "CH" => Ok(Self::CH),
+            "HS" => Ok(Self::HS),
+            "NONE" => Ok(Self::NONE),
+            "ANY" => Ok(Self::ANY),
+            x => x
+                .strip_prefix("CLASS")
+                .and_then(|x| x.parse::<u16>().ok())
+                .map(Self::from)
+                .ok_or_else(|| PresentationError::InvalidField("class", s.to_string())),
         }
     }
 }
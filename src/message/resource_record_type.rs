@@ -1,13 +1,82 @@
-/// Record record type.
-///
-/// Check: https://www.rfc-editor.org/rfc/rfc1035#section-3.2.2
-#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
-#[repr(u8)]
-pub enum ResourceRecordType {
-    /// Invalid value.
-    #[default]
-    Invalid = 0,
+use std::{fmt, str::FromStr};
 
+use crate::PresentationError;
+
+/// Mnemonic of a type: its variant name, unless given.
+macro_rules! mnemonic {
+    ($name:ident) => {
+        stringify!($name)
+    };
+    ($name:ident $mnemonic:literal) => {
+        $mnemonic
+    };
+}
+
+macro_rules! resource_record_types {
+    ($($(#[$doc:meta])* $name:ident $(($mnemonic:literal))? = $value:literal,)*) => {
+        /// Record record type.
+        ///
+        /// Check: https://www.iana.org/assignments/dns-parameters/dns-parameters.xhtml#dns-parameters-4
+        #[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy)]
+        pub enum ResourceRecordType {
+            /// Invalid value.
+            #[default]
+            Invalid,
+
+            $($(#[$doc])* $name,)*
+
+            /// Any value not listed above: kept as is so it can be re-encoded.
+            Unknown(u16),
+        }
+
+        impl From<u16> for ResourceRecordType {
+            fn from(value: u16) -> Self {
+                match value {
+                    0 => Self::Invalid,
+                    $($value => Self::$name,)*
+                    _ => Self::Unknown(value),
+                }
+            }
+        }
+
+        impl From<ResourceRecordType> for u16 {
+            fn from(value: ResourceRecordType) -> Self {
+                match value {
+                    ResourceRecordType::Invalid => 0,
+                    $(ResourceRecordType::$name => $value,)*
+                    ResourceRecordType::Unknown(value) => value,
+                }
+            }
+        }
+
+        impl fmt::Display for ResourceRecordType {
+            /// Format type mnemonic, or `TYPE<n>` for other values (RFC 3597 section 5).
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match self {
+                    $(Self::$name => f.write_str(mnemonic!($name $($mnemonic)?)),)*
+                    _ => write!(f, "TYPE{}", u16::from(*self)),
+                }
+            }
+        }
+
+        impl FromStr for ResourceRecordType {
+            type Err = PresentationError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s.to_ascii_uppercase().as_str() {
+                    $(mnemonic!($name $($mnemonic)?) => Ok(Self::$name),)*
+                    x => x
+                        .strip_prefix("TYPE")
+                        .and_then(|x| x.parse::<u16>().ok())
+                        .map(Self::from)
+                        .ok_or_else(|| PresentationError::InvalidField("type", s.to_string())),
+                }
+            }
+        }
+    };
+}
+
+resource_record_types! {
     /// a host address.
     A = 1,
     /// an authoritative name server.
@@ -40,28 +109,150 @@ pub enum ResourceRecordType {
     MX = 15,
     /// text strings.
     TXT = 16,
-}
-
-impl From<u16> for ResourceRecordType {
-    fn from(value: u16) -> Self {
-        match value {
-            1 => Self::A,
-            2 => Self::NS,
-            3 => Self::MD,
-            4 => Self::MF,
-            5 => Self::CNAME,
-            6 => Self::SOA,
-            7 => Self::MB,
-            8 => Self::MG,
-            9 => Self::MR,
-            10 => Self::NULL,
-            11 => Self::WKS,
-            12 => Self::PTR,
-            13 => Self::HINFO,
-            14 => Self::MINFO,
-            15 => Self::MX,
-            16 => Self::TXT,
-            _ => Self::Invalid,
-        }
-    }
+    /// for Responsible Person.
+    RP = 17,
+    /// for AFS Data Base location.
+    AFSDB = 18,
+    /// for X.25 PSDN address.
+    X25 = 19,
+    /// for ISDN address.
+    ISDN = 20,
+    /// for Route Through.
+    RT = 21,
+    /// for NSAP address, NSAP style A record (DEPRECATED).
+    NSAP = 22,
+    /// for domain name pointer, NSAP style (DEPRECATED).
+    NSAPPTR("NSAP-PTR") = 23,
+    /// for security signature.
+    SIG = 24,
+    /// for security key.
+    KEY = 25,
+    /// X.400 mail mapping information.
+    PX = 26,
+    /// Geographical Position.
+    GPOS = 27,
+    /// an IPv6 host address (RFC 3596).
+    AAAA = 28,
+    /// Location Information.
+    LOC = 29,
+    /// Next Domain (OBSOLETE).
+    NXT = 30,
+    /// Endpoint Identifier.
+    EID = 31,
+    /// Nimrod Locator.
+    NIMLOC = 32,
+    /// location of services (RFC 2782).
+    SRV = 33,
+    /// ATM Address.
+    ATMA = 34,
+    /// Naming Authority Pointer.
+    NAPTR = 35,
+    /// Key Exchanger.
+    KX = 36,
+    /// CERT.
+    CERT = 37,
+    /// A6 (OBSOLETE - use AAAA).
+    A6 = 38,
+    /// DNAME.
+    DNAME = 39,
+    /// SINK.
+    SINK = 40,
+    /// OPT pseudo record (RFC 6891).
+    OPT = 41,
+    /// APL.
+    APL = 42,
+    /// Delegation Signer.
+    DS = 43,
+    /// SSH Key Fingerprint.
+    SSHFP = 44,
+    /// IPSECKEY.
+    IPSECKEY = 45,
+    /// RRSIG.
+    RRSIG = 46,
+    /// NSEC.
+    NSEC = 47,
+    /// DNSKEY.
+    DNSKEY = 48,
+    /// DHCID.
+    DHCID = 49,
+    /// NSEC3.
+    NSEC3 = 50,
+    /// NSEC3PARAM.
+    NSEC3PARAM = 51,
+    /// TLSA.
+    TLSA = 52,
+    /// S/MIME cert association.
+    SMIMEA = 53,
+    /// Host Identity Protocol.
+    HIP = 55,
+    /// NINFO.
+    NINFO = 56,
+    /// RKEY.
+    RKEY = 57,
+    /// Trust Anchor LINK.
+    TALINK = 58,
+    /// Child DS.
+    CDS = 59,
+    /// DNSKEY(s) the Child wants reflected in DS.
+    CDNSKEY = 60,
+    /// OpenPGP Key.
+    OPENPGPKEY = 61,
+    /// Child-To-Parent Synchronization.
+    CSYNC = 62,
+    /// Message Digest Over Zone Data.
+    ZONEMD = 63,
+    /// General-purpose service binding.
+    SVCB = 64,
+    /// SVCB-compatible type for use with HTTP.
+    HTTPS = 65,
+    /// Sender Policy Framework.
+    SPF = 99,
+    /// UINFO.
+    UINFO = 100,
+    /// UID.
+    UID = 101,
+    /// GID.
+    GID = 102,
+    /// UNSPEC.
+    UNSPEC = 103,
+    /// NID.
+    NID = 104,
+    /// L32.
+    L32 = 105,
+    /// L64.
+    L64 = 106,
+    /// LP.
+    LP = 107,
+    /// an EUI-48 address.
+    EUI48 = 108,
+    /// an EUI-64 address.
+    EUI64 = 109,
+    /// Transaction Key.
+    TKEY = 249,
+    /// Transaction Signature.
+    TSIG = 250,
+    /// incremental transfer.
+    IXFR = 251,
+    /// transfer of an entire zone.
+    AXFR = 252,
+    /// mailbox-related RRs (MB, MG or MR).
+    MAILB = 253,
+    /// mail agent RRs (OBSOLETE - see MX).
+    MAILA = 254,
+    /// A request for some or all records the server has available.
+    ANY = 255,
+    /// URI.
+    URI = 256,
+    /// Certification Authority Restriction.
+    CAA = 257,
+    /// Application Visibility and Control.
+    AVC = 258,
+    /// Digital Object Architecture.
+    DOA = 259,
+    /// Automatic Multicast Tunneling Relay.
+    AMTRELAY = 260,
+    /// DNSSEC Trust Authorities.
+    TA = 32768,
+    /// DNSSEC Lookaside Validation (OBSOLETE).
+    DLV = 32769,
 }
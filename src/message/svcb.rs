@@ -0,0 +1,337 @@
+//! Service parameters of SVCB and HTTPS records.
+//!
+//! Check: https://www.rfc-editor.org/rfc/rfc9460#section-7
+
+use std::{
+    fmt::{self, Write as _},
+    io::{self, Write},
+    net::{Ipv4Addr, Ipv6Addr},
+};
+
+use nom::{
+    combinator::{all_consuming, map_opt},
+    multi::{length_data, many1},
+    number::complete::{be_u128, be_u16, be_u32, be_u8},
+    IResult, Parser,
+};
+
+use crate::PresentationError;
+
+use super::{
+    codec::{decode_base64, encode_base64},
+    presentation::fmt_character_string,
+    rdata::encode_length_prefixed_data,
+};
+
+/// Service parameter (SvcParam), a key and its value.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum SvcParam {
+    /// Keys clients must support to use the record.
+    Mandatory(Vec<u16>),
+    /// Supported protocols (ALPN identifiers), each at most 255 bytes long.
+    Alpn(Vec<Vec<u8>>),
+    /// Default protocol is not supported: only `alpn` ones are.
+    NoDefaultAlpn,
+    Port(u16),
+    Ipv4Hint(Vec<Ipv4Addr>),
+    /// Encrypted ClientHello configuration list.
+    Ech(Vec<u8>),
+    Ipv6Hint(Vec<Ipv6Addr>),
+    /// Any other key: raw value.
+    Unknown(u16, Vec<u8>),
+}
+
+impl SvcParam {
+    pub const MANDATORY: u16 = 0;
+    pub const ALPN: u16 = 1;
+    pub const NO_DEFAULT_ALPN: u16 = 2;
+    pub const PORT: u16 = 3;
+    pub const IPV4HINT: u16 = 4;
+    pub const ECH: u16 = 5;
+    pub const IPV6HINT: u16 = 6;
+
+    pub fn key(&self) -> u16 {
+        match self {
+            Self::Mandatory(_) => Self::MANDATORY,
+            Self::Alpn(_) => Self::ALPN,
+            Self::NoDefaultAlpn => Self::NO_DEFAULT_ALPN,
+            Self::Port(_) => Self::PORT,
+            Self::Ipv4Hint(_) => Self::IPV4HINT,
+            Self::Ech(_) => Self::ECH,
+            Self::Ipv6Hint(_) => Self::IPV6HINT,
+            Self::Unknown(key, _) => *key,
+        }
+    }
+
+    /// Parse a parameter value, `None` if malformed (RFC 9460 section 7).
+    fn from_value(key: u16, value: &[u8]) -> Option<Self> {
+        match key {
+            Self::MANDATORY => list(value, be_u16).map(Self::Mandatory),
+            Self::ALPN => list(
+                value,
+                map_opt(length_data(be_u8), |x: &[u8]| {
+                    (!x.is_empty()).then(|| x.to_vec())
+                }),
+            )
+            .map(Self::Alpn),
+            Self::NO_DEFAULT_ALPN => value.is_empty().then_some(Self::NoDefaultAlpn),
+            Self::PORT => <[u8; 2]>::try_from(value)
+                .ok()
+                .map(|x| Self::Port(u16::from_be_bytes(x))),
+            Self::IPV4HINT => list(value, be_u32.map(Ipv4Addr::from)).map(Self::Ipv4Hint),
+            Self::ECH => Some(Self::Ech(value.to_vec())),
+            Self::IPV6HINT => list(value, be_u128.map(Ipv6Addr::from)).map(Self::Ipv6Hint),
+            _ => Some(Self::Unknown(key, value.to_vec())),
+        }
+    }
+
+    fn value(&self) -> Vec<u8> {
+        match self {
+            Self::Mandatory(keys) => {
+                let mut keys = keys.clone();
+                keys.sort_unstable();
+                keys.iter().flat_map(|x| x.to_be_bytes()).collect()
+            }
+            Self::Alpn(ids) => {
+                let mut value = vec![];
+                for id in ids {
+                    encode_length_prefixed_data(id, &mut value)
+                        .expect("Encoding to memory cannot fail");
+                }
+                value
+            }
+            Self::NoDefaultAlpn => vec![],
+            Self::Port(port) => port.to_be_bytes().to_vec(),
+            Self::Ipv4Hint(addrs) => addrs.iter().flat_map(|x| x.octets()).collect(),
+            Self::Ech(data) | Self::Unknown(_, data) => data.clone(),
+            Self::Ipv6Hint(addrs) => addrs.iter().flat_map(|x| x.octets()).collect(),
+        }
+    }
+
+    /// Parse a `key[=value]` field, `value` being already unescaped.
+    pub(crate) fn from_presentation(
+        key: &str,
+        value: Option<&[u8]>,
+    ) -> Result<Self, PresentationError> {
+        let invalid = || {
+            let text = match value {
+                Some(value) => format!("{key}={}", String::from_utf8_lossy(value)),
+                None => key.to_string(),
+            };
+            PresentationError::InvalidField("SvcParam", text)
+        };
+        let key = parse_key(key).ok_or_else(invalid)?;
+
+        // Values are comma separated lists, with `\,` and `\\` escapes in items.
+        let items = || -> Result<Vec<Vec<u8>>, PresentationError> {
+            let value = value.filter(|x| !x.is_empty()).ok_or_else(invalid)?;
+            let mut items = vec![vec![]];
+            let mut bytes = value.iter();
+            while let Some(c) = bytes.next() {
+                match c {
+                    b',' => items.push(vec![]),
+                    b'\\' => items
+                        .last_mut()
+                        .unwrap()
+                        .push(*bytes.next().ok_or_else(invalid)?),
+                    _ => items.last_mut().unwrap().push(*c),
+                }
+            }
+            match items.iter().any(|x| x.is_empty()) {
+                true => Err(invalid()),
+                false => Ok(items),
+            }
+        };
+        let parse_items = || -> Result<Vec<String>, PresentationError> {
+            items()?
+                .into_iter()
+                .map(|x| String::from_utf8(x).map_err(|_| invalid()))
+                .collect()
+        };
+
+        let param = match key {
+            Self::MANDATORY => Self::Mandatory(
+                parse_items()?
+                    .iter()
+                    .map(|x| parse_key(x).ok_or_else(invalid))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Self::ALPN => Self::Alpn(items()?),
+            Self::NO_DEFAULT_ALPN if value.is_none() => Self::NoDefaultAlpn,
+            Self::PORT => {
+                let value = value.ok_or_else(invalid)?;
+                let port = std::str::from_utf8(value).ok().and_then(|x| x.parse().ok());
+                Self::Port(port.ok_or_else(invalid)?)
+            }
+            Self::IPV4HINT => Self::Ipv4Hint(
+                parse_items()?
+                    .iter()
+                    .map(|x| x.parse().map_err(|_| invalid()))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Self::ECH => {
+                let value = value.and_then(|x| std::str::from_utf8(x).ok());
+                Self::Ech(value.and_then(decode_base64).ok_or_else(invalid)?)
+            }
+            Self::IPV6HINT => Self::Ipv6Hint(
+                parse_items()?
+                    .iter()
+                    .map(|x| x.parse().map_err(|_| invalid()))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Self::NO_DEFAULT_ALPN => return Err(invalid()),
+            _ => Self::Unknown(key, value.unwrap_or_default().to_vec()),
+        };
+
+        // Wire format limits, ALPN ones first as `value` asserts them.
+        if matches!(&param, Self::Alpn(x) if x.iter().any(|x| x.len() > 0xFF))
+            || param.value().len() > 0xFFFF
+        {
+            return Err(invalid());
+        }
+        Ok(param)
+    }
+}
+
+impl fmt::Display for SvcParam {
+    /// Format as `key=value`, or `key` alone for empty values.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_key(self.key(), f)?;
+        match self {
+            Self::Mandatory(keys) => {
+                let mut keys = keys.clone();
+                keys.sort_unstable();
+                for (idx, key) in keys.iter().enumerate() {
+                    f.write_char(if idx == 0 { '=' } else { ',' })?;
+                    fmt_key(*key, f)?;
+                }
+                Ok(())
+            }
+            Self::Alpn(ids) => {
+                let mut value = vec![];
+                for (idx, id) in ids.iter().enumerate() {
+                    if idx > 0 {
+                        value.push(b',');
+                    }
+                    for c in id {
+                        if matches!(c, b',' | b'\\') {
+                            value.push(b'\\');
+                        }
+                        value.push(*c);
+                    }
+                }
+                f.write_char('=')?;
+                fmt_character_string(&value, f)
+            }
+            Self::NoDefaultAlpn => Ok(()),
+            Self::Port(port) => write!(f, "={port}"),
+            Self::Ipv4Hint(addrs) => fmt_list(addrs, f),
+            Self::Ech(data) => write!(f, "={}", encode_base64(data)),
+            Self::Ipv6Hint(addrs) => fmt_list(addrs, f),
+            Self::Unknown(_, data) if data.is_empty() => Ok(()),
+            Self::Unknown(_, data) => {
+                f.write_char('=')?;
+                fmt_character_string(data, f)
+            }
+        }
+    }
+}
+
+/// Parse SvcParams, up to the end of RDATA.
+///
+/// Keys must be in strictly increasing order (RFC 9460 section 2.2).
+pub(crate) fn parse_params(mut input: &[u8]) -> IResult<&[u8], Vec<SvcParam>> {
+    let mut params: Vec<SvcParam> = vec![];
+    while !input.is_empty() {
+        let start = input;
+        let (next, key) = be_u16(input)?;
+        let (next, value) = length_data(be_u16)(next)?;
+        let is_ordered = params.last().map(SvcParam::key) < Some(key);
+        let param = SvcParam::from_value(key, value)
+            .filter(|_| is_ordered)
+            .ok_or_else(|| {
+                nom::Err::Error(nom::error::Error::new(start, nom::error::ErrorKind::Verify))
+            })?;
+        params.push(param);
+        input = next;
+    }
+    if !check_mandatory(&params) {
+        return Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Verify,
+        )));
+    }
+    Ok((input, params))
+}
+
+/// Check keys listed by `mandatory`: present, once, and not `mandatory` itself
+/// (RFC 9460 section 8). `params` must be sorted by key.
+pub(crate) fn check_mandatory(params: &[SvcParam]) -> bool {
+    let Some(SvcParam::Mandatory(keys)) = params.first() else {
+        return true;
+    };
+    let mut keys = keys.clone();
+    keys.sort_unstable();
+    keys.windows(2).all(|x| x[0] != x[1])
+        && keys
+            .iter()
+            .all(|key| *key != SvcParam::MANDATORY && params.iter().any(|x| x.key() == *key))
+}
+
+/// Write SvcParams in canonical (increasing key) order.
+pub(crate) fn encode_params<W: Write>(params: &[SvcParam], buf: &mut W) -> io::Result<()> {
+    let mut params: Vec<_> = params.iter().collect();
+    params.sort_by_key(|x| x.key());
+    for param in params {
+        let value = param.value();
+        assert!(value.len() <= 0xFFFF, "SvcParam is too long");
+        buf.write_all(&param.key().to_be_bytes())?;
+        buf.write_all(&(value.len() as u16).to_be_bytes())?;
+        buf.write_all(&value)?;
+    }
+    Ok(())
+}
+
+/// Parse a key name, or `keyNNNNN` for any key (65535 is reserved).
+fn parse_key(name: &str) -> Option<u16> {
+    let key = match name {
+        "mandatory" => SvcParam::MANDATORY,
+        "alpn" => SvcParam::ALPN,
+        "no-default-alpn" => SvcParam::NO_DEFAULT_ALPN,
+        "port" => SvcParam::PORT,
+        "ipv4hint" => SvcParam::IPV4HINT,
+        "ech" => SvcParam::ECH,
+        "ipv6hint" => SvcParam::IPV6HINT,
+        _ => name.strip_prefix("key")?.parse().ok()?,
+    };
+    (key != 0xFFFF).then_some(key)
+}
+
+/// Parse a non-empty list filling `value`.
+fn list<'a, T>(
+    value: &'a [u8],
+    parser: impl Parser<&'a [u8], T, nom::error::Error<&'a [u8]>>,
+) -> Option<Vec<T>> {
+    all_consuming(many1(parser))(value).ok().map(|(_, x)| x)
+}
+
+fn fmt_key(key: u16, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match key {
+        SvcParam::MANDATORY => f.write_str("mandatory"),
+        SvcParam::ALPN => f.write_str("alpn"),
+        SvcParam::NO_DEFAULT_ALPN => f.write_str("no-default-alpn"),
+        SvcParam::PORT => f.write_str("port"),
+        SvcParam::IPV4HINT => f.write_str("ipv4hint"),
+        SvcParam::ECH => f.write_str("ech"),
+        SvcParam::IPV6HINT => f.write_str("ipv6hint"),
+        _ => write!(f, "key{key}"),
+    }
+}
+
+fn fmt_list<T: fmt::Display>(items: &[T], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for (idx, item) in items.iter().enumerate() {
+        f.write_char(if idx == 0 { '=' } else { ',' })?;
+        write!(f, "{item}")?;
+    }
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use std::io::{self, Write};
+
+use super::{AnswerSection, Encoder, Message, ResourceRecordType};
+
+impl Message {
+    /// Encode message with name compression, in at most `max_size` bytes.
+    ///
+    /// See [`Message::truncate`] for records dropped when message is too large.
+    /// Fail with `InvalidInput` if the header and questions alone do not fit:
+    /// nothing is written then.
+    pub fn encode_with_limit<W: Write>(&self, buf: &mut W, max_size: usize) -> io::Result<()> {
+        if self.encoded_len() <= max_size {
+            return self.encode(buf);
+        }
+
+        let mut truncated = self.clone();
+        truncated.truncate(max_size);
+        if truncated.encoded_len() > max_size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Message does not fit in the size limit",
+            ));
+        }
+        truncated.encode(buf)
+    }
+
+    /// Drop whole RRsets from the end of the message until it fits in `max_size` bytes.
+    ///
+    /// Additional records go first (OPT, TSIG and SIG records are kept), then
+    /// authority and answer records. TC bit is set once authority or answer data is dropped:
+    /// missing additional data does not require it (RFC 2181 section 9).
+    /// If the message is still too large, OPT and signature records are dropped
+    /// too, and TC bit is set. Message may still not fit, with its header and
+    /// questions only.
+    ///
+    /// Header counts are updated. Signed messages stay signed, but their
+    /// signature no longer matches: sign after truncating.
+    pub fn truncate(&mut self, max_size: usize) {
+        while self.encoded_len() > max_size {
+            if pop_rrset(&mut self.additionals) {
+                continue;
+            }
+            if pop_rrset(&mut self.authorities) || pop_rrset(&mut self.answers) {
+                self.header.flags.is_truncation = true;
+                continue;
+            }
+            // Only kept records are left.
+            if !self.additionals.is_empty() {
+                self.additionals.clear();
+                self.header.flags.is_truncation = true;
+                continue;
+            }
+            break;
+        }
+        self.update_counts();
+    }
+
+    fn encoded_len(&self) -> usize {
+        let mut encoder = Encoder::new();
+        self.encode_into(&mut encoder)
+            .expect("Encoding to memory cannot fail");
+        encoder.len()
+    }
+}
+
+/// Remove the RRset of the last record, OPT and signature records excluded.
+///
+/// Return `false` if there was nothing to remove.
+fn pop_rrset(records: &mut Vec<AnswerSection>) -> bool {
+    let Some(last) = records
+        .iter()
+        .rev()
+        .find(|x| {
+            !matches!(
+                x.rr_type,
+                ResourceRecordType::OPT | ResourceRecordType::TSIG | ResourceRecordType::SIG
+            )
+        })
+        .cloned()
+    else {
+        return false;
+    };
+
+    records.retain(|x| {
+        x.name != last.name || x.rr_type != last.rr_type || x.rr_class != last.rr_class
+    });
+    true
+}
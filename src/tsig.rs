@@ -0,0 +1,539 @@
+//! Transaction signatures: messages authenticated with a shared secret.
+//!
+//! Check: https://www.rfc-editor.org/rfc/rfc8945
+
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+    str::FromStr,
+};
+
+use crate::{
+    dnssec::sha,
+    message::{
+        codec::decode_base64, AnswerSection, DomainName, Encoder, Message, MessageRef, RData,
+        ResourceRecordClass, ResourceRecordType, ResponseCode,
+    },
+    PresentationError, TsigError,
+};
+
+/// Default fudge: clock skew allowed between signer and verifier, in seconds.
+pub const DEFAULT_FUDGE: u16 = 300;
+
+/// MAC algorithm of a TSIG key.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TsigAlgorithm {
+    HmacSha256,
+    HmacSha512,
+}
+
+impl TsigAlgorithm {
+    /// Algorithm name, as written in TSIG records.
+    pub fn name(self) -> DomainName {
+        let name = match self {
+            Self::HmacSha256 => "hmac-sha256",
+            Self::HmacSha512 => "hmac-sha512",
+        };
+        name.parse().expect("Algorithm names are valid")
+    }
+
+    pub fn from_name(name: &DomainName) -> Option<Self> {
+        [Self::HmacSha256, Self::HmacSha512]
+            .into_iter()
+            .find(|x| x.name() == *name)
+    }
+
+    /// Length of untruncated MACs, in bytes.
+    pub fn mac_len(self) -> usize {
+        match self {
+            Self::HmacSha256 => 32,
+            Self::HmacSha512 => 64,
+        }
+    }
+
+    /// Shortest truncated MAC allowed (RFC 8945 section 5.2.2.1).
+    pub fn min_mac_len(self) -> usize {
+        (self.mac_len() / 2).max(10)
+    }
+
+    fn hmac(self, secret: &[u8], data: &[u8]) -> Vec<u8> {
+        match self {
+            Self::HmacSha256 => sha::hmac::<64>(secret, data, |x| sha::sha256(x).to_vec()),
+            Self::HmacSha512 => sha::hmac::<128>(secret, data, |x| sha::sha512(x).to_vec()),
+        }
+    }
+}
+
+/// Shared secret, known to both ends by its name.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TsigKey {
+    name: DomainName,
+    algorithm: TsigAlgorithm,
+    secret: Vec<u8>,
+    /// Length of MACs sent, and shortest MAC accepted.
+    mac_len: usize,
+    fudge: u16,
+}
+
+impl TsigKey {
+    pub fn new(name: DomainName, algorithm: TsigAlgorithm, secret: Vec<u8>) -> Self {
+        Self {
+            name,
+            algorithm,
+            secret,
+            mac_len: algorithm.mac_len(),
+            fudge: DEFAULT_FUDGE,
+        }
+    }
+
+    pub fn name(&self) -> &DomainName {
+        &self.name
+    }
+
+    pub fn algorithm(&self) -> TsigAlgorithm {
+        self.algorithm
+    }
+
+    pub fn mac_len(&self) -> usize {
+        self.mac_len
+    }
+
+    /// Truncate MACs to `len` bytes when signing, and accept MACs truncated down to `len` bytes.
+    ///
+    /// Length is clamped to the algorithm bounds.
+    pub fn with_mac_len(mut self, len: usize) -> Self {
+        self.mac_len = len.clamp(self.algorithm.min_mac_len(), self.algorithm.mac_len());
+        self
+    }
+
+    pub fn fudge(&self) -> u16 {
+        self.fudge
+    }
+
+    /// Set clock skew allowed in signed messages, in seconds.
+    pub fn with_fudge(mut self, fudge: u16) -> Self {
+        self.fudge = fudge;
+        self
+    }
+
+    /// Largest size of the TSIG record added to messages signed with the key.
+    pub fn record_len(&self) -> usize {
+        // Owner, type, class, TTL and RDLENGTH, then RDATA fixed fields, MAC
+        // and server time of BADTIME errors.
+        self.name.wire_len() + 10 + self.algorithm.name().wire_len() + 16 + self.mac_len + 6
+    }
+
+    fn mac(&self, data: &[u8]) -> Vec<u8> {
+        let mut mac = self.algorithm.hmac(&self.secret, data);
+        mac.truncate(self.mac_len);
+        mac
+    }
+}
+
+impl FromStr for TsigKey {
+    type Err = PresentationError;
+
+    /// Parse a key as `[algorithm:]name:secret`, secret in base64 (as `dig -y`).
+    ///
+    /// Default algorithm is HMAC-SHA256.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || PresentationError::InvalidField("TSIG key", s.to_string());
+
+        let parts: Vec<&str> = s.split(':').collect();
+        let (algorithm, name, secret) = match parts[..] {
+            [name, secret] => (TsigAlgorithm::HmacSha256, name, secret),
+            [algorithm, name, secret] => (
+                TsigAlgorithm::from_name(&algorithm.parse()?).ok_or_else(invalid)?,
+                name,
+                secret,
+            ),
+            _ => return Err(invalid()),
+        };
+        let secret = decode_base64(secret)
+            .filter(|x| !x.is_empty())
+            .ok_or_else(invalid)?;
+
+        Ok(Self::new(name.parse()?, algorithm, secret))
+    }
+}
+
+/// Keys of the peers allowed to send signed messages, by name.
+#[derive(Debug, Default, Clone)]
+pub struct KeyStore {
+    keys: HashMap<DomainName, TsigKey>,
+}
+
+impl KeyStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a key, replacing the key of the same name.
+    pub fn insert(&mut self, key: TsigKey) -> Option<TsigKey> {
+        self.keys.insert(key.name.clone(), key)
+    }
+
+    pub fn get(&self, name: &DomainName) -> Option<&TsigKey> {
+        self.keys.get(name)
+    }
+
+    pub fn remove(&mut self, name: &DomainName) -> Option<TsigKey> {
+        self.keys.remove(name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Verify an encoded request signed with any known key, `None` if it is unsigned.
+    pub fn verify(&self, input: &[u8], now: u64) -> Result<Option<Verified>, TsigError> {
+        let (offset, record) = match find_tsig(input) {
+            Ok(tsig) => tsig,
+            Err(TsigError::Unsigned) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let key = self.keys.get(&record.name).ok_or(TsigError::BadKey)?;
+        check(input, offset, &record, key, now, None).map(Some)
+    }
+}
+
+impl FromIterator<TsigKey> for KeyStore {
+    fn from_iter<T: IntoIterator<Item = TsigKey>>(iter: T) -> Self {
+        let mut store = Self::new();
+        for key in iter {
+            store.insert(key);
+        }
+        store
+    }
+}
+
+/// Signature of a verified message.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Verified {
+    pub key: TsigKey,
+    pub mac: Vec<u8>,
+    /// Seconds since epoch.
+    pub time_signed: u64,
+}
+
+impl Verified {
+    /// Sign the response to the verified request, see [`sign`].
+    pub fn sign_response(&self, response: &mut Message, now: u64) -> Vec<u8> {
+        sign(response, &self.key, now, Some(&self.mac))
+    }
+}
+
+impl TsigError {
+    /// Code sent back for the error: in the TSIG record, or as RCODE for
+    /// malformed and unsigned messages.
+    pub fn code(self) -> ResponseCode {
+        match self {
+            Self::Unsigned | Self::Malformed => ResponseCode::FormatError,
+            Self::BadKey => ResponseCode::BadKey,
+            // BADSIG shares its value with BADVERS.
+            Self::BadSig => ResponseCode::BadVersion,
+            Self::BadTime => ResponseCode::BadTime,
+            Self::BadTrunc => ResponseCode::BadTruncation,
+        }
+    }
+
+    /// Error reported by the TSIG record of a response.
+    fn from_code(code: ResponseCode) -> Self {
+        match code {
+            ResponseCode::BadKey => Self::BadKey,
+            ResponseCode::BadTime => Self::BadTime,
+            ResponseCode::BadTruncation => Self::BadTrunc,
+            _ => Self::BadSig,
+        }
+    }
+}
+
+/// Sign `msg` at time `now` (seconds since epoch): a TSIG record is added at
+/// the end of the additional section.
+///
+/// Responses are signed along with the MAC of their request. Return the MAC,
+/// to verify the response with.
+pub fn sign(msg: &mut Message, key: &TsigKey, now: u64, request_mac: Option<&[u8]>) -> Vec<u8> {
+    sign_with(msg, key, now, ResponseCode::NoError, vec![], request_mac)
+}
+
+fn sign_with(
+    msg: &mut Message,
+    key: &TsigKey,
+    time_signed: u64,
+    error: ResponseCode,
+    other_data: Vec<u8>,
+    request_mac: Option<&[u8]>,
+) -> Vec<u8> {
+    msg.additionals
+        .retain(|x| x.rr_type != ResourceRecordType::TSIG);
+
+    let mut message = vec![];
+    msg.encode(&mut message)
+        .expect("Encoding to memory cannot fail");
+
+    let mut data = RData::TSIG {
+        algorithm: key.algorithm.name(),
+        time_signed,
+        fudge: key.fudge,
+        mac: vec![],
+        original_id: msg.header.id,
+        error,
+        other_data,
+    };
+    let data_to_sign = signed_data(request_mac, &message, &key.name, &data)
+        .expect("Encoding to memory cannot fail");
+    let mac = key.mac(&data_to_sign);
+    if let RData::TSIG {
+        mac: record_mac, ..
+    } = &mut data
+    {
+        record_mac.clone_from(&mac);
+    }
+
+    msg.additionals.push(tsig_record(key.name.clone(), data));
+    msg.update_counts();
+    mac
+}
+
+/// Verify the TSIG record of an encoded message signed with `key`, at time
+/// `now` (seconds since epoch).
+///
+/// Responses are verified along with the MAC of their request, and rejected
+/// if their TSIG record reports an error.
+pub fn verify(
+    input: &[u8],
+    key: &TsigKey,
+    now: u64,
+    request_mac: Option<&[u8]>,
+) -> Result<Verified, TsigError> {
+    let (offset, record) = find_tsig(input)?;
+    check(input, offset, &record, key, now, request_mac)
+}
+
+/// Turn `response` into the answer to a `request` that failed verification
+/// (RFC 8945 section 5.3.2).
+///
+/// Malformed requests get a FORMERR. Otherwise RCODE is NOTAUTH, and a TSIG
+/// record carries the error: only signed for BADTIME and BADTRUNC errors, as
+/// the MAC of the request was verified.
+pub fn reject(
+    response: &mut Message,
+    request: &Message,
+    error: TsigError,
+    keys: &KeyStore,
+    now: u64,
+) {
+    response
+        .additionals
+        .retain(|x| x.rr_type != ResourceRecordType::TSIG);
+
+    let tsig = request
+        .additionals
+        .last()
+        .filter(|x| x.rr_type == ResourceRecordType::TSIG);
+    let Some(
+        tsig @ AnswerSection {
+            data:
+                RData::TSIG {
+                    algorithm,
+                    time_signed,
+                    mac,
+                    ..
+                },
+            ..
+        },
+    ) = tsig
+    else {
+        response.set_response_code(ResponseCode::FormatError);
+        return;
+    };
+    if matches!(error, TsigError::Unsigned | TsigError::Malformed) {
+        response.set_response_code(ResponseCode::FormatError);
+        return;
+    }
+
+    response.set_response_code(ResponseCode::NotAuthoritative);
+    match (error, keys.get(&tsig.name)) {
+        // Time signed is the request one, server time goes in other data.
+        (TsigError::BadTime, Some(key)) => {
+            let server_time = now.to_be_bytes()[2..].to_vec();
+            sign_with(
+                response,
+                key,
+                *time_signed,
+                error.code(),
+                server_time,
+                Some(mac),
+            );
+        }
+        (TsigError::BadTrunc, Some(key)) => {
+            sign_with(response, key, now, error.code(), vec![], Some(mac));
+        }
+        _ => {
+            let data = RData::TSIG {
+                algorithm: algorithm.clone(),
+                time_signed: now,
+                fudge: DEFAULT_FUDGE,
+                mac: vec![],
+                original_id: response.header.id,
+                error: error.code(),
+                other_data: vec![],
+            };
+            response
+                .additionals
+                .push(tsig_record(tsig.name.clone(), data));
+            response.update_counts();
+        }
+    }
+}
+
+fn tsig_record(key_name: DomainName, data: RData) -> AnswerSection {
+    AnswerSection {
+        name: key_name,
+        rr_type: ResourceRecordType::TSIG,
+        rr_class: ResourceRecordClass::ANY,
+        ttl: 0,
+        data,
+    }
+}
+
+/// TSIG record of an encoded message, and its offset.
+///
+/// It must be the only one, at the end of the additional section (RFC 8945 section 5.1).
+fn find_tsig(input: &[u8]) -> Result<(usize, AnswerSection), TsigError> {
+    let msg = MessageRef::parse(input).map_err(|_| TsigError::Malformed)?;
+    let records = msg
+        .answers()
+        .chain(msg.authorities())
+        .chain(msg.additionals())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| TsigError::Malformed)?;
+
+    let mut tsigs = records
+        .iter()
+        .enumerate()
+        .filter(|(_, x)| x.rr_type == ResourceRecordType::TSIG);
+    let (idx, record) = tsigs.next().ok_or(TsigError::Unsigned)?;
+    if tsigs.next().is_some()
+        || idx + 1 != records.len()
+        || msg.header().additional_resource_record_count == 0
+        || record.rr_class != ResourceRecordClass::ANY
+    {
+        return Err(TsigError::Malformed);
+    }
+
+    let tsig = record.to_record().map_err(|_| TsigError::Malformed)?;
+    Ok((record.offset(), tsig))
+}
+
+/// Verify a TSIG record found at `offset` in `input` (RFC 8945 section 5.2).
+fn check(
+    input: &[u8],
+    offset: usize,
+    record: &AnswerSection,
+    key: &TsigKey,
+    now: u64,
+    request_mac: Option<&[u8]>,
+) -> Result<Verified, TsigError> {
+    let RData::TSIG {
+        algorithm,
+        time_signed,
+        fudge,
+        mac,
+        original_id,
+        error,
+        ..
+    } = &record.data
+    else {
+        return Err(TsigError::Malformed);
+    };
+
+    if record.name != key.name || TsigAlgorithm::from_name(algorithm) != Some(key.algorithm) {
+        return Err(TsigError::BadKey);
+    }
+    // Peer could not verify our request.
+    if mac.is_empty() && *error != ResponseCode::NoError {
+        return Err(TsigError::from_code(*error));
+    }
+    if mac.len() > key.algorithm.mac_len() || mac.len() < key.algorithm.min_mac_len() {
+        return Err(TsigError::Malformed);
+    }
+
+    // Message as signed: original ID, and without the TSIG record.
+    let mut message = input[..offset].to_vec();
+    message[..2].copy_from_slice(&original_id.to_be_bytes());
+    let additional_count = u16::from_be_bytes([message[10], message[11]]) - 1;
+    message[10..12].copy_from_slice(&additional_count.to_be_bytes());
+
+    let signed = signed_data(request_mac, &message, &record.name, &record.data)
+        .expect("Encoding to memory cannot fail");
+    let expected = key.algorithm.hmac(&key.secret, &signed);
+    // Compare in constant time.
+    let diff = expected
+        .iter()
+        .zip(mac)
+        .fold(0, |acc, (x, y)| acc | (x ^ y));
+    if diff != 0 {
+        return Err(TsigError::BadSig);
+    }
+
+    if now.abs_diff(*time_signed) > *fudge as u64 {
+        return Err(TsigError::BadTime);
+    }
+    if mac.len() < key.mac_len {
+        return Err(TsigError::BadTrunc);
+    }
+    if *error != ResponseCode::NoError {
+        return Err(TsigError::from_code(*error));
+    }
+
+    Ok(Verified {
+        key: key.clone(),
+        mac: mac.clone(),
+        time_signed: *time_signed,
+    })
+}
+
+/// Data covered by the MAC (RFC 8945 section 4.3).
+fn signed_data(
+    request_mac: Option<&[u8]>,
+    message: &[u8],
+    key_name: &DomainName,
+    tsig: &RData,
+) -> io::Result<Vec<u8>> {
+    let RData::TSIG {
+        algorithm,
+        time_signed,
+        fudge,
+        error,
+        other_data,
+        ..
+    } = tsig
+    else {
+        unreachable!("Signed data is built from TSIG RDATA");
+    };
+
+    let mut encoder = Encoder::without_compression();
+    if let Some(mac) = request_mac {
+        encoder.write_all(&(mac.len() as u16).to_be_bytes())?;
+        encoder.write_all(mac)?;
+    }
+    encoder.write_all(message)?;
+
+    // TSIG variables
+    encoder.encode_name(&key_name.to_lowercase(), false)?;
+    encoder.write_all(&u16::from(ResourceRecordClass::ANY).to_be_bytes())?;
+    encoder.write_all(&0_u32.to_be_bytes())?;
+    encoder.encode_name(&algorithm.to_lowercase(), false)?;
+    encoder.write_all(&time_signed.to_be_bytes()[2..])?;
+    encoder.write_all(&fudge.to_be_bytes())?;
+    encoder.write_all(&u16::from(*error).to_be_bytes())?;
+    encoder.write_all(&(other_data.len() as u16).to_be_bytes())?;
+    encoder.write_all(other_data)?;
+    Ok(encoder.into_bytes())
+}
@@ -0,0 +1,32 @@
+use std::{
+    net::{SocketAddr, UdpSocket},
+    thread,
+};
+
+use dns_starter_rust::message::Message;
+
+/// Serve on a local UDP port as a fake upstream. `respond` gets each query,
+/// its wire format and sender, and returns the response to send (`None` to
+/// leave the query unanswered).
+pub fn serve<F>(mut respond: F) -> SocketAddr
+where
+    F: FnMut(&Message, &[u8], SocketAddr) -> Option<Message> + Send + 'static,
+{
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let addr = socket.local_addr().unwrap();
+
+    thread::spawn(move || {
+        let mut buf = [0; 4096];
+        loop {
+            let (len, peer) = socket.recv_from(&mut buf).unwrap();
+            let (_, query) = Message::parse(&buf[..len]).unwrap();
+
+            if let Some(response) = respond(&query, &buf[..len], peer) {
+                let mut output = vec![];
+                response.encode(&mut output).unwrap();
+                socket.send_to(&output, peer).unwrap();
+            }
+        }
+    });
+    addr
+}
@@ -0,0 +1,304 @@
+#!/usr/bin/env python3
+"""Generate signed test zones for DNSSEC validation tests.
+
+Output (presentation format, one record per line) goes to `signed_zones.txt`,
+each zone starting with a `;; zone <apex>` line. The first line is the trust
+anchor DS record.
+
+Requires the `cryptography` package:
+
+    python3 tests/fixtures/sign_zones.py
+"""
+
+import base64
+import hashlib
+import os
+import struct
+from calendar import timegm
+
+from cryptography.hazmat.primitives import hashes
+from cryptography.hazmat.primitives.asymmetric import ec, ed25519, padding, rsa
+from cryptography.hazmat.primitives.asymmetric.utils import decode_dss_signature
+
+TTL = 3600
+INCEPTION = timegm((2020, 1, 1, 0, 0, 0))
+EXPIRATION = timegm((2045, 1, 1, 0, 0, 0))
+
+TYPES = {
+    "A": 1, "NS": 2, "SOA": 6, "TXT": 16, "AAAA": 28, "DS": 43, "RRSIG": 46,
+    "NSEC": 47, "DNSKEY": 48, "NSEC3": 50, "NSEC3PARAM": 51,
+}
+TYPE_NAMES = {v: k for k, v in TYPES.items()}
+
+
+def labels(name):
+    return [x for x in name.lower().split(".") if x]
+
+
+def wire_name(name):
+    return b"".join(bytes([len(x)]) + x.encode() for x in labels(name)) + b"\0"
+
+
+def canonical_key(name):
+    return [x.encode() for x in reversed(labels(name))]
+
+
+def type_bitmap(types):
+    values = sorted({TYPES[x] for x in types})
+    output = b""
+    for window in sorted({x >> 8 for x in values}):
+        bitmap = bytearray(32)
+        for value in values:
+            if value >> 8 == window:
+                bitmap[(value & 0xFF) // 8] |= 0x80 >> (value % 8)
+        length = max(i for i, x in enumerate(bitmap) if x) + 1
+        output += bytes([window, length]) + bytes(bitmap[:length])
+    return output
+
+
+def base32hex(data):
+    alphabet = "0123456789abcdefghijklmnopqrstuv"
+    bits = "".join(f"{x:08b}" for x in data)
+    bits += "0" * (-len(bits) % 5)
+    return "".join(alphabet[int(bits[i:i + 5], 2)] for i in range(0, len(bits), 5))
+
+
+def nsec3_hash(name, salt, iterations):
+    digest = hashlib.sha1(wire_name(name) + salt).digest()
+    for _ in range(iterations):
+        digest = hashlib.sha1(digest + salt).digest()
+    return digest
+
+
+class Record:
+    def __init__(self, name, rtype, rdata, text):
+        self.name = name
+        self.rtype = rtype
+        self.rdata = rdata
+        self.text = text
+
+    def __str__(self):
+        return f"{self.name} {TTL} IN {self.rtype} {self.text}"
+
+
+def a(name, address):
+    data = bytes(int(x) for x in address.split("."))
+    return Record(name, "A", data, address)
+
+
+def ns(name, target):
+    return Record(name, "NS", wire_name(target), target)
+
+
+def txt(name, text):
+    return Record(name, "TXT", bytes([len(text)]) + text.encode(), f'"{text}"')
+
+
+def soa(name):
+    mname, rname = f"ns.{name}", f"hostmaster.{name}"
+    data = wire_name(mname) + wire_name(rname) + struct.pack("!IIIII", 1, 7200, 3600, 1209600, 300)
+    return Record(name, "SOA", data, f"{mname} {rname} 1 7200 3600 1209600 300")
+
+
+class Key:
+    def __init__(self, algorithm, flags):
+        self.algorithm = algorithm
+        self.flags = flags
+        if algorithm == 8:
+            self.private = rsa.generate_private_key(public_exponent=65537, key_size=1024)
+            numbers = self.private.public_key().public_numbers()
+            exponent = numbers.e.to_bytes(3, "big")
+            self.public = bytes([len(exponent)]) + exponent + numbers.n.to_bytes(128, "big")
+        elif algorithm == 13:
+            self.private = ec.generate_private_key(ec.SECP256R1())
+            numbers = self.private.public_key().public_numbers()
+            self.public = numbers.x.to_bytes(32, "big") + numbers.y.to_bytes(32, "big")
+        elif algorithm == 15:
+            self.private = ed25519.Ed25519PrivateKey.generate()
+            self.public = self.private.public_key().public_bytes_raw()
+
+    def rdata(self):
+        return struct.pack("!HBB", self.flags, 3, self.algorithm) + self.public
+
+    def record(self, zone):
+        text = f"{self.flags} 3 {self.algorithm} {base64.b64encode(self.public).decode()}"
+        return Record(zone, "DNSKEY", self.rdata(), text)
+
+    def tag(self):
+        data = self.rdata()
+        total = sum(x << 8 if i % 2 == 0 else x for i, x in enumerate(data))
+        return (total + (total >> 16)) & 0xFFFF
+
+    def ds(self, zone):
+        digest = hashlib.sha256(wire_name(zone) + self.rdata()).digest()
+        data = struct.pack("!HBB", self.tag(), self.algorithm, 2) + digest
+        return Record(zone, "DS", data, f"{self.tag()} {self.algorithm} 2 {digest.hex().upper()}")
+
+    def sign(self, data):
+        if self.algorithm == 8:
+            return self.private.sign(data, padding.PKCS1v15(), hashes.SHA256())
+        if self.algorithm == 13:
+            r, s = decode_dss_signature(self.private.sign(data, ec.ECDSA(hashes.SHA256())))
+            return r.to_bytes(32, "big") + s.to_bytes(32, "big")
+        return self.private.sign(data)
+
+
+def rrsig(rrset, key, zone):
+    first = rrset[0]
+    owner_labels = labels(first.name)
+    count = len(owner_labels) - (owner_labels[0] == "*")
+    header = struct.pack(
+        "!HBBIIIH", TYPES[first.rtype], key.algorithm, count, TTL, EXPIRATION, INCEPTION, key.tag()
+    ) + wire_name(zone)
+
+    data = header
+    for rdata in sorted({x.rdata for x in rrset}):
+        data += wire_name(first.name) + struct.pack("!HHIH", TYPES[first.rtype], 1, TTL, len(rdata))
+        data += rdata
+    signature = key.sign(data)
+
+    text = (
+        f"{first.rtype} {key.algorithm} {count} {TTL} {EXPIRATION} {INCEPTION} {key.tag()} "
+        f"{zone} {base64.b64encode(signature).decode()}"
+    )
+    return Record(first.name, "RRSIG", header + signature, text)
+
+
+def rrsets(records):
+    groups = {}
+    for record in records:
+        groups.setdefault((record.name.lower(), record.rtype), []).append(record)
+    return list(groups.values())
+
+
+def nsec_chain(zone, records):
+    names = sorted({x.name for x in records}, key=canonical_key)
+    output = []
+    for idx, name in enumerate(names):
+        following = names[(idx + 1) % len(names)]
+        types = {x.rtype for x in records if x.name == name} | {"NSEC"}
+        is_delegation = "NS" in types and name != zone
+        if not is_delegation or "DS" in types:
+            types.add("RRSIG")
+        data = wire_name(following) + type_bitmap(types)
+        text = following + "".join(f" {x}" for x in sorted(types, key=TYPES.get))
+        output.append(Record(name, "NSEC", data, text))
+    return output
+
+
+def nsec3_chain(zone, records, salt, iterations):
+    names = {x.name for x in records}
+    hashed = sorted((nsec3_hash(x, salt, iterations), x) for x in names)
+    params = struct.pack("!BBHB", 1, 0, iterations, len(salt)) + salt
+    output = []
+    for idx, (digest, name) in enumerate(hashed):
+        following = hashed[(idx + 1) % len(hashed)][0]
+        types = {x.rtype for x in records if x.name == name} | {"RRSIG"}
+        data = params + bytes([len(following)]) + following + type_bitmap(types)
+        text = f"1 0 {iterations} {salt.hex() or '-'} {base32hex(following)}" + "".join(
+            f" {x}" for x in sorted(types, key=TYPES.get)
+        )
+        output.append(Record(f"{base32hex(digest)}.{zone}", "NSEC3", data, text))
+    return output
+
+
+def sign_zone(zone, records, keys, denial="nsec", salt=b"", iterations=0):
+    ksk = keys[0]
+    records = records + [key.record(zone) for key in keys]
+    if denial == "nsec3":
+        params = struct.pack("!BBHB", 1, 0, iterations, len(salt)) + salt
+        records.append(Record(zone, "NSEC3PARAM", params, f"1 0 {iterations} {salt.hex() or '-'}"))
+        records += nsec3_chain(zone, records, salt, iterations)
+    else:
+        records += nsec_chain(zone, records)
+
+    output = list(records)
+    for rrset in rrsets(records):
+        first = rrset[0]
+        # Delegation NS records are not signed.
+        if first.rtype == "NS" and first.name != zone:
+            continue
+        signer = ksk if first.rtype == "DNSKEY" else keys[-1]
+        output.append(rrsig(rrset, signer, zone))
+    return output
+
+
+def main():
+    root_ksk, root_zsk = Key(8, 257), Key(8, 256)
+    signed_key = Key(13, 257)
+    nsec3_key = Key(15, 257)
+    costly_key = Key(13, 257)
+
+    signed = sign_zone(
+        "signed.example.",
+        [
+            soa("signed.example."),
+            ns("signed.example.", "ns.example."),
+            a("www.signed.example.", "192.0.2.10"),
+            txt("*.wild.signed.example.", "wildcard"),
+        ],
+        [signed_key],
+    )
+    nsec3 = sign_zone(
+        "nsec3.example.",
+        [
+            soa("nsec3.example."),
+            ns("nsec3.example.", "ns.example."),
+            a("host.nsec3.example.", "192.0.2.20"),
+        ],
+        [nsec3_key],
+        denial="nsec3",
+        salt=bytes.fromhex("aabb"),
+        iterations=1,
+    )
+    # More iterations than validators accept (RFC 9276 section 3.2).
+    costly = sign_zone(
+        "costly.example.",
+        [
+            soa("costly.example."),
+            ns("costly.example.", "ns.example."),
+            a("host.costly.example.", "192.0.2.40"),
+        ],
+        [costly_key],
+        denial="nsec3",
+        iterations=500,
+    )
+    unsigned = [
+        soa("unsigned.example."),
+        ns("unsigned.example.", "ns.example."),
+        a("www.unsigned.example.", "192.0.2.30"),
+    ]
+    parent = sign_zone(
+        "example.",
+        [
+            soa("example."),
+            ns("example.", "ns.example."),
+            a("ns.example.", "192.0.2.53"),
+            a("www.example.", "192.0.2.1"),
+            ns("signed.example.", "ns.example."),
+            signed_key.ds("signed.example."),
+            ns("nsec3.example.", "ns.example."),
+            nsec3_key.ds("nsec3.example."),
+            ns("costly.example.", "ns.example."),
+            costly_key.ds("costly.example."),
+            ns("unsigned.example.", "ns.example."),
+        ],
+        [root_ksk, root_zsk],
+    )
+
+    path = os.path.join(os.path.dirname(__file__), "signed_zones.txt")
+    with open(path, "w") as f:
+        f.write(f";; anchor\n{root_ksk.ds('example.')}\n")
+        for zone, records in [
+            ("example.", parent),
+            ("signed.example.", signed),
+            ("nsec3.example.", nsec3),
+            ("costly.example.", costly),
+            ("unsigned.example.", unsigned),
+        ]:
+            f.write(f";; zone {zone}\n")
+            f.writelines(f"{x}\n" for x in records)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,317 @@
+mod common;
+
+use std::net::SocketAddr;
+
+use dns_starter_rust::{cache::Cache, client::DnsClient, message::*};
+
+const NOW: u64 = 1_700_000_000;
+
+fn subnet(address: &str, source_prefix: u8) -> ClientSubnet {
+    ClientSubnet::new(address.parse().unwrap(), source_prefix)
+}
+
+fn encode(option: &EdnsOption) -> Vec<u8> {
+    let mut buf = vec![];
+    option.encode(&mut buf).unwrap();
+    buf
+}
+
+#[test]
+fn test_client_subnet_option() {
+    let option = EdnsOption::ClientSubnet(subnet("192.0.2.77", 24));
+    let input = b"\x00\x08\x00\x07\x00\x01\x18\x00\xc0\x00\x02";
+    assert_eq!(encode(&option), input);
+    assert_eq!(EdnsOption::parse(input).unwrap().1, option);
+    assert_eq!(option.to_string(), "CLIENT-SUBNET: 192.0.2.0/24/0");
+
+    let ipv6 = subnet("2001:db8:1234:5678::1", 56).with_scope_prefix(48);
+    assert_eq!(ipv6.family(), 2);
+    assert_eq!(
+        ipv6.address(),
+        "2001:db8:1234:5600::".parse::<std::net::IpAddr>().unwrap()
+    );
+    let option = EdnsOption::ClientSubnet(ipv6);
+    let input = b"\x00\x08\x00\x0b\x00\x02\x38\x30\x20\x01\x0d\xb8\x12\x34\x56";
+    assert_eq!(encode(&option), input);
+    assert_eq!(EdnsOption::parse(input).unwrap().1, option);
+    assert_eq!(
+        option.to_string(),
+        "CLIENT-SUBNET: 2001:db8:1234:5600::/56/48"
+    );
+
+    // Prefixes are clamped to the address length.
+    let ipv4 = subnet("192.0.2.1", 40).with_scope_prefix(40);
+    assert_eq!((ipv4.source_prefix(), ipv4.scope_prefix()), (32, 32));
+    assert_eq!(subnet("192.0.2.1", 0).to_string(), "0.0.0.0/0/0");
+    assert_eq!(
+        encode(&EdnsOption::ClientSubnet(subnet("192.0.2.1", 0))),
+        b"\x00\x08\x00\x04\x00\x01\x00\x00"
+    );
+
+    assert_eq!(
+        ClientSubnet::for_client("192.0.2.1".parse().unwrap()),
+        subnet("192.0.2.0", 24)
+    );
+    assert_eq!(
+        ClientSubnet::for_client("2001:db8::1".parse().unwrap()),
+        subnet("2001:db8::", 56)
+    );
+
+    let mut edns = Edns::new(1232);
+    assert_eq!(edns.client_subnet(), None);
+    edns.set_option(option);
+    assert_eq!(edns.client_subnet(), Some(ipv6));
+}
+
+#[test]
+fn test_malformed_client_subnet() {
+    for data in [
+        // Address longer than the source prefix
+        &b"\x00\x01\x10\x00\xc0\x00\x02"[..],
+        // Address bits beyond the source prefix
+        b"\x00\x01\x17\x00\xc0\x00\x03",
+        // Prefix longer than the address
+        b"\x00\x01\x21\x00\xc0\x00\x02\x01\x00",
+        b"\x00\x01\x18\x21\xc0\x00\x02",
+        // Unknown family
+        b"\x00\x03\x00\x00",
+        // Truncated
+        b"\x00\x01\x00",
+    ] {
+        let mut input = vec![0, 8, 0, data.len() as u8];
+        input.extend(data);
+        assert_eq!(
+            EdnsOption::parse(&input).unwrap().1,
+            EdnsOption::Unknown(8, data.to_vec()),
+            "{data:?}"
+        );
+    }
+}
+
+fn response(address: &str, ttl: u32, subnet: Option<ClientSubnet>) -> Message {
+    let question = QuestionSection::new_a("example.com").unwrap();
+    let mut edns = Edns::new(1232);
+    edns.options.extend(subnet.map(EdnsOption::ClientSubnet));
+    MessageBuilder::reply_to(&DnsClient::new_query(&question))
+        .answer(AnswerSection {
+            name: question.name,
+            rr_type: ResourceRecordType::A,
+            rr_class: ResourceRecordClass::IN,
+            ttl,
+            data: RData::A(address.parse().unwrap()),
+        })
+        .edns(edns)
+        .build()
+}
+
+fn cached_address(cache: &mut Cache, subnet: Option<ClientSubnet>, now: u64) -> Option<String> {
+    let question = QuestionSection::new_a("example.com").unwrap();
+    let response = cache.get(&question, subnet.as_ref(), now)?;
+    Some(response.answers[0].data.to_string())
+}
+
+#[test]
+fn test_cache_scope() {
+    let question = QuestionSection::new_a("example.com").unwrap();
+    let mut cache = Cache::new();
+    assert_eq!(cached_address(&mut cache, None, NOW), None);
+
+    cache.insert(question.clone(), response("192.0.2.1", 60, None), NOW);
+    let scoped = subnet("198.51.100.0", 24).with_scope_prefix(16);
+    cache.insert(
+        question.clone(),
+        response("192.0.2.2", 60, Some(scoped)),
+        NOW,
+    );
+    let narrow = subnet("198.51.100.0", 24).with_scope_prefix(24);
+    cache.insert(
+        question.clone(),
+        response("192.0.2.3", 30, Some(narrow)),
+        NOW,
+    );
+
+    // Most specific response for the network
+    let client = Some(subnet("198.51.100.0", 24));
+    assert_eq!(
+        cached_address(&mut cache, client, NOW).unwrap(),
+        "192.0.2.3"
+    );
+    let client = Some(subnet("198.51.7.0", 24));
+    assert_eq!(
+        cached_address(&mut cache, client, NOW).unwrap(),
+        "192.0.2.2"
+    );
+    let client = Some(subnet("203.0.113.0", 24));
+    assert_eq!(
+        cached_address(&mut cache, client, NOW).unwrap(),
+        "192.0.2.1"
+    );
+    assert_eq!(cached_address(&mut cache, None, NOW).unwrap(), "192.0.2.1");
+    let client = Some(subnet("2001:db8::", 56));
+    assert_eq!(
+        cached_address(&mut cache, client, NOW).unwrap(),
+        "192.0.2.1"
+    );
+
+    // Client network is wider than the response scope.
+    let client = Some(subnet("198.51.100.0", 20));
+    assert_eq!(
+        cached_address(&mut cache, client, NOW).unwrap(),
+        "192.0.2.2"
+    );
+
+    // Expired
+    let client = Some(subnet("198.51.100.0", 24));
+    assert_eq!(
+        cached_address(&mut cache, client, NOW + 30).unwrap(),
+        "192.0.2.2"
+    );
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cached_address(&mut cache, client, NOW + 60), None);
+    assert!(cache.is_empty());
+
+    // Replaced
+    cache.insert(
+        question.clone(),
+        response("192.0.2.4", 60, Some(scoped)),
+        NOW + 60,
+    );
+    assert_eq!(
+        cached_address(&mut cache, client, NOW + 60).unwrap(),
+        "192.0.2.4"
+    );
+}
+
+#[test]
+fn test_cache_insert() {
+    let question = QuestionSection::new_a("example.com").unwrap();
+    let mut cache = Cache::new();
+
+    // Scope longer than the source prefix is limited to it.
+    let scoped = subnet("198.51.100.0", 16).with_scope_prefix(24);
+    cache.insert(
+        question.clone(),
+        response("192.0.2.1", 60, Some(scoped)),
+        NOW,
+    );
+    let client = Some(subnet("198.51.7.0", 24));
+    assert_eq!(
+        cached_address(&mut cache, client, NOW).unwrap(),
+        "192.0.2.1"
+    );
+
+    // Scope 0 is for any client.
+    let global = subnet("198.51.100.0", 24);
+    cache.insert(
+        question.clone(),
+        response("192.0.2.2", 60, Some(global)),
+        NOW,
+    );
+    assert_eq!(cached_address(&mut cache, None, NOW).unwrap(), "192.0.2.2");
+
+    // Errors and empty answers are not cached.
+    let mut cache = Cache::new();
+    let mut error = response("192.0.2.3", 60, None);
+    error.set_response_code(ResponseCode::ServerFail);
+    cache.insert(question.clone(), error, NOW);
+    let mut empty = response("192.0.2.3", 60, None);
+    empty.answers.clear();
+    cache.insert(question.clone(), empty, NOW);
+    assert_eq!(cached_address(&mut cache, None, NOW), None);
+}
+
+#[test]
+fn test_cache_ttl() {
+    let question = QuestionSection::new_a("example.com").unwrap();
+    let mut cache = Cache::new();
+    let mut response = response("192.0.2.1", 300, Some(subnet("198.51.100.0", 24)));
+    response
+        .authorities
+        .push("example.com. 3600 IN NS ns.example.com.".parse().unwrap());
+    cache.insert(question.clone(), response, NOW);
+
+    // TTLs are decreased by the time spent in the cache, except for OPT.
+    let cached = cache.get(&question, None, NOW + 100).unwrap();
+    assert_eq!(cached.answers[0].ttl, 200);
+    assert_eq!(cached.authorities[0].ttl, 3500);
+    assert_eq!(cached.edns().unwrap().udp_payload_size, 1232);
+    assert_eq!(cache.get(&question, None, NOW).unwrap().answers[0].ttl, 300);
+}
+
+#[test]
+fn test_cache_capacity() {
+    let questions: Vec<_> = ["a.example", "b.example", "c.example", "d.example"]
+        .into_iter()
+        .map(|x| QuestionSection::new_a(x).unwrap())
+        .collect();
+    let mut cache = Cache::with_capacity(2);
+    assert_eq!(Cache::new().len(), 0);
+
+    // Response expiring first is evicted.
+    cache.insert(questions[0].clone(), response("192.0.2.1", 60, None), NOW);
+    cache.insert(questions[1].clone(), response("192.0.2.2", 30, None), NOW);
+    cache.insert(questions[2].clone(), response("192.0.2.3", 90, None), NOW);
+    assert_eq!(cache.len(), 2);
+    assert!(cache.get(&questions[0], None, NOW).is_some());
+    assert!(cache.get(&questions[1], None, NOW).is_none());
+    assert!(cache.get(&questions[2], None, NOW).is_some());
+
+    // Expired responses are evicted first.
+    cache.insert(
+        questions[3].clone(),
+        response("192.0.2.4", 60, None),
+        NOW + 60,
+    );
+    assert_eq!(cache.len(), 2);
+    assert!(cache.get(&questions[2], None, NOW + 60).is_some());
+    assert!(cache.get(&questions[3], None, NOW + 60).is_some());
+
+    cache.purge(NOW + 90);
+    assert_eq!(cache.len(), 1);
+
+    let mut cache = Cache::with_capacity(0);
+    cache.insert(questions[0].clone(), response("192.0.2.1", 60, None), NOW);
+    assert!(cache.is_empty());
+}
+
+/// Serve answers on a local UDP port, echoing the client subnet with a 16 bits scope.
+fn serve() -> SocketAddr {
+    common::serve(|query, _, _| {
+        let subnet = query
+            .edns()
+            .and_then(|edns| edns.client_subnet())
+            .map(|subnet| subnet.with_scope_prefix(16));
+        let mut response = response("192.0.2.1", 60, subnet);
+        response.header.id = query.header.id;
+        Some(response)
+    })
+}
+
+#[test]
+fn test_client_send_subnet() {
+    let addr = serve();
+    let query = DnsClient::new_query(&QuestionSection::new_a("example.com").unwrap());
+
+    let mut client = DnsClient::connect("127.0.0.1:0", addr).unwrap();
+    let response = client.send_query(query.clone()).unwrap();
+    assert_eq!(response.edns().unwrap().client_subnet(), None);
+
+    client.set_client_subnet(Some(subnet("198.51.100.0", 24)));
+    assert_eq!(client.client_subnet(), Some(&subnet("198.51.100.0", 24)));
+    let response = client.send_query(query.clone()).unwrap();
+    assert_eq!(
+        response.edns().unwrap().client_subnet(),
+        Some(subnet("198.51.100.0", 24).with_scope_prefix(16))
+    );
+
+    // Subnet of the query is kept.
+    let mut own = query.clone();
+    let mut edns = Edns::new(1232);
+    edns.set_option(EdnsOption::ClientSubnet(subnet("203.0.113.0", 24)));
+    own.set_edns(Some(edns));
+    let response = client.send_query(own).unwrap();
+    assert_eq!(
+        response.edns().unwrap().client_subnet(),
+        Some(subnet("203.0.113.0", 24).with_scope_prefix(16))
+    );
+}
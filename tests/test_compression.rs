@@ -24,6 +24,9 @@ fn test_parse() {
                     is_truncation: false,
                     is_recursion_desired: true,
                     is_recursion_available: false,
+                    z: false,
+                    is_authentic_data: false,
+                    is_checking_disabled: false,
                     response_code: ResponseCode::NoError,
                 },
                 question_count: 2,
@@ -33,25 +36,183 @@ fn test_parse() {
             },
             questions: vec![
                 QuestionSection {
-                    labels: vec![
-                        "abc".to_string(),
-                        "longassdomainname".to_string(),
-                        "com".to_string()
-                    ],
+                    name: "abc.longassdomainname.com".parse().unwrap(),
                     rr_type: ResourceRecordType::A,
                     rr_class: ResourceRecordClass::IN
                 },
                 QuestionSection {
-                    labels: vec![
-                        "def".to_string(),
-                        "longassdomainname".to_string(),
-                        "com".to_string()
-                    ],
+                    name: "def.longassdomainname.com".parse().unwrap(),
                     rr_type: ResourceRecordType::A,
                     rr_class: ResourceRecordClass::IN
                 }
             ],
             answers: vec![],
+            authorities: vec![],
+            additionals: vec![],
         }
     )
 }
+
+#[test]
+fn test_encode() {
+    let input = [
+        56, 58, 1, 0, 0, 2, 0, 0, 0, 0, 0, 0, 3, 97, 98, 99, 17, 108, 111, 110, 103, 97, 115, 115,
+        100, 111, 109, 97, 105, 110, 110, 97, 109, 101, 3, 99, 111, 109, 0, 0, 1, 0, 1, 3, 100,
+        101, 102, 192, 16, 0, 1, 0, 1,
+    ];
+    let (_, msg) = Message::parse(&input).unwrap();
+
+    // Test encode with compression
+    let mut buf = Vec::with_capacity(input.len());
+    msg.encode(&mut buf).unwrap();
+    assert_eq!(buf, input);
+
+    // Test encode without compression
+    let mut encoder = Encoder::without_compression();
+    msg.encode_into(&mut encoder).unwrap();
+    assert_eq!(encoder.len(), input.len() + 21);
+    assert_eq!(Message::parse(encoder.as_bytes()).unwrap().1, msg);
+}
+
+#[test]
+fn test_encode_names() {
+    let name = |url: &str| url.parse::<DomainName>().unwrap();
+
+    let mut encoder = Encoder::new();
+    encoder.encode_name(&name("www.Example.com"), true).unwrap();
+    // Case insensitive match
+    encoder.encode_name(&name("example.COM"), true).unwrap();
+    // Partial match
+    encoder
+        .encode_name(&name("mail.example.com"), true)
+        .unwrap();
+    // Compression not allowed
+    encoder.encode_name(&name("example.com"), false).unwrap();
+
+    assert_eq!(
+        encoder.as_bytes(),
+        b"\x03www\x07Example\x03com\x00\xc0\x04\x04mail\xc0\x04\x07example\x03com\x00"
+    );
+}
+
+#[test]
+fn test_encode_rdata_names() {
+    let name = |url: &str| url.parse::<DomainName>().unwrap();
+    let record = |data| AnswerSection {
+        name: name("example.com"),
+        rr_type: ResourceRecordType::MX,
+        rr_class: ResourceRecordClass::IN,
+        ttl: 300,
+        data,
+    };
+
+    let mut encoder = Encoder::new();
+    record(RData::MX {
+        preference: 10,
+        exchange: name("mail.example.com"),
+    })
+    .encode_into(&mut encoder)
+    .unwrap();
+    record(RData::SRV {
+        priority: 0,
+        weight: 0,
+        port: 443,
+        target: name("example.com"),
+    })
+    .encode_into(&mut encoder)
+    .unwrap();
+
+    assert_eq!(
+        encoder.as_bytes(),
+        [
+            b"\x07example\x03com\x00\x00\x0f\x00\x01\x00\x00\x01\x2c\x00\x09".as_slice(),
+            b"\x00\x0a\x04mail\xc0\x00".as_slice(),
+            b"\xc0\x00\x00\x0f\x00\x01\x00\x00\x01\x2c\x00\x13".as_slice(),
+            b"\x00\x00\x00\x00\x01\xbb\x07example\x03com\x00".as_slice(),
+        ]
+        .concat()
+    );
+}
+
+/// Build a query message with given question name bytes (type A, class IN).
+fn query_with_name(name: &[u8]) -> Vec<u8> {
+    let mut input = vec![0x12, 0x34, 1, 0, 0, 1, 0, 0, 0, 0, 0, 0];
+    input.extend(name);
+    input.extend([0, 1, 0, 1]);
+    input
+}
+
+#[test]
+fn test_parse_chained_pointers() {
+    // Reply to:
+    // ;www.example.com.                  IN       CNAME
+    // With:
+    // www.example.com.      300          IN       CNAME    web.example.com.
+    // web.example.com.      300          IN       A        192.0.2.1
+    let mut input = vec![0x12, 0x34, 0x81, 0x80, 0, 1, 0, 2, 0, 0, 0, 0];
+    input.extend(b"\x03www\x07example\x03com\x00\x00\x05\x00\x01");
+    input.extend(b"\xc0\x0c\x00\x05\x00\x01\x00\x00\x01\x2c\x00\x06\x03web\xc0\x10");
+    // Name at offset 45 points to "web" at 39, which points to "example.com" at 16.
+    input.extend(b"\xc0\x2d\x00\x01\x00\x01\x00\x00\x01\x2c\x00\x04\xc0\x00\x02\x01");
+
+    let (_, msg) = Message::parse(&input).unwrap();
+    assert_eq!(
+        msg.answers[1].name,
+        "web.example.com".parse::<DomainName>().unwrap()
+    );
+}
+
+#[test]
+fn test_parse_pointer_loop() {
+    // Pointer to itself.
+    assert!(Message::parse(&query_with_name(b"\xc0\x0c")).is_err());
+
+    // Pointer inside its own name.
+    assert!(Message::parse(&query_with_name(b"\x01a\xc0\x0c")).is_err());
+
+    // Two records pointing to each other.
+    let mut input = vec![0x12, 0x34, 1, 0, 0, 2, 0, 0, 0, 0, 0, 0];
+    input.extend(b"\x01a\xc0\x14\x00\x01\x00\x01");
+    input.extend(b"\x01b\xc0\x0c\x00\x01\x00\x01");
+    assert!(Message::parse(&input).is_err());
+}
+
+#[test]
+fn test_parse_forward_pointer() {
+    let mut input = vec![0x12, 0x34, 1, 0, 0, 2, 0, 0, 0, 0, 0, 0];
+    input.extend(b"\xc0\x12\x00\x01\x00\x01");
+    input.extend(b"\x01b\x00\x00\x01\x00\x01");
+    assert!(Message::parse(&input).is_err());
+}
+
+#[test]
+fn test_parse_pointer_out_of_bounds() {
+    assert!(Message::parse(&query_with_name(b"\x01a\xff\xff")).is_err());
+    assert!(Message::parse(&[0x12, 0x34, 1, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0xc0]).is_err());
+}
+
+#[test]
+fn test_parse_bad_label_type() {
+    assert!(Message::parse(&query_with_name(b"\x40\x00")).is_err());
+    assert!(Message::parse(&query_with_name(b"\x80\x00")).is_err());
+}
+
+#[test]
+fn test_parse_name_too_long() {
+    // 4 * 64 + 1 = 257 bytes.
+    let mut name = Vec::new();
+    for _ in 0..4 {
+        name.push(63);
+        name.extend([b'a'; 63]);
+    }
+    name.push(0);
+    assert!(Message::parse(&query_with_name(&name)).is_err());
+
+    // Same using compression: 2 * 64 + 2 then pointer to previous 128 + 1 bytes.
+    let mut input = vec![0x12, 0x34, 1, 0, 0, 2, 0, 0, 0, 0, 0, 0];
+    input.extend(&name[128..]);
+    input.extend([0, 1, 0, 1]);
+    input.extend(&name[..128]);
+    input.extend([0xc0, 12, 0, 1, 0, 1]);
+    assert!(Message::parse(&input).is_err());
+}
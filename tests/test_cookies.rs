@@ -0,0 +1,278 @@
+mod common;
+
+use std::net::{IpAddr, SocketAddr};
+
+use dns_starter_rust::{
+    client::DnsClient,
+    cookie::{CookieCheck, CookieSecrets},
+    message::*,
+    DnsError,
+};
+
+const NOW: u64 = 1_700_000_000;
+const CLIENT: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+
+fn secrets() -> CookieSecrets {
+    CookieSecrets::new(b"0123456789abcdef".to_vec())
+}
+
+fn ip(text: &str) -> IpAddr {
+    text.parse().unwrap()
+}
+
+fn hex(data: &[u8]) -> String {
+    data.iter().map(|x| format!("{x:02x}")).collect()
+}
+
+fn query(cookie: Option<EdnsOption>) -> Message {
+    let mut edns = Edns::new(1232);
+    edns.options.extend(cookie);
+    MessageBuilder::query()
+        .question(QuestionSection::new_a("example.com").unwrap())
+        .edns(edns)
+        .build()
+}
+
+fn cookie(server: Vec<u8>) -> Option<EdnsOption> {
+    Some(EdnsOption::Cookie {
+        client: CLIENT,
+        server,
+    })
+}
+
+#[test]
+fn test_cookie_option() {
+    let option = cookie(vec![0xaa; 16]).unwrap();
+    let mut buf = vec![];
+    option.encode(&mut buf).unwrap();
+    assert_eq!(buf[..4], [0, 10, 0, 24]);
+    assert_eq!(EdnsOption::parse(&buf).unwrap().1, option);
+    assert_eq!(
+        option.to_string(),
+        format!("COOKIE: 0102030405060708{}", "aa".repeat(16))
+    );
+
+    // Server cookies are 8 to 32 bytes.
+    let input = b"\x00\x0a\x00\x0c\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0a\x0b\x0c";
+    assert_eq!(
+        EdnsOption::parse(input).unwrap().1,
+        EdnsOption::Unknown(10, input[4..].to_vec())
+    );
+
+    let mut edns = Edns::new(1232);
+    edns.set_option(EdnsOption::Unknown(10, vec![]));
+    edns.set_option(option.clone());
+    assert_eq!(edns.options, vec![option.clone()]);
+    assert_eq!(edns.option(EdnsOption::COOKIE), Some(&option));
+}
+
+#[test]
+fn test_server_cookie() {
+    let secrets = secrets();
+    let server = secrets.server_cookie(&CLIENT, ip("192.0.2.1"), NOW);
+    // Computed with Python `hmac` module.
+    assert_eq!(hex(&server), "010000006553f1000d2dfc9d365c2350");
+
+    let check = secrets.check(&query(cookie(server.clone())), ip("192.0.2.1"), NOW + 60);
+    assert_eq!(
+        check,
+        CookieCheck::Valid {
+            client: CLIENT,
+            server: server.clone(),
+            is_fresh: true,
+        }
+    );
+    assert_eq!(
+        secrets.response_option(&check, ip("192.0.2.1"), NOW + 60),
+        cookie(server.clone())
+    );
+
+    // Other client address or cookie
+    let check = secrets.check(&query(cookie(server.clone())), ip("192.0.2.2"), NOW);
+    assert_eq!(check, CookieCheck::Invalid(CLIENT));
+    let mut other = query(cookie(server.clone()));
+    let mut edns = other.edns().unwrap();
+    edns.set_option(EdnsOption::Cookie {
+        client: [0; 8],
+        server: server.clone(),
+    });
+    other.set_edns(Some(edns));
+    let check = secrets.check(&other, ip("192.0.2.1"), NOW);
+    assert_eq!(check, CookieCheck::Invalid([0; 8]));
+
+    // Tampered cookie
+    let mut tampered = server.clone();
+    tampered[15] ^= 1;
+    let check = secrets.check(&query(cookie(tampered)), ip("192.0.2.1"), NOW);
+    assert_eq!(check, CookieCheck::Invalid(CLIENT));
+}
+
+#[test]
+fn test_server_cookie_age() {
+    let secrets = secrets();
+    let client_ip = ip("2001:db8::1");
+    let server = secrets.server_cookie(&CLIENT, client_ip, NOW);
+    let request = query(cookie(server.clone()));
+
+    // Replaced after half an hour.
+    let check = secrets.check(&request, client_ip, NOW + 1801);
+    assert!(matches!(
+        check,
+        CookieCheck::Valid {
+            is_fresh: false,
+            ..
+        }
+    ));
+    assert_eq!(
+        secrets.response_option(&check, client_ip, NOW + 1801),
+        cookie(secrets.server_cookie(&CLIENT, client_ip, NOW + 1801))
+    );
+
+    // Expired after an hour, or too far in the future.
+    let check = secrets.check(&request, client_ip, NOW + 3601);
+    assert_eq!(check, CookieCheck::Invalid(CLIENT));
+    let check = secrets.check(&request, client_ip, NOW - 301);
+    assert_eq!(check, CookieCheck::Invalid(CLIENT));
+    let check = secrets.check(&request, client_ip, NOW - 300);
+    assert!(matches!(check, CookieCheck::Valid { is_fresh: true, .. }));
+}
+
+#[test]
+fn test_cookie_secret_rotation() {
+    let mut secrets = secrets();
+    let client_ip = ip("192.0.2.1");
+    let request = query(cookie(secrets.server_cookie(&CLIENT, client_ip, NOW)));
+
+    // Previous secret is still accepted, but its cookies are replaced.
+    secrets.rotate(b"fedcba9876543210".to_vec());
+    let check = secrets.check(&request, client_ip, NOW);
+    assert!(matches!(
+        check,
+        CookieCheck::Valid {
+            is_fresh: false,
+            ..
+        }
+    ));
+    let Some(EdnsOption::Cookie { server, .. }) = secrets.response_option(&check, client_ip, NOW)
+    else {
+        panic!("Missing cookie");
+    };
+    assert_eq!(
+        secrets.check(&query(cookie(server)), client_ip, NOW),
+        CookieCheck::Valid {
+            client: CLIENT,
+            server: secrets.server_cookie(&CLIENT, client_ip, NOW),
+            is_fresh: true,
+        }
+    );
+
+    secrets.rotate(b"another secret".to_vec());
+    let check = secrets.check(&request, client_ip, NOW);
+    assert_eq!(check, CookieCheck::Invalid(CLIENT));
+}
+
+#[test]
+fn test_cookie_check() {
+    let secrets = secrets();
+    let client_ip = ip("192.0.2.1");
+
+    let mut plain = query(None);
+    assert_eq!(secrets.check(&plain, client_ip, NOW), CookieCheck::Missing);
+    plain.set_edns(None);
+    assert_eq!(secrets.check(&plain, client_ip, NOW), CookieCheck::Missing);
+    assert_eq!(
+        secrets.response_option(&CookieCheck::Missing, client_ip, NOW),
+        None
+    );
+
+    let malformed = query(Some(EdnsOption::Unknown(10, vec![1, 2, 3])));
+    assert_eq!(
+        secrets.check(&malformed, client_ip, NOW),
+        CookieCheck::Malformed
+    );
+
+    let check = secrets.check(&query(cookie(vec![])), client_ip, NOW);
+    assert_eq!(check, CookieCheck::ClientOnly(CLIENT));
+    assert_eq!(
+        secrets.response_option(&check, client_ip, NOW),
+        cookie(secrets.server_cookie(&CLIENT, client_ip, NOW))
+    );
+
+    // Unknown server cookie format
+    let check = secrets.check(&query(cookie(vec![2; 16])), client_ip, NOW);
+    assert_eq!(check, CookieCheck::Invalid(CLIENT));
+}
+
+/// Serve answers on a local UDP port to queries with a valid server
+/// cookie, and BADCOOKIE to others. `tamper` edits responses before sending.
+fn serve(tamper: fn(&mut Message)) -> SocketAddr {
+    let secrets = secrets();
+    common::serve(move |query, _, peer| {
+        let check = secrets.check(query, peer.ip(), NOW);
+        let mut response = MessageBuilder::reply_to(query);
+        response = match check {
+            CookieCheck::Valid { .. } => {
+                response.answer("example.com. 60 IN A 192.0.2.1".parse().unwrap())
+            }
+            _ => response.response_code(ResponseCode::BadCookie),
+        };
+        let mut edns = Edns::new(1232);
+        edns.options
+            .extend(secrets.response_option(&check, peer.ip(), NOW));
+        let mut response = response.edns(edns).build();
+        tamper(&mut response);
+        Some(response)
+    })
+}
+
+#[test]
+fn test_client_cookies() {
+    let addr = serve(|_| {});
+    let question = QuestionSection::new_a("example.com").unwrap();
+
+    // First query gets BADCOOKIE, and is retried with the server cookie.
+    let mut client = DnsClient::connect("127.0.0.1:0", addr).unwrap();
+    let client_cookie = client.client_cookie().unwrap();
+    assert_eq!(client.server_cookie(), None);
+    let answer = client.query(&question).unwrap();
+    assert_eq!(answer.data, RData::A("192.0.2.1".parse().unwrap()));
+    let expected = secrets().server_cookie(&client_cookie, ip("127.0.0.1"), NOW);
+    assert_eq!(client.server_cookie(), Some(expected.as_slice()));
+
+    // Server cookie is remembered.
+    client.query(&question).unwrap();
+    assert_eq!(client.server_cookie(), Some(expected.as_slice()));
+
+    // Without cookies
+    client.set_cookies(false);
+    assert_eq!(client.client_cookie(), None);
+    assert_eq!(client.server_cookie(), None);
+    assert!(matches!(
+        client.query(&question),
+        Err(DnsError::ErrorResponse(ResponseCode::BadCookie))
+    ));
+
+    // New client cookie
+    client.set_cookies(true);
+    assert_ne!(client.client_cookie(), None);
+    client.query(&question).unwrap();
+}
+
+#[test]
+fn test_client_bad_cookie() {
+    let addr = serve(|response| {
+        let mut edns = response.edns().unwrap();
+        if let Some(EdnsOption::Cookie { client, .. }) = edns.options.first_mut() {
+            client[0] ^= 1;
+        }
+        response.set_edns(Some(edns));
+    });
+    let question = QuestionSection::new_a("example.com").unwrap();
+
+    let mut client = DnsClient::connect("127.0.0.1:0", addr).unwrap();
+    assert!(matches!(
+        client.query(&question),
+        Err(DnsError::InvalidResponse(_))
+    ));
+    assert_eq!(client.server_cookie(), None);
+}
@@ -0,0 +1,80 @@
+use dns_starter_rust::message::*;
+
+fn record(s: &str) -> AnswerSection {
+    s.parse().unwrap()
+}
+
+#[test]
+fn test_format_response() {
+    let query = MessageBuilder::query()
+        .id(43981)
+        .recursion_desired(true)
+        .question(QuestionSection::new_a("example.com").unwrap())
+        .build();
+
+    let mut edns = Edns::new(1232);
+    edns.dnssec_ok = true;
+    edns.options
+        .push(EdnsOption::Unknown(65001, vec![0xca, 0xfe]));
+
+    let response = MessageBuilder::reply_to(&query)
+        .recursion_available(true)
+        .authentic_data(true)
+        .answer(record("example.com. 300 IN A 192.0.2.1"))
+        .authority(record("example.com. 3600 IN NS ns1.example.com."))
+        .additional(record("ns1.example.com. 3600 IN A 192.0.2.53"))
+        .edns(edns)
+        .build();
+
+    assert_eq!(
+        response.to_string(),
+        "\
+;; ->>HEADER<<- opcode: QUERY, status: NOERROR, id: 43981
+;; flags: qr rd ra ad; QUERY: 1, ANSWER: 1, AUTHORITY: 1, ADDITIONAL: 2
+
+;; OPT PSEUDOSECTION:
+; EDNS: version: 0, flags: do; udp: 1232
+; OPT=65001: cafe
+
+;; QUESTION SECTION:
+;example.com.\t\tIN\tA
+
+;; ANSWER SECTION:
+example.com.\t300\tIN\tA\t192.0.2.1
+
+;; AUTHORITY SECTION:
+example.com.\t3600\tIN\tNS\tns1.example.com.
+
+;; ADDITIONAL SECTION:
+ns1.example.com.\t3600\tIN\tA\t192.0.2.53
+"
+    );
+}
+
+#[test]
+fn test_format_extended_status() {
+    let msg = MessageBuilder::response()
+        .id(1)
+        .opcode(OpCode::Status)
+        .response_code(ResponseCode::BadVersion)
+        .build();
+
+    assert_eq!(
+        msg.to_string(),
+        "\
+;; ->>HEADER<<- opcode: STATUS, status: BADVERS, id: 1
+;; flags: qr; QUERY: 0, ANSWER: 0, AUTHORITY: 0, ADDITIONAL: 1
+
+;; OPT PSEUDOSECTION:
+; EDNS: version: 0, flags:; udp: 512
+"
+    );
+}
+
+#[test]
+fn test_format_mnemonics() {
+    assert_eq!(OpCode::Reserved4.to_string(), "NOTIFY");
+    assert_eq!(OpCode::ReservedA.to_string(), "RESERVED10");
+    assert_eq!(ResponseCode::NonExistentDomain.to_string(), "NXDOMAIN");
+    assert_eq!(ResponseCode::Unknown(3841).to_string(), "RCODE3841");
+}
@@ -0,0 +1,493 @@
+mod common;
+
+use std::net::SocketAddr;
+
+use dns_starter_rust::{
+    client::DnsClient,
+    dnssec::{self, TrustAnchor, ValidationStatus},
+    message::*,
+    DnssecError,
+};
+
+/// Zones generated by `fixtures/sign_zones.py`.
+const SIGNED_ZONES: &str = include_str!("fixtures/signed_zones.txt");
+
+/// 2030-01-01, within fixtures signature validity.
+const NOW: u32 = 1_893_456_000;
+
+fn record(s: &str) -> AnswerSection {
+    s.parse().unwrap()
+}
+
+fn name(s: &str) -> DomainName {
+    s.parse().unwrap()
+}
+
+struct Zone {
+    apex: DomainName,
+    records: Vec<AnswerSection>,
+}
+
+/// Trust anchor and zones from fixtures.
+fn load_zones() -> (TrustAnchor, Vec<Zone>) {
+    let mut anchor = None;
+    let mut zones: Vec<Zone> = vec![];
+
+    for line in SIGNED_ZONES.lines() {
+        if let Some(apex) = line.strip_prefix(";; zone ") {
+            zones.push(Zone {
+                apex: name(apex),
+                records: vec![],
+            });
+        } else if !line.starts_with(";;") {
+            let rr = record(line);
+            match zones.last_mut() {
+                Some(zone) => zone.records.push(rr),
+                None => anchor = Some(TrustAnchor::new(rr.name, vec![rr.data])),
+            }
+        }
+    }
+    (anchor.unwrap(), zones)
+}
+
+fn zone_records(apex: &str) -> Vec<AnswerSection> {
+    let (_, zones) = load_zones();
+    zones
+        .into_iter()
+        .find(|x| x.apex == name(apex))
+        .unwrap()
+        .records
+}
+
+fn is_rrsig_of(record: &AnswerSection, rr_type: ResourceRecordType) -> bool {
+    matches!(record.data, RData::RRSIG { type_covered, .. } if type_covered == rr_type)
+}
+
+/// Records of a type, with their signatures.
+fn find(zone: &Zone, owner: &DomainName, rr_type: ResourceRecordType) -> Vec<AnswerSection> {
+    zone.records
+        .iter()
+        .filter(|x| x.name == *owner && (x.rr_type == rr_type || is_rrsig_of(x, rr_type)))
+        .cloned()
+        .collect()
+}
+
+/// Authoritative answer from the zone closest to the question name.
+fn resolve(zones: &[Zone], query: &Message) -> Message {
+    let question = &query.questions[0];
+    let zone = zones
+        .iter()
+        .filter(|x| question.name.is_subdomain_of(&x.apex))
+        // DS records are served by the parent zone.
+        .filter(|x| question.rr_type != ResourceRecordType::DS || question.name != x.apex)
+        .max_by_key(|x| x.apex.label_count())
+        .unwrap();
+
+    let exists = |owner: &DomainName| zone.records.iter().any(|x| x.name.is_subdomain_of(owner));
+    let mut answers = find(zone, &question.name, question.rr_type);
+    let mut response_code = ResponseCode::NoError;
+    let mut is_wildcard = false;
+
+    if !exists(&question.name) {
+        let encloser = question.name.parents().find(exists).unwrap();
+        let wildcard = encloser.prepend_label("*").unwrap();
+        answers = find(zone, &wildcard, question.rr_type)
+            .into_iter()
+            .map(|x| AnswerSection {
+                name: question.name.clone(),
+                ..x
+            })
+            .collect();
+        is_wildcard = exists(&wildcard);
+        if !is_wildcard {
+            response_code = ResponseCode::NonExistentDomain;
+        }
+    }
+
+    let mut builder = MessageBuilder::reply_to(query)
+        .authoritative_answer(true)
+        .response_code(response_code);
+    if answers.is_empty() || is_wildcard {
+        let denial = [
+            ResourceRecordType::SOA,
+            ResourceRecordType::NSEC,
+            ResourceRecordType::NSEC3,
+        ];
+        builder = builder.authorities(
+            zone.records
+                .iter()
+                .filter(|x| denial.iter().any(|t| x.rr_type == *t || is_rrsig_of(x, *t)))
+                .cloned(),
+        );
+    }
+    builder.answers(answers).build()
+}
+
+/// Serve fixtures zones on a local UDP port, altering responses with `tamper`.
+fn serve(tamper: fn(&mut Message)) -> SocketAddr {
+    let (_, zones) = load_zones();
+    common::serve(move |query, _, _| {
+        let mut response = resolve(&zones, query);
+        tamper(&mut response);
+        response.update_counts();
+        Some(response)
+    })
+}
+
+fn client(tamper: fn(&mut Message), anchor: Option<TrustAnchor>) -> DnsClient {
+    let mut client = DnsClient::connect("127.0.0.1:0", serve(tamper)).unwrap();
+    client.set_udp_payload_size(Some(4096));
+    client.set_trust_anchor(anchor);
+    client
+}
+
+fn status(client: &mut DnsClient, qname: &str, rr_type: ResourceRecordType) -> ValidationStatus {
+    let question = QuestionSection::new(name(qname), rr_type);
+    client.query_validated(&question).unwrap().status
+}
+
+#[test]
+fn test_key_tag_and_ds_digest() {
+    // RFC 4034 section 5.4 and RFC 4509 section 2.3
+    let key = record(
+        "dskey.example.com. 86400 IN DNSKEY 256 3 5 AQOeiiR0GOMYkDshWoSKz9XzfwJr1AYtsmx3TGkJaNXVbfi/2pHm822aJ5iI9BMzNXxeYCmZDRD99WYwYqUSdjMmmAphXdvxegXd/M5+X7OrzKBaMbCVdFLUUh6DhweJBjEVv5f2wwjM9XzcnOf+EPbtG9DMBmADjFDc2w/rljwvFw==",
+    );
+    assert_eq!(dnssec::key_tag(&key.data), Some(60485));
+    assert_eq!(dnssec::key_tag(&RData::NS(name("example.com"))), None);
+
+    let sha1 =
+        record("dskey.example.com. 86400 IN DS 60485 5 1 2BB183AF5F22588179A53B0A98631FAD1A292118");
+    let sha256 = record("dskey.example.com. 86400 IN DS 60485 5 2 D4B7D520E7BB5F0F67674A0CCEB1E3E0614B93C4F9E99B8383F6A1E4469DA50A");
+    for ds in [&sha1, &sha256] {
+        let RData::DS {
+            digest_type,
+            digest,
+            ..
+        } = &ds.data
+        else {
+            unreachable!();
+        };
+        assert_eq!(
+            dnssec::ds_digest(&key.name, &key.data, *digest_type).unwrap(),
+            *digest
+        );
+        assert!(dnssec::ds_matches(&key.name, &ds.data, &key.data));
+        assert!(!dnssec::ds_matches(
+            &name("example.com"),
+            &ds.data,
+            &key.data
+        ));
+    }
+
+    assert_eq!(
+        dnssec::ds_digest(&key.name, &key.data, 4),
+        Err(DnssecError::UnsupportedDigest(4))
+    );
+}
+
+#[test]
+fn test_nsec3_hash() {
+    // RFC 5155 appendix A
+    let salt = [0xaa, 0xbb, 0xcc, 0xdd];
+    assert_eq!(
+        encode_base32hex(&dnssec::nsec3_hash(&name("example"), &salt, 12)),
+        "0p9mhaveqvm6t7vbl5lop2u3t2rp3tom"
+    );
+    assert_eq!(
+        encode_base32hex(&dnssec::nsec3_hash(&name("a.example"), &salt, 12)),
+        "35mthgpgcu1qg68fab165klnsnk3dpvl"
+    );
+}
+
+fn encode_base32hex(data: &[u8]) -> String {
+    // Reuse presentation format of NSEC3 records.
+    let rr = AnswerSection {
+        name: name("example"),
+        rr_type: ResourceRecordType::NSEC3,
+        rr_class: ResourceRecordClass::IN,
+        ttl: 0,
+        data: RData::NSEC3 {
+            hash_algorithm: 1,
+            flags: 0,
+            iterations: 0,
+            salt: vec![],
+            next_hashed_owner_name: data.to_vec(),
+            types: vec![],
+        },
+    };
+    rr.to_string().rsplit(' ').next().unwrap().to_owned()
+}
+
+#[test]
+fn test_verify_rrsig() {
+    for (apex, owner) in [
+        ("example.", "www.example."),
+        ("signed.example.", "www.signed.example."),
+        ("nsec3.example.", "host.nsec3.example."),
+    ] {
+        let records = zone_records(apex);
+        let rrset: Vec<AnswerSection> = records
+            .iter()
+            .filter(|x| x.name == name(owner) && x.rr_type == ResourceRecordType::A)
+            .cloned()
+            .collect();
+        let rrsig = records
+            .iter()
+            .find(|x| x.name == name(owner) && is_rrsig_of(x, ResourceRecordType::A))
+            .unwrap();
+        let RData::RRSIG { key_tag, .. } = &rrsig.data else {
+            unreachable!();
+        };
+        let key = records
+            .iter()
+            .find(|x| dnssec::key_tag(&x.data) == Some(*key_tag))
+            .unwrap();
+
+        assert_eq!(
+            dnssec::verify_rrsig(&rrset, rrsig, &key.data, NOW),
+            Ok(()),
+            "{owner}"
+        );
+
+        // Owner name case does not matter.
+        let mut upper = rrset.clone();
+        upper[0].name = name(&owner.to_uppercase());
+        let mut upper_rrsig = rrsig.clone();
+        upper_rrsig.name = upper[0].name.clone();
+        assert_eq!(
+            dnssec::verify_rrsig(&upper, &upper_rrsig, &key.data, NOW),
+            Ok(())
+        );
+
+        let mut tampered = rrset.clone();
+        tampered[0].data = RData::A("192.0.2.99".parse().unwrap());
+        assert_eq!(
+            dnssec::verify_rrsig(&tampered, rrsig, &key.data, NOW),
+            Err(DnssecError::BadSignature),
+            "{owner}"
+        );
+
+        assert_eq!(
+            dnssec::verify_rrsig(&rrset, rrsig, &key.data, 1_500_000_000),
+            Err(DnssecError::SignatureNotYetValid)
+        );
+        assert_eq!(
+            dnssec::verify_rrsig(&rrset, rrsig, &key.data, 2_400_000_000),
+            Err(DnssecError::SignatureExpired)
+        );
+        assert_eq!(
+            dnssec::verify_rrsig(&rrset, rrsig, &RData::NS(name("example")), NOW),
+            Err(DnssecError::KeyMismatch)
+        );
+    }
+}
+
+#[test]
+fn test_verify_rrsig_mismatch() {
+    let records = zone_records("example.");
+    let rrset: Vec<AnswerSection> = records
+        .iter()
+        .filter(|x| x.name == name("www.example") && x.rr_type == ResourceRecordType::A)
+        .cloned()
+        .collect();
+    let soa_rrsig = records
+        .iter()
+        .find(|x| is_rrsig_of(x, ResourceRecordType::SOA))
+        .unwrap();
+    let key = records
+        .iter()
+        .find(|x| x.rr_type == ResourceRecordType::DNSKEY)
+        .unwrap();
+
+    assert_eq!(
+        dnssec::verify_rrsig(&rrset, soa_rrsig, &key.data, NOW),
+        Err(DnssecError::RrsigMismatch)
+    );
+    assert_eq!(
+        dnssec::verify_rrsig(&[], soa_rrsig, &key.data, NOW),
+        Err(DnssecError::RrsigMismatch)
+    );
+}
+
+#[test]
+fn test_secure_answers() {
+    let (anchor, _) = load_zones();
+    let mut client = client(|_| {}, Some(anchor));
+
+    for (qname, rr_type) in [
+        ("www.example", ResourceRecordType::A),
+        ("www.signed.example", ResourceRecordType::A),
+        ("host.nsec3.example", ResourceRecordType::A),
+        ("host.costly.example", ResourceRecordType::A),
+        ("example", ResourceRecordType::SOA),
+        ("anything.wild.signed.example", ResourceRecordType::TXT),
+    ] {
+        assert_eq!(
+            status(&mut client, qname, rr_type),
+            ValidationStatus::Secure,
+            "{qname} {rr_type:?}"
+        );
+    }
+}
+
+#[test]
+fn test_secure_denial() {
+    let (anchor, _) = load_zones();
+    let mut client = client(|_| {}, Some(anchor));
+
+    for (qname, rr_type, response_code) in [
+        // NSEC
+        (
+            "nope.example",
+            ResourceRecordType::A,
+            ResponseCode::NonExistentDomain,
+        ),
+        (
+            "www.example",
+            ResourceRecordType::AAAA,
+            ResponseCode::NoError,
+        ),
+        (
+            "zzz.signed.example",
+            ResourceRecordType::A,
+            ResponseCode::NonExistentDomain,
+        ),
+        (
+            "x.wild.signed.example",
+            ResourceRecordType::A,
+            ResponseCode::NoError,
+        ),
+        // NSEC3
+        (
+            "nope.nsec3.example",
+            ResourceRecordType::A,
+            ResponseCode::NonExistentDomain,
+        ),
+        (
+            "host.nsec3.example",
+            ResourceRecordType::AAAA,
+            ResponseCode::NoError,
+        ),
+    ] {
+        let question = QuestionSection::new(name(qname), rr_type);
+        let validated = client.query_validated(&question).unwrap();
+        assert_eq!(validated.response.response_code(), response_code);
+        assert!(validated.response.answers.is_empty());
+        assert_eq!(
+            validated.status,
+            ValidationStatus::Secure,
+            "{qname} {rr_type:?}"
+        );
+    }
+}
+
+#[test]
+fn test_insecure() {
+    let (anchor, _) = load_zones();
+
+    // Unsigned delegation
+    let mut validating = client(|_| {}, Some(anchor.clone()));
+    assert_eq!(
+        status(
+            &mut validating,
+            "www.unsigned.example",
+            ResourceRecordType::A
+        ),
+        ValidationStatus::Insecure
+    );
+    assert_eq!(
+        status(
+            &mut validating,
+            "nope.unsigned.example",
+            ResourceRecordType::A
+        ),
+        ValidationStatus::Insecure
+    );
+
+    // NSEC3 with too many iterations (RFC 9276 section 3.2)
+    for (qname, rr_type) in [
+        ("nope.costly.example", ResourceRecordType::A),
+        ("host.costly.example", ResourceRecordType::AAAA),
+    ] {
+        assert_eq!(
+            status(&mut validating, qname, rr_type),
+            ValidationStatus::Insecure,
+            "{qname} {rr_type:?}"
+        );
+    }
+
+    // Trust anchor for another zone
+    let other = TrustAnchor::new(name("org"), anchor.records().to_vec());
+    let mut unrelated = client(|_| {}, Some(other));
+    assert_eq!(
+        status(&mut unrelated, "www.example", ResourceRecordType::A),
+        ValidationStatus::Insecure
+    );
+
+    // No trust anchor
+    let mut plain = client(|_| {}, None);
+    assert_eq!(
+        status(&mut plain, "www.example", ResourceRecordType::A),
+        ValidationStatus::Insecure
+    );
+}
+
+#[test]
+fn test_bogus_answers() {
+    let (anchor, _) = load_zones();
+
+    let mut tampered = client(
+        |msg| {
+            for rr in &mut msg.answers {
+                if rr.rr_type == ResourceRecordType::A {
+                    rr.data = RData::A("192.0.2.99".parse().unwrap());
+                }
+            }
+        },
+        Some(anchor.clone()),
+    );
+    assert_eq!(
+        status(&mut tampered, "www.signed.example", ResourceRecordType::A),
+        ValidationStatus::Bogus(DnssecError::BadSignature)
+    );
+
+    let mut stripped = client(
+        |msg| {
+            if msg.questions[0].rr_type == ResourceRecordType::A {
+                msg.answers
+                    .retain(|x| x.rr_type != ResourceRecordType::RRSIG);
+            }
+        },
+        Some(anchor.clone()),
+    );
+    assert_eq!(
+        status(&mut stripped, "www.signed.example", ResourceRecordType::A),
+        ValidationStatus::Bogus(DnssecError::MissingSignature)
+    );
+
+    let mut no_denial = client(
+        |msg| {
+            msg.authorities
+                .retain(|x| x.rr_type == ResourceRecordType::SOA)
+        },
+        Some(anchor.clone()),
+    );
+    assert_eq!(
+        status(&mut no_denial, "nope.example", ResourceRecordType::A),
+        ValidationStatus::Bogus(DnssecError::MissingDenial)
+    );
+
+    let RData::DS { digest, .. } = &anchor.records()[0] else {
+        unreachable!();
+    };
+    let mut wrong_digest = anchor.records()[0].clone();
+    if let RData::DS { digest: wrong, .. } = &mut wrong_digest {
+        *wrong = digest.iter().map(|x| x ^ 0xff).collect();
+    }
+    let wrong_anchor = TrustAnchor::new(anchor.zone().clone(), vec![wrong_digest]);
+    let mut untrusted = client(|_| {}, Some(wrong_anchor));
+    assert_eq!(
+        status(&mut untrusted, "www.example", ResourceRecordType::A),
+        ValidationStatus::Bogus(DnssecError::NoTrustedKey)
+    );
+}
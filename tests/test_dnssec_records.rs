@@ -0,0 +1,272 @@
+use dns_starter_rust::{message::*, PresentationError};
+
+fn record(s: &str) -> AnswerSection {
+    s.parse().unwrap()
+}
+
+fn encode(data: &RData) -> Vec<u8> {
+    let mut buf = vec![];
+    data.encode(&mut buf).unwrap();
+    buf
+}
+
+fn assert_round_trip(rr_type: ResourceRecordType, input: &[u8], expected: RData) {
+    let (_, data) = RData::parse(input, rr_type, input).unwrap();
+    assert_eq!(data, expected);
+    assert_eq!(encode(&data), input);
+}
+
+#[test]
+fn test_nsec_type_bitmap() {
+    // RFC 4034 section 4.3
+    let mut input = b"\x04host\x07example\x03com\x00".to_vec();
+    input.extend([0x00, 0x06, 0x40, 0x01, 0x00, 0x00, 0x00, 0x03]);
+    input.extend([0x04, 0x1b]);
+    input.extend([0; 26]);
+    input.push(0x20);
+
+    assert_round_trip(
+        ResourceRecordType::NSEC,
+        &input,
+        RData::NSEC {
+            next_domain_name: "host.example.com".parse().unwrap(),
+            types: vec![
+                ResourceRecordType::A,
+                ResourceRecordType::MX,
+                ResourceRecordType::RRSIG,
+                ResourceRecordType::NSEC,
+                ResourceRecordType::Unknown(1234),
+            ],
+        },
+    );
+
+    // Types are sorted and deduplicated on encode.
+    let data = RData::NSEC {
+        next_domain_name: "host.example.com".parse().unwrap(),
+        types: vec![
+            ResourceRecordType::NSEC,
+            ResourceRecordType::A,
+            ResourceRecordType::Unknown(1234),
+            ResourceRecordType::RRSIG,
+            ResourceRecordType::MX,
+            ResourceRecordType::A,
+        ],
+    };
+    assert_eq!(encode(&data), input);
+}
+
+#[test]
+fn test_invalid_type_bitmap() {
+    let parse = |bitmap: &[u8]| {
+        let mut input = b"\x00".to_vec();
+        input.extend(bitmap);
+        RData::parse(&input, ResourceRecordType::NSEC, &input).is_ok()
+    };
+
+    assert!(parse(&[]));
+    assert!(parse(&[0, 1, 0x40, 1, 1, 0x80]));
+    // Empty bitmap
+    assert!(!parse(&[0, 0]));
+    // Bitmap too long
+    assert!(!parse(&[[0, 33].as_slice(), &[0xFF; 33]].concat()));
+    // Windows out of order
+    assert!(!parse(&[1, 1, 0x80, 0, 1, 0x40]));
+    // Truncated bitmap
+    assert!(!parse(&[0, 2, 0x40]));
+}
+
+#[test]
+fn test_parse_ds_and_nsec3param() {
+    let mut input = 60485_u16.to_be_bytes().to_vec();
+    input.extend([5, 1, 0xde, 0xad, 0xbe, 0xef]);
+    assert_round_trip(
+        ResourceRecordType::DS,
+        &input,
+        RData::DS {
+            key_tag: 60485,
+            algorithm: 5,
+            digest_type: 1,
+            digest: vec![0xde, 0xad, 0xbe, 0xef],
+        },
+    );
+
+    assert_round_trip(
+        ResourceRecordType::NSEC3PARAM,
+        &[1, 0, 0, 12, 4, 0xaa, 0xbb, 0xcc, 0xdd],
+        RData::NSEC3PARAM {
+            hash_algorithm: 1,
+            flags: 0,
+            iterations: 12,
+            salt: vec![0xaa, 0xbb, 0xcc, 0xdd],
+        },
+    );
+}
+
+#[test]
+fn test_dnskey_presentation() {
+    // RFC 4034 section 2.3
+    let text = "example.com. 86400 IN DNSKEY 256 3 5 ( AQPSKmynfzW4kyBv015MUG2DeIQ3
+        Cbl+BBZH4b/0PY1kxkmvHjcZc8no kfzj31GajIQKY+5CptLr3buXA10h
+        WqTkF7H6RfoRqXQeogmMHfpftf6z Mv1LyBUgia7za6ZEzOJBOztyvhjL
+        742iU/TpPSEDhm2SNKLijfUppn1U aNvv4w== )";
+    let rr = record(text);
+
+    let RData::DNSKEY {
+        flags,
+        protocol,
+        algorithm,
+        public_key,
+    } = &rr.data
+    else {
+        panic!("Unexpected RDATA: {:?}", rr.data);
+    };
+    assert_eq!((*flags, *protocol, *algorithm), (256, 3, 5));
+    assert_eq!(public_key.len(), 130);
+    assert_eq!(&public_key[..4], &[0x01, 0x03, 0xd2, 0x2a]);
+
+    assert_eq!(
+        rr.to_string(),
+        "example.com. 86400 IN DNSKEY 256 3 5 AQPSKmynfzW4kyBv015MUG2DeIQ3Cbl+BBZH4b/0PY1kxkmvHjcZc8nokfzj31GajIQKY+5CptLr3buXA10hWqTkF7H6RfoRqXQeogmMHfpftf6zMv1LyBUgia7za6ZEzOJBOztyvhjL742iU/TpPSEDhm2SNKLijfUppn1UaNvv4w=="
+    );
+    assert_eq!(record(&rr.to_string()), rr);
+}
+
+#[test]
+fn test_rrsig_presentation() {
+    // RFC 4034 section 3.3
+    let text = "host.example.com. 86400 IN RRSIG A 5 3 86400 20030322173103 (
+        20030220173103 2642 example.com.
+        oJB1W6WNGv+ldvQ3WDG0MQkg5IEhjRip8WTr
+        PYGv07h108dUKGMeDPKijVCHX3DDKdfb+v6o
+        B9wfuh3DTJXUAfI/M0zmO/zz8bW0Rznl8O3t
+        GNazPwQKkRN20XPXV6nwwfoXmJQbsLNrLfkG
+        J5D6fwFm8nN+6pBzeDQfsS3Ap3o= )";
+    let rr = record(text);
+
+    let RData::RRSIG {
+        type_covered,
+        expiration,
+        inception,
+        key_tag,
+        signer_name,
+        signature,
+        ..
+    } = &rr.data
+    else {
+        panic!("Unexpected RDATA: {:?}", rr.data);
+    };
+    assert_eq!(*type_covered, ResourceRecordType::A);
+    assert_eq!(*expiration, 1048354263);
+    assert_eq!(*inception, 1045762263);
+    assert_eq!(*key_tag, 2642);
+    assert_eq!(*signer_name, "example.com".parse().unwrap());
+    assert_eq!(signature.len(), 128);
+
+    let formatted = rr.to_string();
+    assert!(formatted.starts_with(
+        "host.example.com. 86400 IN RRSIG A 5 3 86400 20030322173103 20030220173103 2642 example.com. oJB1W6WNGv+l"
+    ));
+    assert_eq!(record(&formatted), rr);
+
+    // Wire round trip, signer name is never compressed.
+    let mut buf = vec![];
+    rr.encode(&mut buf).unwrap();
+    let (_, decoded) = AnswerSection::parse(&buf, &buf).unwrap();
+    assert_eq!(decoded, rr);
+
+    // Times may also be given as seconds since epoch.
+    let data = RData::from_presentation(
+        ResourceRecordType::RRSIG,
+        "A 5 3 86400 4294967295 0 2642 example.com. AAAA",
+    )
+    .unwrap();
+    assert!(data
+        .to_string()
+        .starts_with("A 5 3 86400 21060207062815 19700101000000 "));
+}
+
+#[test]
+fn test_ds_and_nsec3_presentation() {
+    // RFC 4034 section 5.4
+    let rr = record(
+        "dskey.example.com. 86400 IN DS 60485 5 1 ( 2BB183AF5F22588179A53B0A
+                                                    98631FAD1A292118 )",
+    );
+    assert_eq!(
+        rr.to_string(),
+        "dskey.example.com. 86400 IN DS 60485 5 1 2BB183AF5F22588179A53B0A98631FAD1A292118"
+    );
+
+    // RFC 5155 appendix A
+    let rr = record(
+        "0p9mhaveqvm6t7vbl5lop2u3t2rp3tom.example. 3600 IN NSEC3 1 1 12 aabbccdd (
+            2t7b4g4vsa5smi47k61mv5bv1a22bojr MX DNSKEY NS SOA NSEC3PARAM RRSIG )",
+    );
+    let RData::NSEC3 {
+        next_hashed_owner_name,
+        types,
+        ..
+    } = &rr.data
+    else {
+        panic!("Unexpected RDATA: {:?}", rr.data);
+    };
+    assert_eq!(next_hashed_owner_name.len(), 20);
+    assert_eq!(types.len(), 6);
+
+    // Wire encoding sorts types.
+    let buf = encode(&rr.data);
+    let (_, decoded) = RData::parse(&buf, ResourceRecordType::NSEC3, &buf).unwrap();
+    assert_eq!(
+        decoded.to_string(),
+        "1 1 12 aabbccdd 2t7b4g4vsa5smi47k61mv5bv1a22bojr NS SOA MX RRSIG DNSKEY NSEC3PARAM"
+    );
+
+    let data = RData::from_presentation(ResourceRecordType::NSEC3PARAM, "1 0 0 -").unwrap();
+    assert_eq!(
+        data,
+        RData::NSEC3PARAM {
+            hash_algorithm: 1,
+            flags: 0,
+            iterations: 0,
+            salt: vec![],
+        }
+    );
+    assert_eq!(data.to_string(), "1 0 0 -");
+}
+
+#[test]
+fn test_dnssec_presentation_errors() {
+    let parse = RData::from_presentation;
+    assert_eq!(
+        parse(ResourceRecordType::DNSKEY, "256 3 5 AQ=x"),
+        Err(PresentationError::InvalidField(
+            "public key",
+            "AQ=x".to_string()
+        ))
+    );
+    assert_eq!(
+        parse(ResourceRecordType::DS, "1 5 1"),
+        Err(PresentationError::MissingField("digest"))
+    );
+    assert_eq!(
+        parse(
+            ResourceRecordType::RRSIG,
+            "A 5 3 86400 20030230000000 0 1 example.com. AAAA"
+        ),
+        Err(PresentationError::InvalidField(
+            "signature expiration",
+            "20030230000000".to_string()
+        ))
+    );
+    assert_eq!(
+        parse(ResourceRecordType::NSEC, "host.example.com. A BOGUS"),
+        Err(PresentationError::InvalidField("type", "BOGUS".to_string()))
+    );
+    assert_eq!(
+        parse(ResourceRecordType::NSEC3, "1 0 0 - 2t7b4g4"),
+        Err(PresentationError::InvalidField(
+            "next hashed owner name",
+            "2t7b4g4".to_string()
+        ))
+    );
+}
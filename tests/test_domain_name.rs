@@ -0,0 +1,214 @@
+use std::collections::HashSet;
+
+use dns_starter_rust::{message::*, DomainNameError};
+
+fn name(url: &str) -> DomainName {
+    url.parse().unwrap()
+}
+
+#[test]
+fn test_parse() {
+    let n = name("www.Example.com");
+    assert_eq!(
+        n.labels(),
+        [b"www".to_vec(), b"Example".to_vec(), b"com".to_vec()]
+    );
+    assert_eq!(n.wire_len(), 17);
+    assert_eq!(n.to_string(), "www.Example.com.");
+
+    // Trailing dot
+    assert_eq!(name("www.example.com."), n);
+
+    // Root
+    assert!(name(".").is_root());
+    assert_eq!(DomainName::root().to_string(), ".");
+}
+
+#[test]
+fn test_parse_escapes() {
+    let n = name(r"a\.b.c\\d.\000\255\032x.com");
+    assert_eq!(
+        n.labels(),
+        [
+            b"a.b".to_vec(),
+            b"c\\d".to_vec(),
+            b"\x00\xff x".to_vec(),
+            b"com".to_vec()
+        ]
+    );
+
+    // Display escape special and non printable bytes
+    assert_eq!(n.to_string(), r"a\.b.c\\d.\000\255\032x.com.");
+    assert_eq!(name(&n.to_string()), n);
+
+    assert_eq!("a\\".parse::<DomainName>(), Err(DomainNameError::BadEscape));
+    assert_eq!(
+        "a\\25".parse::<DomainName>(),
+        Err(DomainNameError::BadEscape)
+    );
+    assert_eq!(
+        "a\\256".parse::<DomainName>(),
+        Err(DomainNameError::BadEscape)
+    );
+}
+
+#[test]
+fn test_parse_invalid() {
+    for input in ["", "a..b", ".a", "a.."] {
+        assert_eq!(
+            input.parse::<DomainName>(),
+            Err(DomainNameError::EmptyLabel),
+            "{input}"
+        );
+    }
+
+    let label = "a".repeat(64);
+    assert_eq!(
+        label.parse::<DomainName>(),
+        Err(DomainNameError::LabelTooLong(64))
+    );
+
+    // 4 * 63 + 4 length + root = 257
+    let long = vec!["a".repeat(63); 4].join(".");
+    assert_eq!(
+        long.parse::<DomainName>(),
+        Err(DomainNameError::NameTooLong)
+    );
+    // 3 * 63 + 61 + 4 length + root = 255
+    let long = format!("{}.{}", vec!["a".repeat(63); 3].join("."), "a".repeat(61));
+    assert_eq!(name(&long).wire_len(), 255);
+
+    assert_eq!(
+        QuestionSection::new_a("hello..com"),
+        Err(DomainNameError::EmptyLabel)
+    );
+}
+
+#[test]
+fn test_case_insensitive() {
+    assert_eq!(name("WWW.example.COM"), name("www.EXAMPLE.com"));
+    assert_ne!(name("www.example.com"), name("www.example.org"));
+    assert_ne!(name("www.example.com"), name("example.com"));
+
+    let set: HashSet<_> = [name("Example.com"), name("example.COM")].into();
+    assert_eq!(set.len(), 1);
+}
+
+#[test]
+fn test_canonical_ordering() {
+    // Check: https://www.rfc-editor.org/rfc/rfc4034#section-6.1
+    let expected: Vec<_> = [
+        "example",
+        "a.example",
+        "yljkjljk.a.example",
+        "Z.a.example",
+        r"zABC.a.EXAMPLE",
+        "z.example",
+        r"\001.z.example",
+        "*.z.example",
+        r"\200.z.example",
+    ]
+    .into_iter()
+    .map(name)
+    .collect();
+
+    let mut names = expected.clone();
+    names.reverse();
+    names.sort();
+    assert_eq!(names, expected);
+}
+
+#[test]
+fn test_hierarchy() {
+    let n = name("www.example.com");
+
+    assert!(n.is_subdomain_of(&name("EXAMPLE.com")));
+    assert!(n.is_subdomain_of(&n));
+    assert!(n.is_subdomain_of(&DomainName::root()));
+    assert!(!n.is_subdomain_of(&name("ample.com")));
+    assert!(!name("example.com").is_subdomain_of(&n));
+
+    assert_eq!(n.parent(), Some(name("example.com")));
+    assert_eq!(DomainName::root().parent(), None);
+    assert_eq!(
+        n.parents().collect::<Vec<_>>(),
+        vec![name("example.com"), name("com"), DomainName::root()]
+    );
+
+    assert_eq!(name("example.com").prepend_label("www"), Ok(n));
+}
+
+#[test]
+fn test_unicode_names() {
+    let n = DomainName::from_unicode("Bücher.example").unwrap();
+    assert_eq!(n.to_string(), "xn--bcher-kva.example.");
+    assert_eq!(n.to_unicode(), "bücher.example.");
+    assert_eq!(n, name("xn--bcher-kva.example"));
+    assert_eq!(QuestionSection::new_a("bücher.example").unwrap().name, n);
+
+    // Ideographic full stop separates labels.
+    let n = DomainName::from_unicode("例え。テスト").unwrap();
+    assert_eq!(n.to_string(), "xn--r8jz45g.xn--zckzah.");
+    assert_eq!(n.to_unicode(), "例え.テスト.");
+
+    // RFC 3492 section 7.1
+    for (unicode, ascii) in [
+        ("他们为什么不说中文", "ihqwcrb4cv8a8dqg056pqjye"),
+        ("他們爲什麽不說中文", "ihqwctvzc91f659drss3x8bo0yb"),
+        ("3年B組金八先生", "3b-ww4c5e180e575a65lsy2b"),
+        ("そのスピードで", "d9juau41awczczp"),
+        ("münchen", "mnchen-3ya"),
+    ] {
+        let n = DomainName::from_unicode(unicode).unwrap();
+        assert_eq!(n.labels(), [format!("xn--{ascii}").into_bytes()]);
+        assert_eq!(n.to_unicode(), format!("{}.", unicode.to_lowercase()));
+        assert_eq!(name(&format!("XN--{ascii}")).to_unicode(), n.to_unicode());
+    }
+
+    // ASCII names are unchanged, escapes included.
+    let n = DomainName::from_unicode(r"www.Ex\.ample.com").unwrap();
+    assert_eq!(n.labels()[1], b"Ex.ample");
+    assert_eq!(n.to_unicode(), r"www.Ex\.ample.com.");
+    assert_eq!(DomainName::from_unicode(".").unwrap().to_unicode(), ".");
+}
+
+#[test]
+fn test_unicode_names_invalid() {
+    for s in [
+        "-bücher.example",
+        "bücher-.example",
+        "bü cher",
+        "bü_cher",
+        "ab--ü",
+    ] {
+        assert_eq!(
+            DomainName::from_unicode(s),
+            Err(DomainNameError::InvalidIdna),
+            "{s}"
+        );
+    }
+    assert_eq!(
+        DomainName::from_unicode("bücher..example"),
+        Err(DomainNameError::EmptyLabel)
+    );
+    assert_eq!(
+        DomainName::from_unicode(&"ü".repeat(60)),
+        Err(DomainNameError::LabelTooLong(66))
+    );
+}
+
+#[test]
+fn test_binary_labels() {
+    // Labels that are not valid A-labels are kept as escaped bytes.
+    for s in [
+        r"\255\000x.example.",
+        "xn--.example.",
+        "xn--bcher-kva9.example.",
+        "xn--abc.example.",
+        r"\195\188.example.",
+    ] {
+        let n = name(s);
+        assert_eq!(n.to_unicode(), s, "{s}");
+        assert_eq!(DomainName::from_unicode(&n.to_unicode()).unwrap(), n);
+    }
+}
@@ -0,0 +1,107 @@
+use dns_starter_rust::{client::DnsClient, message::*};
+
+fn query_input() -> Vec<u8> {
+    // Query to:
+    // ;example.com.                      IN       A
+    // With:
+    // ; EDNS: version: 0, flags: do; udp: 4096
+    // ; COOKIE: 0102030405060708
+    let mut input = vec![0x12, 0x34, 1, 0, 0, 1, 0, 0, 0, 0, 0, 1];
+    input.extend(b"\x07example\x03com\x00\x00\x01\x00\x01");
+    input.extend(b"\x00\x00\x29\x10\x00\x00\x00\x80\x00\x00\x0c");
+    input.extend(b"\x00\x0a\x00\x08\x01\x02\x03\x04\x05\x06\x07\x08");
+    input
+}
+
+#[test]
+fn test_parse_edns() {
+    let input = query_input();
+
+    // Test parse
+    let (_, msg) = Message::parse(&input).unwrap();
+    assert_eq!(
+        msg.edns(),
+        Some(Edns {
+            udp_payload_size: 4096,
+            extended_rcode: 0,
+            version: 0,
+            dnssec_ok: true,
+            z: 0,
+            options: vec![EdnsOption::Cookie {
+                client: [1, 2, 3, 4, 5, 6, 7, 8],
+                server: vec![],
+            }],
+        })
+    );
+
+    // Test encode
+    let mut buf = Vec::with_capacity(input.len());
+    msg.encode(&mut buf).unwrap();
+    assert_eq!(buf, input);
+}
+
+#[test]
+fn test_edns_record() {
+    let edns = Edns {
+        udp_payload_size: 1232,
+        extended_rcode: 1,
+        version: 2,
+        dnssec_ok: true,
+        z: 3,
+        options: vec![],
+    };
+
+    let record = edns.to_record();
+    assert_eq!(
+        record,
+        AnswerSection {
+            name: DomainName::root(),
+            rr_type: ResourceRecordType::OPT,
+            rr_class: ResourceRecordClass::Unknown(1232),
+            ttl: 0x0102_8003,
+            data: RData::Opt(vec![]),
+        }
+    );
+    assert_eq!(Edns::from_record(&record), Some(edns));
+
+    let (_, msg) = Message::parse(&query_input()).unwrap();
+    assert_eq!(Edns::from_record(&msg.additionals[0]).unwrap().version, 0);
+}
+
+#[test]
+fn test_set_edns() {
+    let (_, mut msg) = Message::parse(&query_input()).unwrap();
+
+    // Replace
+    msg.set_edns(Some(Edns::new(512)));
+    assert_eq!(msg.additionals.len(), 1);
+    assert_eq!(msg.header.additional_resource_record_count, 1);
+    assert_eq!(msg.edns(), Some(Edns::new(512)));
+
+    // Remove
+    msg.set_edns(None);
+    assert!(msg.additionals.is_empty());
+    assert_eq!(msg.header.additional_resource_record_count, 0);
+    assert_eq!(msg.edns(), None);
+
+    let mut buf = Vec::new();
+    msg.encode(&mut buf).unwrap();
+    assert_eq!(buf.len(), 29);
+    assert_eq!(Message::parse(&buf).unwrap().1, msg);
+}
+
+#[test]
+fn test_client_payload_size() {
+    let mut client = DnsClient::connect("127.0.0.1:0", "127.0.0.1:53").unwrap();
+    assert_eq!(client.udp_payload_size(), Some(1232));
+
+    client.set_udp_payload_size(Some(4096));
+    assert_eq!(client.udp_payload_size(), Some(4096));
+
+    // Values below 512 are treated as 512.
+    client.set_udp_payload_size(Some(100));
+    assert_eq!(client.udp_payload_size(), Some(512));
+
+    client.set_udp_payload_size(None);
+    assert_eq!(client.udp_payload_size(), None);
+}
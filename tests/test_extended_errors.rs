@@ -0,0 +1,190 @@
+mod common;
+
+use std::{net::SocketAddr, thread, time::Duration};
+
+use dns_starter_rust::{client::DnsClient, message::*, DnsError, DnssecError};
+
+fn encode(option: &EdnsOption) -> Vec<u8> {
+    let mut buf = vec![];
+    option.encode(&mut buf).unwrap();
+    buf
+}
+
+#[test]
+fn test_extended_error_option() {
+    let error = ExtendedError::new(ExtendedErrorCode::DnssecBogus, "bad signature");
+    let option = EdnsOption::ExtendedError(error.clone());
+    let input = b"\x00\x0f\x00\x0f\x00\x06bad signature";
+    assert_eq!(encode(&option), input);
+    assert_eq!(EdnsOption::parse(input).unwrap().1, option);
+    assert_eq!(option.to_string(), "EDE: 6 (DNSSEC Bogus): (bad signature)");
+
+    // Without extra text
+    let option = EdnsOption::ExtendedError(ExtendedError::new(ExtendedErrorCode::Blocked, ""));
+    let input = b"\x00\x0f\x00\x02\x00\x0f";
+    assert_eq!(encode(&option), input);
+    assert_eq!(EdnsOption::parse(input).unwrap().1, option);
+    assert_eq!(option.to_string(), "EDE: 15 (Blocked)");
+
+    // Unassigned code
+    let input = b"\x00\x0f\x00\x03\x03\xe8x";
+    let (_, option) = EdnsOption::parse(input).unwrap();
+    assert_eq!(
+        option,
+        EdnsOption::ExtendedError(ExtendedError::new(ExtendedErrorCode::Unknown(1000), "x"))
+    );
+    assert_eq!(option.to_string(), "EDE: 1000: (x)");
+    assert_eq!(encode(&option), input);
+
+    // Malformed: too short, or text is not UTF-8
+    for data in [&b"\x00"[..], b"\x00\x06\xff"] {
+        let mut input = vec![0, 15, 0, data.len() as u8];
+        input.extend(data);
+        assert_eq!(
+            EdnsOption::parse(&input).unwrap().1,
+            EdnsOption::Unknown(15, data.to_vec())
+        );
+    }
+}
+
+#[test]
+fn test_extended_error_code() {
+    for value in 0..=24 {
+        let code = ExtendedErrorCode::from(value);
+        assert_ne!(code, ExtendedErrorCode::Unknown(value));
+        assert!(code.purpose().is_some());
+        assert_eq!(u16::from(code), value);
+    }
+    assert_eq!(ExtendedErrorCode::from(25), ExtendedErrorCode::Unknown(25));
+    assert_eq!(
+        ExtendedErrorCode::NetworkError.purpose(),
+        Some("Network Error")
+    );
+
+    let error = ExtendedError::from(&DnssecError::SignatureExpired);
+    assert_eq!(
+        error,
+        ExtendedError::new(ExtendedErrorCode::SignatureExpired, "signature expired")
+    );
+    assert_eq!(
+        ExtendedErrorCode::from(&DnssecError::UnsupportedAlgorithm(3)),
+        ExtendedErrorCode::UnsupportedDnskeyAlgorithm
+    );
+    assert_eq!(
+        ExtendedErrorCode::from(&DnssecError::BadSignature),
+        ExtendedErrorCode::DnssecBogus
+    );
+}
+
+#[test]
+fn test_message_extended_errors() {
+    let question = QuestionSection::new_a("example.com").unwrap();
+    let mut edns = Edns::new(1232);
+    let blocked = ExtendedError::new(ExtendedErrorCode::Blocked, "");
+    let filtered = ExtendedError::new(ExtendedErrorCode::Filtered, "parental control");
+    edns.options
+        .push(EdnsOption::ExtendedError(blocked.clone()));
+    edns.options.push(EdnsOption::Unknown(65001, vec![]));
+    edns.options
+        .push(EdnsOption::ExtendedError(filtered.clone()));
+    let response = MessageBuilder::reply_to(&DnsClient::new_query(&question))
+        .response_code(ResponseCode::Refused)
+        .edns(edns)
+        .build();
+
+    assert_eq!(response.extended_errors(), vec![blocked, filtered]);
+    let output = response.to_string();
+    assert!(output.contains("\n; EDE: 15 (Blocked)\n"), "{output}");
+    assert!(
+        output.contains("\n; EDE: 17 (Filtered): (parental control)\n"),
+        "{output}"
+    );
+
+    let mut plain = response.clone();
+    plain.set_edns(None);
+    assert_eq!(plain.extended_errors(), vec![]);
+}
+
+/// Serve SERVFAIL with an extended error for `fail.example.`, and answers
+/// for other names. Queries for `slow.example.` are not answered, and those
+/// for `late.example.` are answered after 200ms.
+fn serve() -> SocketAddr {
+    common::serve(|query, _, _| {
+        let name = query.questions[0].name.to_string();
+        let mut edns = Edns::new(1232);
+        let response = match name.as_str() {
+            "slow.example." => return None,
+            "late.example." => {
+                thread::sleep(Duration::from_millis(200));
+                MessageBuilder::reply_to(query)
+                    .answer(format!("{name} 60 IN A 192.0.2.1").parse().unwrap())
+            }
+            "fail.example." => {
+                let error = ExtendedError::new(ExtendedErrorCode::NoReachableAuthority, "");
+                edns.options.push(EdnsOption::ExtendedError(error));
+                MessageBuilder::reply_to(query).response_code(ResponseCode::ServerFail)
+            }
+            _ => MessageBuilder::reply_to(query)
+                .answer(format!("{name} 60 IN A 192.0.2.1").parse().unwrap()),
+        };
+        Some(response.edns(edns).build())
+    })
+}
+
+#[test]
+fn test_client_extended_errors() {
+    let addr = serve();
+
+    let mut client = DnsClient::connect("127.0.0.1:0", addr).unwrap();
+    assert!(client.extended_errors().is_empty());
+
+    let question = QuestionSection::new_a("fail.example").unwrap();
+    assert!(matches!(
+        client.query(&question),
+        Err(DnsError::ErrorResponse(ResponseCode::ServerFail))
+    ));
+    assert_eq!(
+        client.extended_errors(),
+        [ExtendedError::new(
+            ExtendedErrorCode::NoReachableAuthority,
+            ""
+        )]
+    );
+
+    // Cleared by the next response
+    let question = QuestionSection::new_a("www.example").unwrap();
+    client.query(&question).unwrap();
+    assert!(client.extended_errors().is_empty());
+}
+
+#[test]
+fn test_client_timeout() {
+    let addr = serve();
+
+    let mut client = DnsClient::connect("127.0.0.1:0", addr).unwrap();
+    assert_eq!(client.timeout().unwrap(), None);
+    client
+        .set_timeout(Some(Duration::from_millis(100)))
+        .unwrap();
+    assert_eq!(client.timeout().unwrap(), Some(Duration::from_millis(100)));
+
+    let question = QuestionSection::new_a("slow.example").unwrap();
+    assert!(matches!(client.query(&question), Err(DnsError::Timeout)));
+}
+
+#[test]
+fn test_client_late_response() {
+    let mut client = DnsClient::connect("127.0.0.1:0", serve()).unwrap();
+    client
+        .set_timeout(Some(Duration::from_millis(100)))
+        .unwrap();
+
+    let question = QuestionSection::new_a("late.example").unwrap();
+    assert!(matches!(client.query(&question), Err(DnsError::Timeout)));
+    assert_eq!(client.timeout().unwrap(), Some(Duration::from_millis(100)));
+
+    // The late response is discarded.
+    let question = QuestionSection::new_a("www.example").unwrap();
+    let answer = client.query(&question).unwrap();
+    assert_eq!(answer.name, question.name);
+}
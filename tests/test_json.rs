@@ -0,0 +1,171 @@
+use dns_starter_rust::{message::*, JsonError};
+
+fn referral_input() -> Vec<u8> {
+    // Reply to:
+    // ;example.com.                      IN       A
+    // With:
+    // example.com.          3600         IN       NS       ns1.example.com.
+    // ns1.example.com.      3600         IN       A        192.0.2.1
+    let mut input = vec![
+        0xab, 0xcd, 0x81, 0x00, 0, 1, 0, 0, 0, 1, 0, 1, 7, b'e', b'x', b'a', b'm', b'p', b'l',
+        b'e', 3, b'c', b'o', b'm', 0, 0, 1, 0, 1,
+    ];
+    // Authority
+    input.extend([0xc0, 12, 0, 2, 0, 1, 0, 0, 0x0e, 0x10, 0, 6]);
+    input.extend([3, b'n', b's', b'1', 0xc0, 12]);
+    // Additional
+    input.extend([
+        3, b'n', b's', b'1', 0xc0, 12, 0, 1, 0, 1, 0, 0, 0x0e, 0x10, 0, 4,
+    ]);
+    input.extend([192, 0, 2, 1]);
+    input
+}
+
+#[test]
+fn test_record_to_json() {
+    let record: AnswerSection = "example.com. 300 IN MX 10 mail.example.com."
+        .parse()
+        .unwrap();
+    assert_eq!(
+        record.to_json(),
+        concat!(
+            r#"{"NAME":"example.com.","TYPE":15,"TYPEname":"MX","CLASS":1,"CLASSname":"IN","#,
+            r#""TTL":300,"RDLENGTH":20,"#,
+            r#""RDATAHEX":"000A046D61696C076578616D706C6503636F6D00","#,
+            r#""rdataMX":"10 mail.example.com."}"#
+        )
+    );
+    assert_eq!(AnswerSection::from_json(&record.to_json()), Ok(record));
+}
+
+#[test]
+fn test_record_from_json() {
+    let expected: AnswerSection = "example.com. 60 IN TXT \"hello\"".parse().unwrap();
+
+    // Presentation format only, with type name.
+    let json =
+        r#"{ "NAME": "example.com.", "TYPEname": "TXT", "TTL": 60, "rdataTXT": "\"hello\"" }"#;
+    assert_eq!(AnswerSection::from_json(json), Ok(expected.clone()));
+
+    // Hexadecimal RDATA wins.
+    let json = r#"{"NAME":"example.com.","TYPE":16,"CLASS":1,"TTL":60,"RDATAHEX":"0568656c6c6f","rdataTXT":"ignored"}"#;
+    assert_eq!(AnswerSection::from_json(json), Ok(expected));
+
+    // Unknown types are only in hexadecimal form.
+    let record: AnswerSection = "example.com. 60 IN TYPE731 \\# 2 abcd".parse().unwrap();
+    assert!(!record.to_json().contains("rdata"));
+    assert_eq!(AnswerSection::from_json(&record.to_json()), Ok(record));
+}
+
+#[test]
+fn test_question_json() {
+    let question = QuestionSection::new_a("example.com").unwrap();
+    let json = question.to_json();
+    assert_eq!(
+        json,
+        r#"{"NAME":"example.com.","TYPE":1,"TYPEname":"A","CLASS":1,"CLASSname":"IN"}"#
+    );
+    assert_eq!(QuestionSection::from_json(&json), Ok(question));
+}
+
+#[test]
+fn test_header_json() {
+    let (_, msg) = Message::parse(&referral_input()).unwrap();
+    let json = msg.header.to_json();
+    assert_eq!(
+        json,
+        concat!(
+            r#"{"ID":43981,"QR":true,"Opcode":0,"AA":false,"TC":false,"RD":true,"RA":false,"#,
+            r#""Z":false,"AD":false,"CD":false,"RCODE":0,"QDCOUNT":1,"ANCOUNT":0,"NSCOUNT":1,"ARCOUNT":1}"#
+        )
+    );
+    assert_eq!(Header::from_json(&json), Ok(msg.header));
+
+    // RFC 8427 examples use integers for flags.
+    let header = Header::from_json(r#"{"ID": 1, "QR": 1, "RD": 0}"#).unwrap();
+    assert_eq!(header.flags.qr, QrFlag::Reply);
+    assert!(!header.flags.is_recursion_desired);
+
+    // Reserved bit
+    let mut header = msg.header;
+    header.flags.z = true;
+    let json = header.to_json();
+    assert!(json.contains(r#""Z":true"#), "{json}");
+    assert_eq!(Header::from_json(&json), Ok(header));
+}
+
+#[test]
+fn test_message_round_trip() {
+    let (_, msg) = Message::parse(&referral_input()).unwrap();
+    let json = msg.to_json();
+    assert!(json.contains(r#""authorityRRs":[{"NAME":"example.com.","TYPE":2,"#));
+    assert_eq!(Message::from_json(&json), Ok(msg));
+}
+
+#[test]
+fn test_message_octets() {
+    let input = referral_input();
+    let hex: String = input.iter().map(|x| format!("{x:02X}")).collect();
+    let json = format!(r#"{{"messageOctetsHEX": "{hex}"}}"#);
+
+    let (_, expected) = Message::parse(&input).unwrap();
+    assert_eq!(Message::from_json(&json), Ok(expected));
+
+    let json = format!(r#"{{"messageOctetsHEX": "{}"}}"#, &hex[..60]);
+    assert!(matches!(
+        Message::from_json(&json),
+        Err(JsonError::Message(_))
+    ));
+}
+
+#[test]
+fn test_json_errors() {
+    assert_eq!(Message::from_json(""), Err(JsonError::Syntax(0)));
+
+    // Deep nesting is rejected, without exhausting the stack.
+    let deep = "[".repeat(500_000);
+    assert_eq!(Message::from_json(&deep), Err(JsonError::Syntax(64)));
+    let nested = format!("{}{}", "[".repeat(64), "]".repeat(64));
+    assert_eq!(
+        Message::from_json(&nested),
+        Err(JsonError::InvalidMember("object"))
+    );
+    assert_eq!(
+        Message::from_json(r#"{"ID": 1,}"#),
+        Err(JsonError::Syntax(9))
+    );
+    assert_eq!(
+        Message::from_json(r#"{"ID": 1} x"#),
+        Err(JsonError::Syntax(10))
+    );
+    assert_eq!(
+        Message::from_json("[]"),
+        Err(JsonError::InvalidMember("object"))
+    );
+    assert_eq!(
+        Message::from_json("{}"),
+        Err(JsonError::MissingMember("ID"))
+    );
+    assert_eq!(
+        Message::from_json(r#"{"ID": 70000}"#),
+        Err(JsonError::InvalidMember("ID"))
+    );
+    assert_eq!(
+        AnswerSection::from_json(r#"{"NAME": "a.", "TYPE": 1, "TTL": 1}"#),
+        Err(JsonError::MissingMember("RDATAHEX"))
+    );
+}
+
+#[test]
+fn test_json_string_escapes() {
+    let json = r#"{"NAME":"café.\"q\".","TYPE":1,"CLASS":1}"#;
+    let question = QuestionSection::from_json(json).unwrap();
+    assert_eq!(
+        question.name.labels(),
+        &["café".as_bytes().to_vec(), b"\"q\"".to_vec()]
+    );
+    assert_eq!(
+        question.to_json(),
+        r#"{"NAME":"caf\\195\\169.\\\"q\\\".","TYPE":1,"TYPEname":"A","CLASS":1,"CLASSname":"IN"}"#
+    );
+}
@@ -0,0 +1,99 @@
+use std::net::Ipv4Addr;
+
+use dns_starter_rust::message::*;
+
+fn question() -> QuestionSection {
+    QuestionSection::new_a("example.com").unwrap()
+}
+
+fn record() -> AnswerSection {
+    AnswerSection {
+        name: "example.com".parse().unwrap(),
+        rr_type: ResourceRecordType::A,
+        rr_class: ResourceRecordClass::IN,
+        ttl: 60,
+        data: RData::A(Ipv4Addr::new(192, 0, 2, 1)),
+    }
+}
+
+#[test]
+fn test_build_query() {
+    let msg = MessageBuilder::query()
+        .id(1234)
+        .recursion_desired(true)
+        .question(question())
+        .build();
+
+    assert_eq!(msg.header.id, 1234);
+    assert_eq!(msg.header.flags.qr, QrFlag::Query);
+    assert_eq!(msg.header.flags.opcode, OpCode::Query);
+    assert!(msg.header.flags.is_recursion_desired);
+    assert_eq!(msg.header.question_count, 1);
+    assert_eq!(msg.questions, vec![question()]);
+}
+
+#[test]
+fn test_build_counts() {
+    let msg = MessageBuilder::response()
+        .answers([record(), record()])
+        .authority(record())
+        .additional(record())
+        .edns(Edns::new(1232))
+        .build();
+
+    assert_eq!(msg.header.question_count, 0);
+    assert_eq!(msg.header.answer_count, 2);
+    assert_eq!(msg.header.authority_resource_record_count, 1);
+    assert_eq!(msg.header.additional_resource_record_count, 2);
+}
+
+#[test]
+fn test_build_extended_response_code() {
+    let msg = MessageBuilder::response()
+        .response_code(ResponseCode::BadVersion)
+        .build();
+
+    assert_eq!(msg.response_code(), ResponseCode::BadVersion);
+    assert_eq!(msg.header.additional_resource_record_count, 1);
+}
+
+#[test]
+fn test_reply_to() {
+    let mut query = MessageBuilder::query()
+        .id(4321)
+        .opcode(OpCode::Status)
+        .recursion_desired(true)
+        .checking_disabled(true)
+        .question(question())
+        .build();
+    query.header.flags.is_authentic_data = true;
+
+    let reply = Message::reply_to(&query);
+    assert_eq!(reply.header.id, 4321);
+    assert_eq!(reply.header.flags.qr, QrFlag::Reply);
+    assert_eq!(reply.header.flags.opcode, OpCode::Status);
+    assert!(reply.header.flags.is_recursion_desired);
+    assert!(!reply.header.flags.is_checking_disabled);
+    assert!(!reply.header.flags.is_authentic_data);
+    assert_eq!(reply.header.question_count, 1);
+    assert_eq!(reply.questions, query.questions);
+    assert!(reply.answers.is_empty());
+}
+
+#[test]
+fn test_encode_derives_counts() {
+    let mut msg = MessageBuilder::response()
+        .question(question())
+        .answer(record())
+        .build();
+    msg.answers.push(record());
+    msg.header.question_count = 7;
+
+    let mut buf = vec![];
+    msg.encode(&mut buf).unwrap();
+    assert_eq!(&buf[4..12], &[0, 1, 0, 2, 0, 0, 0, 0]);
+
+    let (_, decoded) = Message::parse(&buf).unwrap();
+    msg.update_counts();
+    assert_eq!(decoded, msg);
+}
@@ -30,6 +30,9 @@ fn test_parse_header() {
                 is_truncation: true,
                 is_recursion_desired: true,
                 is_recursion_available: true,
+                z: false,
+                is_authentic_data: false,
+                is_checking_disabled: false,
                 response_code: ResponseCode::NoError,
             },
             question_count: 42,
@@ -57,6 +60,9 @@ fn test_parse_header_flags_qr() {
             is_truncation: false,
             is_recursion_desired: false,
             is_recursion_available: false,
+            z: false,
+            is_authentic_data: false,
+            is_checking_disabled: false,
             response_code: ResponseCode::NoError,
         }
     );
@@ -71,6 +77,9 @@ fn test_parse_header_flags_qr() {
             is_truncation: false,
             is_recursion_desired: false,
             is_recursion_available: false,
+            z: false,
+            is_authentic_data: false,
+            is_checking_disabled: false,
             response_code: ResponseCode::NoError,
         }
     );
@@ -88,6 +97,9 @@ fn test_parse_header_flags_opcode() {
             is_truncation: false,
             is_recursion_desired: false,
             is_recursion_available: false,
+            z: false,
+            is_authentic_data: false,
+            is_checking_disabled: false,
             response_code: ResponseCode::NoError,
         }
     );
@@ -102,6 +114,9 @@ fn test_parse_header_flags_opcode() {
             is_truncation: false,
             is_recursion_desired: false,
             is_recursion_available: false,
+            z: false,
+            is_authentic_data: false,
+            is_checking_disabled: false,
             response_code: ResponseCode::NoError,
         }
     );
@@ -119,6 +134,9 @@ fn test_parse_header_flags_bool() {
             is_truncation: false,
             is_recursion_desired: true,
             is_recursion_available: false,
+            z: false,
+            is_authentic_data: false,
+            is_checking_disabled: false,
             response_code: ResponseCode::NoError,
         }
     );
@@ -133,6 +151,9 @@ fn test_parse_header_flags_bool() {
             is_truncation: true,
             is_recursion_desired: false,
             is_recursion_available: true,
+            z: false,
+            is_authentic_data: false,
+            is_checking_disabled: false,
             response_code: ResponseCode::NoError,
         }
     );
@@ -150,7 +171,50 @@ fn test_parse_header_flags_response_code() {
             is_truncation: false,
             is_recursion_desired: false,
             is_recursion_available: false,
+            z: false,
+            is_authentic_data: false,
+            is_checking_disabled: false,
             response_code: ResponseCode::ServerFail,
         }
     );
 }
+
+#[test]
+fn test_parse_header_flags_dnssec() {
+    for (input, z, is_authentic_data, is_checking_disabled) in [
+        (0b0100_0000, true, false, false),
+        (0b0010_0000, false, true, false),
+        (0b0001_0000, false, false, true),
+        (0b0111_0000, true, true, true),
+    ] {
+        let (_, h) = HeaderFlags::parse(&[0b0000_0000, input]).unwrap();
+        assert_eq!(
+            h,
+            HeaderFlags {
+                qr: QrFlag::Query,
+                opcode: OpCode::Query,
+                is_authoritative_answer: false,
+                is_truncation: false,
+                is_recursion_desired: false,
+                is_recursion_available: false,
+                z,
+                is_authentic_data,
+                is_checking_disabled,
+                response_code: ResponseCode::NoError,
+            }
+        );
+
+        // Test encode
+        let header = Header {
+            id: 0,
+            flags: h,
+            question_count: 0,
+            answer_count: 0,
+            authority_resource_record_count: 0,
+            additional_resource_record_count: 0,
+        };
+        let mut buf = Vec::new();
+        header.encode(&mut buf).unwrap();
+        assert_eq!(buf[2..4], [0b0000_0000, input]);
+    }
+}
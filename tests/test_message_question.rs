@@ -9,7 +9,7 @@ fn test_parse_empty() {
     assert_eq!(
         q,
         QuestionSection {
-            labels: vec![],
+            name: DomainName::root(),
             rr_type: ResourceRecordType::A,
             rr_class: ResourceRecordClass::IN,
         }
@@ -30,7 +30,7 @@ fn test_parse_full() {
     assert_eq!(
         q,
         QuestionSection {
-            labels: vec!["google".to_string(), "com".to_string()],
+            name: "google.com".parse().unwrap(),
             rr_type: ResourceRecordType::MR,
             rr_class: ResourceRecordClass::CS,
         }
@@ -44,11 +44,11 @@ fn test_parse_full() {
 
 #[test]
 fn test_new() {
-    let q = QuestionSection::new_a("hello.world.com");
+    let q = QuestionSection::new_a("hello.world.com").unwrap();
     assert_eq!(
         q,
         QuestionSection {
-            labels: vec!["hello".to_string(), "world".to_string(), "com".to_string(),],
+            name: "hello.world.com".parse().unwrap(),
             rr_type: ResourceRecordType::A,
             rr_class: ResourceRecordClass::IN,
         }
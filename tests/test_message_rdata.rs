@@ -0,0 +1,133 @@
+use std::net::Ipv6Addr;
+
+use dns_starter_rust::message::*;
+
+fn name(url: &str) -> DomainName {
+    url.parse().unwrap()
+}
+
+fn assert_round_trip(rr_type: ResourceRecordType, input: &[u8], expected: RData) {
+    // Test parse
+    let (_, data) = RData::parse(input, rr_type, input).unwrap();
+    assert_eq!(data, expected);
+
+    // Test encode
+    let mut buf = Vec::with_capacity(input.len());
+    data.encode(&mut buf).unwrap();
+    assert_eq!(buf, input);
+}
+
+#[test]
+fn test_parse_aaaa() {
+    assert_round_trip(
+        ResourceRecordType::AAAA,
+        &[0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        RData::AAAA(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1)),
+    );
+}
+
+#[test]
+fn test_parse_mx() {
+    assert_round_trip(
+        ResourceRecordType::MX,
+        b"\x00\x0a\x04mail\x07example\x03com\x00",
+        RData::MX {
+            preference: 10,
+            exchange: name("mail.example.com"),
+        },
+    );
+}
+
+#[test]
+fn test_parse_soa() {
+    let mut input = b"\x03ns1\x07example\x03com\x00\x05admin\x07example\x03com\x00".to_vec();
+    input.extend(2024010101_u32.to_be_bytes());
+    input.extend(7200_u32.to_be_bytes());
+    input.extend(3600_u32.to_be_bytes());
+    input.extend(1209600_u32.to_be_bytes());
+    input.extend(300_u32.to_be_bytes());
+
+    assert_round_trip(
+        ResourceRecordType::SOA,
+        &input,
+        RData::SOA {
+            mname: name("ns1.example.com"),
+            rname: name("admin.example.com"),
+            serial: 2024010101,
+            refresh: 7200,
+            retry: 3600,
+            expire: 1209600,
+            minimum: 300,
+        },
+    );
+}
+
+#[test]
+fn test_parse_txt_srv_hinfo() {
+    assert_round_trip(
+        ResourceRecordType::TXT,
+        b"\x05hello\x00\x05world",
+        RData::TXT(vec![b"hello".to_vec(), vec![], b"world".to_vec()]),
+    );
+    assert_round_trip(
+        ResourceRecordType::SRV,
+        b"\x00\x01\x00\x02\x01\xbb\x03sip\x07example\x03com\x00",
+        RData::SRV {
+            priority: 1,
+            weight: 2,
+            port: 443,
+            target: name("sip.example.com"),
+        },
+    );
+    assert_round_trip(
+        ResourceRecordType::HINFO,
+        b"\x03x86\x05Linux",
+        RData::HINFO {
+            cpu: b"x86".to_vec(),
+            os: b"Linux".to_vec(),
+        },
+    );
+}
+
+#[test]
+fn test_parse_unknown() {
+    assert_round_trip(
+        ResourceRecordType::NULL,
+        b"\x01\x02\x03",
+        RData::Unknown(ResourceRecordType::NULL, vec![1, 2, 3]),
+    );
+}
+
+#[test]
+fn test_parse_bad_length() {
+    assert!(RData::parse(b"", ResourceRecordType::A, b"\x01\x02\x03").is_err());
+    assert!(RData::parse(b"", ResourceRecordType::A, b"\x01\x02\x03\x04\x05").is_err());
+}
+
+#[test]
+fn test_parse_compressed_name() {
+    // Reply to:
+    // ;example.com.                      IN       MX
+    // With:
+    // example.com.          300          IN       MX       10 mail.example.com.
+    let input = [
+        0x12, 0x34, 0x81, 0x80, 0, 1, 0, 1, 0, 0, 0, 0, 7, b'e', b'x', b'a', b'm', b'p', b'l',
+        b'e', 3, b'c', b'o', b'm', 0, 0, 15, 0, 1, 0xc0, 12, 0, 15, 0, 1, 0, 0, 1, 44, 0, 9, 0, 10,
+        4, b'm', b'a', b'i', b'l', 0xc0, 12,
+    ];
+
+    let (_, msg) = Message::parse(&input).unwrap();
+    assert_eq!(
+        msg.answers,
+        vec![AnswerSection {
+            name: name("example.com"),
+            rr_type: ResourceRecordType::MX,
+            rr_class: ResourceRecordClass::IN,
+            ttl: 300,
+            data: RData::MX {
+                preference: 10,
+                exchange: name("mail.example.com"),
+            },
+        }]
+    );
+}
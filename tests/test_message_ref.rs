@@ -0,0 +1,99 @@
+use std::net::Ipv4Addr;
+
+use dns_starter_rust::message::*;
+
+fn referral_input() -> Vec<u8> {
+    // Reply to:
+    // ;example.com.                      IN       A
+    // With:
+    // example.com.          3600         IN       NS       ns1.example.com.
+    // ns1.example.com.      3600         IN       A        192.0.2.1
+    let mut input = vec![0xab, 0xcd, 0x81, 0x00, 0, 1, 0, 0, 0, 1, 0, 1];
+    input.extend(b"\x07example\x03com\x00\x00\x01\x00\x01");
+    input.extend(b"\xc0\x0c\x00\x02\x00\x01\x00\x00\x0e\x10\x00\x06\x03ns1\xc0\x0c");
+    input.extend(b"\xc0\x29\x00\x01\x00\x01\x00\x00\x0e\x10\x00\x04\xc0\x00\x02\x01");
+    input
+}
+
+fn name(url: &str) -> DomainName {
+    url.parse().unwrap()
+}
+
+#[test]
+fn test_parse_lazy() {
+    let input = referral_input();
+    let msg = MessageRef::parse(&input).unwrap();
+    assert_eq!(msg.header().id, 0xabcd);
+    assert_eq!(msg.as_bytes(), input);
+
+    // Questions
+    let questions: Vec<_> = msg.questions().collect::<Result<_, _>>().unwrap();
+    assert_eq!(questions.len(), 1);
+    assert_eq!(questions[0].name, name("EXAMPLE.com"));
+    assert_eq!(questions[0].name.to_string(), "example.com.");
+    assert_eq!(questions[0].rr_type, ResourceRecordType::A);
+    assert_eq!(questions[0].rr_class, ResourceRecordClass::IN);
+
+    // Records
+    assert_eq!(msg.answers().count(), 0);
+
+    let authority = msg.authorities().next().unwrap().unwrap();
+    assert_eq!(authority.name, name("example.com"));
+    assert_eq!(authority.ttl, 3600);
+    assert_eq!(authority.rdata, b"\x03ns1\xc0\x0c");
+    assert_eq!(
+        authority.data().unwrap(),
+        RData::NS(name("ns1.example.com"))
+    );
+
+    let additional = msg.additionals().next().unwrap().unwrap();
+    assert_eq!(additional.name, name("ns1.example.com"));
+    assert_ne!(additional.name, name("example.com"));
+    assert_eq!(
+        additional.name.labels().collect::<Vec<_>>(),
+        [b"ns1".as_slice(), b"example", b"com"]
+    );
+    assert_eq!(
+        additional.data().unwrap(),
+        RData::A(Ipv4Addr::new(192, 0, 2, 1))
+    );
+}
+
+#[test]
+fn test_to_message() {
+    let input = referral_input();
+    let msg = MessageRef::parse(&input).unwrap();
+
+    let (_, expected) = Message::parse(&input).unwrap();
+    assert_eq!(msg.to_message().unwrap(), expected);
+    assert_eq!(
+        msg.additionals()
+            .next()
+            .unwrap()
+            .unwrap()
+            .to_record()
+            .unwrap(),
+        expected.additionals[0]
+    );
+}
+
+#[test]
+fn test_parse_errors() {
+    assert!(MessageRef::parse(&[0, 1, 2]).is_err());
+
+    // Truncated in authority section
+    let input = referral_input();
+    let msg = MessageRef::parse(&input[..40]).unwrap();
+    assert_eq!(msg.questions().count(), 1);
+
+    let mut authorities = msg.authorities();
+    assert!(authorities.next().unwrap().is_err());
+    assert!(authorities.next().is_none());
+
+    // Following sections report the error
+    let mut additionals = msg.additionals();
+    assert!(additionals.next().unwrap().is_err());
+    assert!(additionals.next().is_none());
+
+    assert!(msg.to_message().is_err());
+}
@@ -0,0 +1,72 @@
+use std::net::Ipv4Addr;
+
+use dns_starter_rust::message::*;
+
+fn referral_input() -> Vec<u8> {
+    // Reply to:
+    // ;example.com.                      IN       A
+    // With:
+    // example.com.          3600         IN       NS       ns1.example.com.
+    // ns1.example.com.      3600         IN       A        192.0.2.1
+    let mut input = vec![
+        0xab, 0xcd, 0x81, 0x00, 0, 1, 0, 0, 0, 1, 0, 1, 7, b'e', b'x', b'a', b'm', b'p', b'l',
+        b'e', 3, b'c', b'o', b'm', 0, 0, 1, 0, 1,
+    ];
+    // Authority
+    input.extend([0xc0, 12, 0, 2, 0, 1, 0, 0, 0x0e, 0x10, 0, 6]);
+    input.extend([3, b'n', b's', b'1', 0xc0, 12]);
+    // Additional
+    input.extend([
+        3, b'n', b's', b'1', 0xc0, 12, 0, 1, 0, 1, 0, 0, 0x0e, 0x10, 0, 4,
+    ]);
+    input.extend([192, 0, 2, 1]);
+    input
+}
+
+fn name(url: &str) -> DomainName {
+    url.parse().unwrap()
+}
+
+#[test]
+fn test_parse_authority_and_additional() {
+    let input = referral_input();
+
+    let (rem, msg) = Message::parse(&input).unwrap();
+    assert!(rem.is_empty());
+    assert!(msg.answers.is_empty());
+    assert_eq!(
+        msg.authorities,
+        vec![AnswerSection {
+            name: name("example.com"),
+            rr_type: ResourceRecordType::NS,
+            rr_class: ResourceRecordClass::IN,
+            ttl: 3600,
+            data: RData::NS(name("ns1.example.com")),
+        }]
+    );
+    assert_eq!(
+        msg.additionals,
+        vec![AnswerSection {
+            name: name("ns1.example.com"),
+            rr_type: ResourceRecordType::A,
+            rr_class: ResourceRecordClass::IN,
+            ttl: 3600,
+            data: RData::A(Ipv4Addr::new(192, 0, 2, 1)),
+        }]
+    );
+}
+
+#[test]
+fn test_encode_authority_and_additional() {
+    let input = referral_input();
+    let (_, msg) = Message::parse(&input).unwrap();
+
+    // Test encode
+    let mut buf = Vec::with_capacity(input.len());
+    msg.encode(&mut buf).unwrap();
+
+    // Test round trip
+    let (rem, decoded) = Message::parse(&buf).unwrap();
+    assert!(rem.is_empty());
+    assert_eq!(decoded, msg);
+}
@@ -0,0 +1,148 @@
+use dns_starter_rust::{message::*, MessageSection, ParseError, ParseErrorKind};
+
+fn referral_input() -> Vec<u8> {
+    // Reply to:
+    // ;example.com.                      IN       A
+    // With:
+    // example.com.          3600         IN       NS       ns1.example.com.
+    // ns1.example.com.      3600         IN       A        192.0.2.1
+    let mut input = vec![
+        0xab, 0xcd, 0x81, 0x00, 0, 1, 0, 0, 0, 1, 0, 1, 7, b'e', b'x', b'a', b'm', b'p', b'l',
+        b'e', 3, b'c', b'o', b'm', 0, 0, 1, 0, 1,
+    ];
+    // Authority
+    input.extend([0xc0, 12, 0, 2, 0, 1, 0, 0, 0x0e, 0x10, 0, 6]);
+    input.extend([3, b'n', b's', b'1', 0xc0, 12]);
+    // Additional
+    input.extend([
+        3, b'n', b's', b'1', 0xc0, 12, 0, 1, 0, 1, 0, 0, 0x0e, 0x10, 0, 4,
+    ]);
+    input.extend([192, 0, 2, 1]);
+    input
+}
+
+fn query_with_name(name: &[u8]) -> Vec<u8> {
+    let mut input = vec![0x12, 0x34, 1, 0, 0, 1, 0, 0, 0, 0, 0, 0];
+    input.extend(name);
+    input.extend([0, 1, 0, 1]);
+    input
+}
+
+fn parse_error(input: &[u8]) -> ParseError {
+    let err = Message::parse(input).unwrap_err();
+
+    // Lazy parser must report the same error.
+    let msg_ref = MessageRef::parse(input).and_then(|x| x.to_message());
+    assert_eq!(msg_ref.unwrap_err(), err);
+
+    err
+}
+
+#[test]
+fn test_truncated_header() {
+    let err = parse_error(&[0xab, 0xcd, 0x81]);
+    assert_eq!(err.section, MessageSection::Header);
+    assert_eq!(err.kind, ParseErrorKind::TruncatedHeader);
+}
+
+#[test]
+fn test_truncated_record() {
+    let input = referral_input();
+    assert_eq!(
+        parse_error(&input[..40]),
+        ParseError {
+            section: MessageSection::Authority,
+            index: 0,
+            offset: 39,
+            kind: ParseErrorKind::Truncated,
+        }
+    );
+}
+
+#[test]
+fn test_count_mismatch() {
+    let mut input = referral_input();
+    input[11] = 2;
+    assert_eq!(
+        parse_error(&input),
+        ParseError {
+            section: MessageSection::Additional,
+            index: 1,
+            offset: input.len(),
+            kind: ParseErrorKind::CountMismatch,
+        }
+    );
+}
+
+#[test]
+fn test_rdlength_overrun() {
+    let mut input = referral_input();
+    let rdlength_offset = input.len() - 6;
+    input[rdlength_offset + 1] = 5;
+    assert_eq!(
+        parse_error(&input),
+        ParseError {
+            section: MessageSection::Additional,
+            index: 0,
+            offset: rdlength_offset,
+            kind: ParseErrorKind::RdLengthOverrun,
+        }
+    );
+}
+
+#[test]
+fn test_bad_rdata() {
+    // A record with 3 bytes of data.
+    let mut input = referral_input();
+    let rdlength_offset = input.len() - 6;
+    input[rdlength_offset + 1] = 3;
+    input.pop();
+
+    let err = parse_error(&input);
+    assert_eq!(err.section, MessageSection::Additional);
+    assert_eq!(err.index, 0);
+    assert_eq!(err.kind, ParseErrorKind::BadRData);
+}
+
+#[test]
+fn test_bad_names() {
+    assert_eq!(
+        parse_error(&query_with_name(b"\x01a\xc0\x0c")),
+        ParseError {
+            section: MessageSection::Question,
+            index: 0,
+            offset: 14,
+            kind: ParseErrorKind::BadPointer,
+        }
+    );
+    assert_eq!(
+        parse_error(&query_with_name(b"\x01a\x40\x00")),
+        ParseError {
+            section: MessageSection::Question,
+            index: 0,
+            offset: 14,
+            kind: ParseErrorKind::BadLabelLength,
+        }
+    );
+
+    let mut name = vec![];
+    for _ in 0..5 {
+        name.push(63);
+        name.extend([b'a'; 63]);
+    }
+    name.push(0);
+    assert_eq!(
+        parse_error(&query_with_name(&name)).kind,
+        ParseErrorKind::NameTooLong
+    );
+}
+
+#[test]
+fn test_display() {
+    let input = referral_input();
+    let err = Message::parse(&input[..40]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "unexpected end of message in authority section (item 0) at offset 39"
+    );
+}
@@ -0,0 +1,144 @@
+use std::net::Ipv4Addr;
+
+use dns_starter_rust::{message::*, PresentationError};
+
+fn name(url: &str) -> DomainName {
+    url.parse().unwrap()
+}
+
+fn record(s: &str) -> AnswerSection {
+    s.parse().unwrap()
+}
+
+fn check_round_trip(s: &str) {
+    assert_eq!(record(s).to_string(), s);
+}
+
+#[test]
+fn test_type_and_class_mnemonics() {
+    assert_eq!(ResourceRecordType::MX.to_string(), "MX");
+    assert_eq!(ResourceRecordType::Unknown(731).to_string(), "TYPE731");
+    assert_eq!("aaaa".parse(), Ok(ResourceRecordType::AAAA));
+    assert_eq!("TYPE1".parse(), Ok(ResourceRecordType::A));
+    assert_eq!("TYPE731".parse(), Ok(ResourceRecordType::Unknown(731)));
+    assert!("FOO".parse::<ResourceRecordType>().is_err());
+    assert_eq!(ResourceRecordType::NSAPPTR.to_string(), "NSAP-PTR");
+    assert_eq!("nsap-ptr".parse(), Ok(ResourceRecordType::NSAPPTR));
+    assert!("NSAPPTR".parse::<ResourceRecordType>().is_err());
+
+    assert_eq!(ResourceRecordClass::CH.to_string(), "CH");
+    assert_eq!(ResourceRecordClass::Unknown(42).to_string(), "CLASS42");
+    assert_eq!("in".parse(), Ok(ResourceRecordClass::IN));
+    assert_eq!("CLASS42".parse(), Ok(ResourceRecordClass::Unknown(42)));
+}
+
+#[test]
+fn test_format_record() {
+    let mx = AnswerSection {
+        name: name("example.com"),
+        rr_type: ResourceRecordType::MX,
+        rr_class: ResourceRecordClass::IN,
+        ttl: 300,
+        data: RData::MX {
+            preference: 10,
+            exchange: name("mail.example.com"),
+        },
+    };
+    assert_eq!(
+        mx.to_string(),
+        "example.com. 300 IN MX 10 mail.example.com."
+    );
+    assert_eq!(record("example.com. 300 IN MX 10 mail.example.com."), mx);
+}
+
+#[test]
+fn test_round_trip() {
+    check_round_trip("example.com. 60 IN A 192.0.2.1");
+    check_round_trip("example.com. 60 IN AAAA 2001:db8::1");
+    check_round_trip("example.com. 60 IN NS ns1.example.com.");
+    check_round_trip("www.example.com. 60 IN CNAME example.com.");
+    check_round_trip("1.2.0.192.in-addr.arpa. 60 IN PTR example.com.");
+    check_round_trip(
+        "example.com. 3600 IN SOA ns1.example.com. admin.example.com. 2024010101 7200 3600 1209600 300",
+    );
+    check_round_trip("_sip._tcp.example.com. 60 IN SRV 10 20 5060 sip.example.com.");
+    check_round_trip("example.com. 60 IN HINFO \"x86\" \"Linux\"");
+    check_round_trip("example.com. 60 IN TXT \"v=spf1 -all\" \"say \\\"hi\\\"\" \"\\009\"");
+    check_round_trip("example.com. 60 CLASS42 TYPE731 \\# 3 abcdef");
+    check_round_trip("example.com. 60 IN TYPE731 \\# 0");
+}
+
+#[test]
+fn test_parse_record_variants() {
+    let expected = AnswerSection {
+        name: name("example.com"),
+        rr_type: ResourceRecordType::A,
+        rr_class: ResourceRecordClass::IN,
+        ttl: 60,
+        data: RData::A(Ipv4Addr::new(192, 0, 2, 1)),
+    };
+
+    // Class is optional and may come before TTL.
+    assert_eq!(record("example.com. 60 A 192.0.2.1"), expected);
+    assert_eq!(record("example.com. IN 60 A 192.0.2.1"), expected);
+    // Comments, parentheses and generic RDATA.
+    assert_eq!(
+        record("example.com. 60 IN A ( 192.0.2.1 ) ; comment"),
+        expected
+    );
+    assert_eq!(record("example.com. 60 IN A \\# 4 c0 00 02 01"), expected);
+
+    // Unquoted and escaped character strings.
+    assert_eq!(
+        record("example.com. 60 IN TXT hello\\032world").data,
+        RData::TXT(vec![b"hello world".to_vec()])
+    );
+}
+
+#[test]
+fn test_parse_record_errors() {
+    let parse = |s: &str| s.parse::<AnswerSection>().unwrap_err();
+
+    assert_eq!(
+        parse("example.com."),
+        PresentationError::MissingField("type")
+    );
+    assert_eq!(
+        parse("example.com. IN A 192.0.2.1"),
+        PresentationError::MissingField("TTL")
+    );
+    assert_eq!(
+        parse("example.com. 60 IN A 192.0.2"),
+        PresentationError::InvalidField("address", "192.0.2".to_string())
+    );
+    assert_eq!(
+        parse("example.com. 60 IN A 192.0.2.1 extra"),
+        PresentationError::TrailingData("extra".to_string())
+    );
+    assert_eq!(
+        parse("example.com. 60 IN TXT \"abc"),
+        PresentationError::UnterminatedQuote
+    );
+    assert_eq!(
+        parse("example.com. 60 IN A \\# 5 c0000201"),
+        PresentationError::InvalidField("RDATA", "c0000201".to_string())
+    );
+    assert_eq!(
+        parse("example.com. 60 IN TYPE731 abcd"),
+        PresentationError::GenericFormatRequired(ResourceRecordType::Unknown(731))
+    );
+}
+
+#[test]
+fn test_question() {
+    let question = QuestionSection::new_a("example.com").unwrap();
+    assert_eq!(question.to_string(), "example.com. IN A");
+    assert_eq!("example.com. IN A".parse(), Ok(question.clone()));
+    assert_eq!("example.com A".parse(), Ok(question));
+
+    let any: QuestionSection = "example.com. ANY".parse().unwrap();
+    assert_eq!(any.rr_class, ResourceRecordClass::IN);
+    assert_eq!(any.rr_type, ResourceRecordType::ANY);
+
+    assert!("example.com. 60 IN A".parse::<QuestionSection>().is_err());
+}
@@ -0,0 +1,54 @@
+use dns_starter_rust::message::*;
+
+#[test]
+fn test_type_round_trip() {
+    for (value, expected) in [
+        (0, ResourceRecordType::Invalid),
+        (1, ResourceRecordType::A),
+        (16, ResourceRecordType::TXT),
+        (28, ResourceRecordType::AAAA),
+        (33, ResourceRecordType::SRV),
+        (41, ResourceRecordType::OPT),
+        (65, ResourceRecordType::HTTPS),
+        (255, ResourceRecordType::ANY),
+        (257, ResourceRecordType::CAA),
+        (32769, ResourceRecordType::DLV),
+        (54, ResourceRecordType::Unknown(54)),
+        (65280, ResourceRecordType::Unknown(65280)),
+    ] {
+        let rr_type = ResourceRecordType::from(value);
+        assert_eq!(rr_type, expected);
+        assert_eq!(u16::from(rr_type), value);
+    }
+}
+
+#[test]
+fn test_class_round_trip() {
+    for (value, expected) in [
+        (0, ResourceRecordClass::Invalid),
+        (1, ResourceRecordClass::IN),
+        (3, ResourceRecordClass::CH),
+        (254, ResourceRecordClass::NONE),
+        (255, ResourceRecordClass::ANY),
+        (4096, ResourceRecordClass::Unknown(4096)),
+    ] {
+        let rr_class = ResourceRecordClass::from(value);
+        assert_eq!(rr_class, expected);
+        assert_eq!(u16::from(rr_class), value);
+    }
+}
+
+#[test]
+fn test_question_unknown_type() {
+    let input = b"\x07example\x03com\x00\xff\x00\x00\xfe";
+
+    // Test parse
+    let (_, (q, _)) = QuestionSection::parse(input).unwrap();
+    assert_eq!(q.rr_type, ResourceRecordType::Unknown(0xff00));
+    assert_eq!(q.rr_class, ResourceRecordClass::NONE);
+
+    // Test encode
+    let mut buf = Vec::with_capacity(input.len());
+    q.encode(&mut buf).unwrap();
+    assert_eq!(buf, input);
+}
@@ -0,0 +1,82 @@
+use dns_starter_rust::message::*;
+
+#[test]
+fn test_code_round_trip() {
+    for (value, expected) in [
+        (0, ResponseCode::NoError),
+        (3, ResponseCode::NonExistentDomain),
+        (5, ResponseCode::Refused),
+        (9, ResponseCode::NotAuthoritative),
+        (11, ResponseCode::DsoTypeNotImplemented),
+        (12, ResponseCode::Unknown(12)),
+        (15, ResponseCode::Unknown(15)),
+        (16, ResponseCode::BadVersion),
+        (22, ResponseCode::BadTruncation),
+        (23, ResponseCode::BadCookie),
+        (3841, ResponseCode::Unknown(3841)),
+    ] {
+        let code = ResponseCode::from(value);
+        assert_eq!(code, expected);
+        assert_eq!(u16::from(code), value);
+        assert_eq!(
+            ResponseCode::from_parts(code.header_bits(), code.extended_bits()),
+            code
+        );
+    }
+}
+
+#[test]
+fn test_header_response_code() {
+    let (_, h) = HeaderFlags::parse(&[0b1000_0000, 0b0000_0101]).unwrap();
+    assert_eq!(h.response_code, ResponseCode::Refused);
+
+    let (_, h) = HeaderFlags::parse(&[0b1000_0000, 0b0000_1111]).unwrap();
+    assert_eq!(h.response_code, ResponseCode::Unknown(15));
+
+    // Only lower 4 bits are written in header
+    let (_, mut header) = Header::parse(&[0; 12]).unwrap();
+    header.flags.response_code = ResponseCode::BadCookie;
+
+    let mut buf = Vec::new();
+    header.encode(&mut buf).unwrap();
+    assert_eq!(buf[3], 7);
+}
+
+#[test]
+fn test_message_extended_response_code() {
+    // Reply with:
+    // ; EDNS: version: 0, flags:; udp: 1232; ext rcode: 1
+    // ;; ->>HEADER<<- status: BADCOOKIE (7 + 1 << 4)
+    let input = [
+        0x12, 0x34, 0x80, 0x07, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 41, 0x04, 0xd0, 1, 0, 0, 0, 0, 0,
+    ];
+
+    let (_, mut msg) = Message::parse(&input).unwrap();
+    assert_eq!(msg.header.flags.response_code, ResponseCode::RrSetExists);
+    assert_eq!(msg.response_code(), ResponseCode::BadCookie);
+
+    msg.set_response_code(ResponseCode::Refused);
+    assert_eq!(msg.header.flags.response_code, ResponseCode::Refused);
+    assert_eq!(msg.edns().unwrap().extended_rcode, 0);
+    assert_eq!(msg.response_code(), ResponseCode::Refused);
+
+    // Test encode
+    msg.set_response_code(ResponseCode::BadCookie);
+    let mut buf = Vec::new();
+    msg.encode(&mut buf).unwrap();
+    assert_eq!(buf, input);
+}
+
+#[test]
+fn test_set_extended_response_code_without_edns() {
+    let (_, mut msg) = Message::parse(&[0x12, 0x34, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0]).unwrap();
+
+    msg.set_response_code(ResponseCode::ServerFail);
+    assert_eq!(msg.edns(), None);
+    assert_eq!(msg.response_code(), ResponseCode::ServerFail);
+
+    msg.set_response_code(ResponseCode::BadVersion);
+    assert_eq!(msg.header.flags.response_code, ResponseCode::NoError);
+    assert_eq!(msg.edns().unwrap().extended_rcode, 1);
+    assert_eq!(msg.response_code(), ResponseCode::BadVersion);
+}
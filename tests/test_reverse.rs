@@ -0,0 +1,119 @@
+mod common;
+
+use std::net::{IpAddr, SocketAddr};
+
+use dns_starter_rust::{client::DnsClient, message::*, DnsError};
+
+fn addr(s: &str) -> IpAddr {
+    s.parse().unwrap()
+}
+
+fn name(s: &str) -> DomainName {
+    s.parse().unwrap()
+}
+
+#[test]
+fn test_reverse_name() {
+    let n = DomainName::reverse(addr("192.0.2.10"));
+    assert_eq!(n.to_string(), "10.2.0.192.in-addr.arpa.");
+    assert_eq!(n.reverse_address(), Some((addr("192.0.2.10"), 32)));
+
+    // RFC 3596 section 2.5
+    let n = DomainName::reverse(addr("4321:0:1:2:3:4:567:89ab"));
+    assert_eq!(
+        n.to_string(),
+        "b.a.9.8.7.6.5.0.4.0.0.0.3.0.0.0.2.0.0.0.1.0.0.0.0.0.0.0.1.2.3.4.ip6.arpa."
+    );
+    assert_eq!(
+        n.reverse_address(),
+        Some((addr("4321:0:1:2:3:4:567:89ab"), 128))
+    );
+
+    let question = QuestionSection::new_ptr(addr("::1"));
+    assert_eq!(question.rr_type, ResourceRecordType::PTR);
+    assert_eq!(question.name.label_count(), 34);
+}
+
+#[test]
+fn test_reverse_address() {
+    // Networks
+    assert_eq!(
+        name("2.0.192.IN-ADDR.ARPA").reverse_address(),
+        Some((addr("192.0.2.0"), 24))
+    );
+    assert_eq!(
+        name("in-addr.arpa").reverse_address(),
+        Some((addr("0.0.0.0"), 0))
+    );
+    assert_eq!(
+        name("8.b.d.0.1.0.0.2.ip6.arpa").reverse_address(),
+        Some((addr("2001:db8::"), 32))
+    );
+    assert_eq!(
+        name("F.ip6.arpa").reverse_address(),
+        Some((addr("f000::"), 4))
+    );
+
+    for s in [
+        "example.com",
+        "arpa",
+        ".",
+        "1.2.3.4.5.in-addr.arpa",
+        "256.in-addr.arpa",
+        "01.in-addr.arpa",
+        "+1.in-addr.arpa",
+        "0/25.2.0.192.in-addr.arpa",
+        "10.ip6.arpa",
+        "g.ip6.arpa",
+        "2.0.192.in-addr.example",
+    ] {
+        assert_eq!(name(s).reverse_address(), None, "{s}");
+    }
+    let too_long = format!("{}ip6.arpa", "0.".repeat(33));
+    assert_eq!(name(&too_long).reverse_address(), None);
+}
+
+/// Serve PTR answers for 192.0.2.1 (two names), 192.0.2.130 (through a CNAME,
+/// as for classless delegation), and NXDOMAIN for other addresses.
+fn serve() -> SocketAddr {
+    common::serve(|query, _, _| {
+        let owner = query.questions[0].name.to_string();
+        let records: &[String] = match owner.as_str() {
+            "1.2.0.192.in-addr.arpa." => &[
+                format!("{owner} 60 PTR www.example."),
+                format!("{owner} 60 PTR mail.example."),
+            ],
+            "130.2.0.192.in-addr.arpa." => &[
+                format!("{owner} 60 CNAME 130.128/25.2.0.192.in-addr.arpa."),
+                "130.128/25.2.0.192.in-addr.arpa. 60 PTR host.example.".to_string(),
+            ],
+            _ => &[],
+        };
+        let mut response = MessageBuilder::reply_to(query);
+        for record in records {
+            response = response.answer(record.parse().unwrap());
+        }
+        if records.is_empty() {
+            response = response.response_code(ResponseCode::NonExistentDomain);
+        }
+        Some(response.build())
+    })
+}
+
+#[test]
+fn test_reverse_lookup() {
+    let mut client = DnsClient::connect("127.0.0.1:0", serve()).unwrap();
+
+    assert_eq!(
+        client.reverse_lookup(addr("192.0.2.1")).unwrap(),
+        [name("www.example"), name("mail.example")]
+    );
+    assert_eq!(
+        client.reverse_lookup(addr("192.0.2.130")).unwrap(),
+        [name("host.example")]
+    );
+    assert!(matches!(
+        client.reverse_lookup(addr("2001:db8::1")),
+        Err(DnsError::ErrorResponse(ResponseCode::NonExistentDomain))
+    ));
+}
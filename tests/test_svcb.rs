@@ -0,0 +1,275 @@
+use dns_starter_rust::{message::*, JsonError, PresentationError};
+
+fn encode(data: &RData) -> Vec<u8> {
+    let mut buf = vec![];
+    data.encode(&mut buf).unwrap();
+    buf
+}
+
+fn parse(rr_type: ResourceRecordType, input: &[u8]) -> Option<RData> {
+    RData::parse(input, rr_type, input).ok().map(|(_, x)| x)
+}
+
+/// Check presentation and wire formats of a record, and conversions between them.
+fn check(rr_type: ResourceRecordType, text: &str, input: &[u8]) -> RData {
+    let data = RData::from_presentation(rr_type, text).unwrap();
+    assert_eq!(encode(&data), input, "{text}");
+    assert_eq!(parse(rr_type, input).unwrap(), data);
+    assert_eq!(data.to_string(), text);
+    data
+}
+
+#[test]
+fn test_svcb_test_vectors() {
+    // RFC 9460 appendix D
+    let target = b"\x03foo\x07example\x03com\x00";
+    let with_target = |prefix: &[u8], params: &[u8]| [prefix, &target[..], params].concat();
+
+    let data = check(
+        ResourceRecordType::HTTPS,
+        "0 foo.example.com.",
+        &with_target(b"\x00\x00", b""),
+    );
+    assert_eq!(
+        data,
+        RData::HTTPS {
+            priority: 0,
+            target: "foo.example.com".parse().unwrap(),
+            params: vec![],
+        }
+    );
+    check(ResourceRecordType::SVCB, "1 .", b"\x00\x01\x00");
+
+    let data = check(
+        ResourceRecordType::SVCB,
+        "16 foo.example.com. port=53",
+        &with_target(b"\x00\x10", b"\x00\x03\x00\x02\x00\x35"),
+    );
+    assert!(matches!(data, RData::SVCB { ref params, .. } if params == &[SvcParam::Port(53)]));
+
+    check(
+        ResourceRecordType::SVCB,
+        "1 foo.example.com. key667=\"hello\"",
+        &with_target(b"\x00\x01", b"\x02\x9b\x00\x05hello"),
+    );
+    check(
+        ResourceRecordType::SVCB,
+        "1 foo.example.com. key667=\"hello\\210qoo\"",
+        &with_target(b"\x00\x01", b"\x02\x9b\x00\x09hello\xd2qoo"),
+    );
+
+    let mut params = b"\x00\x06\x00\x20".to_vec();
+    params.extend(b"\x20\x01\x0d\xb8\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x01");
+    params.extend(b"\x20\x01\x0d\xb8\x00\x00\x00\x00\x00\x00\x00\x00\x00\x53\x00\x01");
+    check(
+        ResourceRecordType::SVCB,
+        "1 foo.example.com. ipv6hint=2001:db8::1,2001:db8::53:1",
+        &with_target(b"\x00\x01", &params),
+    );
+    check(
+        ResourceRecordType::SVCB,
+        "1 example.com. ipv6hint=2001:db8:122:344::c000:221",
+        &[
+            &b"\x00\x01\x07example\x03com\x00\x00\x06\x00\x10"[..],
+            b"\x20\x01\x0d\xb8\x01\x22\x03\x44\x00\x00\x00\x00\xc0\x00\x02\x21",
+        ]
+        .concat(),
+    );
+}
+
+#[test]
+fn test_svcb_canonical_order() {
+    // RFC 9460 appendix D: keys are sorted, in any order in presentation format.
+    let input = [
+        &b"\x00\x10\x03foo\x07example\x03org\x00"[..],
+        b"\x00\x00\x00\x04\x00\x01\x00\x04",
+        b"\x00\x01\x00\x09\x02h2\x05h3-19",
+        b"\x00\x04\x00\x04\xc0\x00\x02\x01",
+    ]
+    .concat();
+    let data = RData::from_presentation(
+        ResourceRecordType::SVCB,
+        "16 foo.example.org. ( alpn=h2,h3-19 mandatory=ipv4hint,alpn\n ipv4hint=192.0.2.1 )",
+    )
+    .unwrap();
+    assert_eq!(encode(&data), input);
+    assert_eq!(
+        data.to_string(),
+        "16 foo.example.org. mandatory=alpn,ipv4hint alpn=\"h2,h3-19\" ipv4hint=192.0.2.1"
+    );
+
+    // Built out of order
+    let data = RData::SVCB {
+        priority: 16,
+        target: "foo.example.org".parse().unwrap(),
+        params: vec![
+            SvcParam::Ipv4Hint(vec!["192.0.2.1".parse().unwrap()]),
+            SvcParam::Alpn(vec![b"h2".to_vec(), b"h3-19".to_vec()]),
+            SvcParam::Mandatory(vec![SvcParam::IPV4HINT, SvcParam::ALPN]),
+        ],
+    };
+    assert_eq!(encode(&data), input);
+
+    // Unsorted keys on the wire are malformed.
+    let mut swapped = input[..21].to_vec();
+    swapped.extend(&input[21 + 8..34 + 8]);
+    swapped.extend(&input[21..21 + 8]);
+    swapped.extend(&input[34 + 8..]);
+    assert_eq!(swapped.len(), input.len());
+    assert_eq!(parse(ResourceRecordType::SVCB, &swapped), None);
+}
+
+#[test]
+fn test_svcb_escaped_values() {
+    // RFC 9460 appendix D: `\,` and `\\` escapes within alpn items
+    let input = [
+        &b"\x00\x10\x03foo\x07example\x03org\x00"[..],
+        b"\x00\x01\x00\x0c\x08f\\oo,bar\x02h2",
+    ]
+    .concat();
+    let data = check(
+        ResourceRecordType::SVCB,
+        "16 foo.example.org. alpn=\"f\\\\\\\\oo\\\\,bar,h2\"",
+        &input,
+    );
+    let alt = RData::from_presentation(
+        ResourceRecordType::SVCB,
+        "16 foo.example.org. alpn=f\\\\\\092oo\\092,bar,h2",
+    )
+    .unwrap();
+    assert_eq!(alt, data);
+}
+
+#[test]
+fn test_https_params() {
+    let data = RData::from_presentation(
+        ResourceRecordType::HTTPS,
+        "1 . alpn=h3 no-default-alpn port=8443 ech=AEX+DQBB ipv4hint=192.0.2.1,192.0.2.2 key65000",
+    )
+    .unwrap();
+    let RData::HTTPS { ref params, .. } = data else {
+        panic!("{data:?}");
+    };
+    assert_eq!(
+        params,
+        &[
+            SvcParam::Alpn(vec![b"h3".to_vec()]),
+            SvcParam::NoDefaultAlpn,
+            SvcParam::Port(8443),
+            SvcParam::Ipv4Hint(vec![
+                "192.0.2.1".parse().unwrap(),
+                "192.0.2.2".parse().unwrap()
+            ]),
+            SvcParam::Ech(vec![0x00, 0x45, 0xfe, 0x0d, 0x00, 0x41]),
+            SvcParam::Unknown(65000, vec![]),
+        ]
+    );
+    assert_eq!(params[2].key(), SvcParam::PORT);
+    assert_eq!(
+        data.to_string(),
+        "1 . alpn=\"h3\" no-default-alpn port=8443 ipv4hint=192.0.2.1,192.0.2.2 ech=AEX+DQBB key65000"
+    );
+
+    let input = encode(&data);
+    assert_eq!(parse(ResourceRecordType::HTTPS, &input).unwrap(), data);
+
+    // Full record, and generic format
+    let record: AnswerSection = "example.com. 300 IN HTTPS 1 . alpn=h2".parse().unwrap();
+    assert_eq!(record.rr_type, ResourceRecordType::HTTPS);
+    assert_eq!(
+        record.to_string(),
+        "example.com. 300 IN HTTPS 1 . alpn=\"h2\""
+    );
+    let generic = RData::from_presentation(
+        ResourceRecordType::HTTPS,
+        "\\# 10 0001 00 0001 0003 02 6832",
+    )
+    .unwrap();
+    assert_eq!(generic, record.data);
+}
+
+#[test]
+fn test_svcb_presentation_errors() {
+    // RFC 9460 appendix D.3, and malformed values
+    for text in [
+        "1 foo.example.com. key123=abc key123=def",
+        "1 foo.example.com. mandatory",
+        "1 foo.example.com. alpn",
+        "1 foo.example.com. port",
+        "1 foo.example.com. port=",
+        "1 foo.example.com. port=65536",
+        "1 foo.example.com. ipv4hint",
+        "1 foo.example.com. ipv4hint=2001:db8::1",
+        "1 foo.example.com. ipv6hint=192.0.2.1",
+        "1 foo.example.com. no-default-alpn=abc",
+        "1 foo.example.com. mandatory=key123",
+        "1 foo.example.com. mandatory=mandatory",
+        "1 foo.example.com. ipv6hint=::1 mandatory=ipv6hint,ipv6hint",
+        "1 foo.example.com. alpn=h2,,h3",
+        "1 foo.example.com. ech=!",
+        "1 foo.example.com. key65535",
+        "1 foo.example.com. unknown=1",
+    ] {
+        assert!(
+            matches!(
+                RData::from_presentation(ResourceRecordType::SVCB, text),
+                Err(PresentationError::InvalidField("SvcParam", _))
+            ),
+            "{text}"
+        );
+    }
+    assert_eq!(
+        RData::from_presentation(ResourceRecordType::SVCB, "1"),
+        Err(PresentationError::MissingField("target"))
+    );
+}
+
+#[test]
+fn test_malformed_svcb() {
+    for params in [
+        // Duplicate key
+        &b"\x00\x03\x00\x02\x00\x35\x00\x03\x00\x02\x00\x35"[..],
+        // Invalid lengths
+        b"\x00\x03\x00\x01\x00",
+        b"\x00\x04\x00\x00",
+        b"\x00\x04\x00\x05\xc0\x00\x02\x01\x00",
+        b"\x00\x06\x00\x04\x20\x01\x0d\xb8",
+        b"\x00\x02\x00\x01\x00",
+        b"\x00\x00\x00\x00",
+        b"\x00\x00\x00\x01\x00",
+        // Empty alpn item, truncated alpn item
+        b"\x00\x01\x00\x03\x02h2\x00",
+        b"\x00\x01\x00\x02\x02h",
+        // Mandatory key missing, or mandatory itself
+        b"\x00\x00\x00\x02\x00\x03",
+        b"\x00\x00\x00\x02\x00\x00",
+        // Truncated
+        b"\x00\x03\x00\x02\x00",
+        b"\x00",
+    ] {
+        let input = [&b"\x00\x01\x00"[..], params].concat();
+        assert_eq!(parse(ResourceRecordType::SVCB, &input), None, "{params:?}");
+    }
+}
+
+#[test]
+fn test_alpn_too_long() {
+    // ALPN ids are at most 255 bytes long (RFC 9460 section 7.1.1).
+    let text = format!("1 . alpn={}", "a".repeat(0x100));
+    assert!(matches!(
+        RData::from_presentation(ResourceRecordType::HTTPS, &text),
+        Err(PresentationError::InvalidField("SvcParam", _))
+    ));
+    let json =
+        format!(r#"{{"NAME":"example.com.","TYPEname":"HTTPS","TTL":60,"rdataHTTPS":"{text}"}}"#);
+    assert!(matches!(
+        AnswerSection::from_json(&json),
+        Err(JsonError::Presentation(PresentationError::InvalidField(
+            "SvcParam",
+            _
+        )))
+    ));
+
+    let text = format!("1 . alpn={}", "a".repeat(0xFF));
+    assert!(RData::from_presentation(ResourceRecordType::HTTPS, &text).is_ok());
+}
@@ -0,0 +1,129 @@
+use dns_starter_rust::{message::*, tsig};
+
+fn record(s: &str) -> AnswerSection {
+    s.parse().unwrap()
+}
+
+fn encoded_len(msg: &Message) -> usize {
+    let mut buf = vec![];
+    msg.encode(&mut buf).unwrap();
+    buf.len()
+}
+
+fn response() -> Message {
+    let query = MessageBuilder::query()
+        .id(1)
+        .question(QuestionSection::new_a("example.com").unwrap())
+        .build();
+
+    MessageBuilder::reply_to(&query)
+        .answers((1..=4).map(|x| record(&format!("example.com. 60 IN A 192.0.2.{x}"))))
+        .answer(record("example.com. 60 IN TXT \"first\""))
+        .authority(record("example.com. 60 IN NS ns1.example.com."))
+        .authority(record("example.com. 60 IN NS ns2.example.com."))
+        .additional(record("ns1.example.com. 60 IN A 192.0.2.53"))
+        .additional(record("ns2.example.com. 60 IN A 192.0.2.54"))
+        .edns(Edns::new(1232))
+        .build()
+}
+
+#[test]
+fn test_fitting_message_is_unchanged() {
+    let msg = response();
+    let mut buf = vec![];
+    msg.encode_with_limit(&mut buf, 512).unwrap();
+
+    let (_, decoded) = Message::parse(&buf).unwrap();
+    assert_eq!(decoded, msg);
+}
+
+#[test]
+fn test_drop_additional_first() {
+    let mut msg = response();
+    let full_len = encoded_len(&msg);
+    msg.truncate(full_len - 1);
+
+    // Last glue record is dropped, OPT record is kept, TC is not needed.
+    assert_eq!(msg.additionals.len(), 2);
+    assert_eq!(msg.additionals[0].name, "ns1.example.com".parse().unwrap());
+    assert!(msg.edns().is_some());
+    assert!(!msg.header.flags.is_truncation);
+    assert_eq!(msg.header.additional_resource_record_count, 2);
+}
+
+#[test]
+fn test_drop_whole_rrsets() {
+    let mut msg = response();
+    msg.truncate(120);
+
+    // Authority NS RRset and TXT RRset are gone, A RRset fits and is kept whole.
+    assert!(msg.header.flags.is_truncation);
+    assert!(msg.authorities.is_empty());
+    assert!(msg
+        .answers
+        .iter()
+        .all(|x| x.rr_type == ResourceRecordType::A));
+    assert_eq!(msg.answers.len(), 4);
+    assert!(encoded_len(&msg) <= 120);
+    assert_eq!(msg.header.answer_count, 4);
+    assert_eq!(msg.header.authority_resource_record_count, 0);
+    assert_eq!(msg.header.additional_resource_record_count, 1);
+}
+
+#[test]
+fn test_encode_with_limit() {
+    let msg = response();
+    let mut buf = vec![];
+    msg.encode_with_limit(&mut buf, 60).unwrap();
+    assert!(buf.len() <= 60);
+
+    let (_, decoded) = Message::parse(&buf).unwrap();
+    assert!(decoded.header.flags.is_truncation);
+    assert_eq!(decoded.questions, msg.questions);
+    assert!(decoded.answers.is_empty());
+    assert!(decoded.authorities.is_empty());
+    assert_eq!(decoded.edns(), msg.edns());
+}
+
+#[test]
+fn test_keep_signature() {
+    let key = "test-key:MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY="
+        .parse()
+        .unwrap();
+    let mut msg = response();
+    msg.additionals
+        .push(record("example.com. 0 ANY SIG \\# 2 0000"));
+    tsig::sign(&mut msg, &key, 1_700_000_000, None);
+    msg.truncate(140);
+    assert!(encoded_len(&msg) <= 140);
+
+    // Glue is dropped, OPT and signatures are kept.
+    let types: Vec<_> = msg.additionals.iter().map(|x| x.rr_type).collect();
+    assert_eq!(
+        types,
+        [
+            ResourceRecordType::OPT,
+            ResourceRecordType::SIG,
+            ResourceRecordType::TSIG
+        ]
+    );
+    assert!(msg.header.flags.is_truncation);
+}
+
+#[test]
+fn test_truncate_kept_records() {
+    // Only the header and question fit: OPT is dropped too.
+    let mut msg = response();
+    msg.truncate(30);
+    assert!(msg.header.flags.is_truncation);
+    assert!(msg.additionals.is_empty());
+    assert_eq!(msg.header.additional_resource_record_count, 0);
+    assert_eq!(msg.questions.len(), 1);
+    assert!(encoded_len(&msg) <= 30);
+
+    // Questions do not fit.
+    let mut buf = vec![];
+    let e = response().encode_with_limit(&mut buf, 20).unwrap_err();
+    assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(buf.is_empty());
+}
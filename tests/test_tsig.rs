@@ -0,0 +1,407 @@
+mod common;
+
+use std::net::SocketAddr;
+
+use dns_starter_rust::{
+    client::DnsClient,
+    message::*,
+    tsig::{self, KeyStore, TsigAlgorithm, TsigKey},
+    DnsError, TsigError,
+};
+
+const NOW: u64 = 1_700_000_000;
+
+/// Base64 of `0123456789abcdef0123456789abcdef`.
+const SECRET: &str = "MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY=";
+
+fn key(algorithm: &str) -> TsigKey {
+    format!("{algorithm}:test-key:{SECRET}").parse().unwrap()
+}
+
+fn hex(data: &[u8]) -> String {
+    data.iter().map(|x| format!("{x:02x}")).collect()
+}
+
+fn query() -> Message {
+    MessageBuilder::query()
+        .id(0x1234)
+        .recursion_desired(true)
+        .question(QuestionSection::new_a("example.com").unwrap())
+        .build()
+}
+
+fn encode(msg: &Message) -> Vec<u8> {
+    let mut buf = vec![];
+    msg.encode(&mut buf).unwrap();
+    buf
+}
+
+fn signed_query(key: &TsigKey, now: u64) -> (Message, Vec<u8>) {
+    let mut msg = query();
+    let mac = tsig::sign(&mut msg, key, now, None);
+    (msg, mac)
+}
+
+fn tsig_data(msg: &Message) -> &RData {
+    let last = msg.additionals.last().unwrap();
+    assert_eq!(last.rr_type, ResourceRecordType::TSIG);
+    &last.data
+}
+
+#[test]
+fn test_tsig_record() {
+    let (msg, mac) = signed_query(&key("hmac-sha256"), NOW);
+    let record = msg.additionals.last().unwrap();
+    assert_eq!(record.name, "test-key".parse().unwrap());
+    assert_eq!(record.rr_class, ResourceRecordClass::ANY);
+    assert_eq!(record.ttl, 0);
+    assert_eq!(
+        record.data,
+        RData::TSIG {
+            algorithm: "hmac-sha256".parse().unwrap(),
+            time_signed: NOW,
+            fudge: 300,
+            mac: mac.clone(),
+            original_id: 0x1234,
+            error: ResponseCode::NoError,
+            other_data: vec![],
+        }
+    );
+    assert_eq!(msg.header.additional_resource_record_count, 1);
+
+    // Wire round trip
+    let buf = encode(&msg);
+    let (_, decoded) = Message::parse(&buf).unwrap();
+    assert_eq!(decoded, msg);
+
+    assert_eq!(
+        record.to_string(),
+        "test-key. 0 ANY TSIG hmac-sha256. 1700000000 300 32 \
+         xdnfpA3Ubp4NdcHqK/V0E/ctzf4kFvDoJdY8wex8h+w= 4660 NOERROR 0"
+    );
+}
+
+#[test]
+fn test_sign_known_answers() {
+    // MACs computed with Python hmac module.
+    let long_secret = "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8gISIjJCUmJygpKissLS4vMDEyMzQ1Njc4OTo7PD0+P0BBQkNERUZHSElKS0xNTk9QUVJTVFVWV1hZWltcXV5fYGFiYwABAgMEBQYHCAkKCwwNDg8QERITFBUWFxgZGhscHR4fICEiIyQlJicoKSorLC0uLzAxMjM0NTY3ODk6Ozw9Pj9AQUJDREVGR0hJSktMTU5PUFFSU1RVVldYWVpbXF1eX2BhYmM=";
+    for (key, expected) in [
+        (
+            key("hmac-sha256"),
+            "c5d9dfa40dd46e9e0d75c1ea2bf57413f72dcdfe2416f0e825d63cc1ec7c87ec",
+        ),
+        (
+            key("hmac-sha512"),
+            "ce6c9c41ca1f1bcd1f8088a30a5dacb6c9435abcacf70c9ba163cfbef45edb4db91716184409349dc31715cf27f71b080771118e9dae2e75f51fc31992ebe93e",
+        ),
+        // Secrets longer than a hash block are hashed first.
+        (
+            format!("test-key:{long_secret}").parse().unwrap(),
+            "1ff0cf0fc9b9164ed8d123bc415dc8800b74871a24001613e33a219f5baee9d9",
+        ),
+        (
+            format!("hmac-sha512:test-key:{long_secret}").parse().unwrap(),
+            "17a6b00d564325d3dba00f9074d58ad4d8e91edcf4b5f2fc49da0569f8bd7d0e026a3cbf31c83cd909f2c78ae73438573ad54f7de4ceab2de9f2cab1018bc260",
+        ),
+    ] {
+        let (_, mac) = signed_query(&key, NOW);
+        assert_eq!(hex(&mac), expected);
+    }
+}
+
+#[test]
+fn test_verify_request_and_response() {
+    for algorithm in ["hmac-sha256", "hmac-sha512"] {
+        let key = key(algorithm);
+        let keys = KeyStore::from_iter([key.clone()]);
+        let (request, request_mac) = signed_query(&key, NOW);
+
+        let verified = keys.verify(&encode(&request), NOW + 10).unwrap().unwrap();
+        assert_eq!(verified.key, key);
+        assert_eq!(verified.mac, request_mac);
+        assert_eq!(verified.time_signed, NOW);
+
+        // Response covers the request MAC.
+        let mut response = MessageBuilder::reply_to(&request)
+            .answer("example.com. 60 IN A 192.0.2.1".parse().unwrap())
+            .edns(Edns::new(1232))
+            .build();
+        verified.sign_response(&mut response, NOW + 20);
+        let buf = encode(&response);
+        assert!(tsig::verify(&buf, &key, NOW + 30, Some(&request_mac)).is_ok());
+        assert_eq!(
+            tsig::verify(&buf, &key, NOW + 30, None),
+            Err(TsigError::BadSig)
+        );
+
+        // TSIG record comes after OPT record.
+        let (_, decoded) = Message::parse(&buf).unwrap();
+        assert_eq!(decoded.additionals.len(), 2);
+        assert!(decoded.edns().is_some());
+    }
+
+    // Unsigned messages
+    let keys = KeyStore::from_iter([key("hmac-sha256")]);
+    assert_eq!(keys.verify(&encode(&query()), NOW), Ok(None));
+    assert_eq!(
+        tsig::verify(&encode(&query()), &key("hmac-sha256"), NOW, None),
+        Err(TsigError::Unsigned)
+    );
+}
+
+#[test]
+fn test_verify_with_compressed_names() {
+    // Signature covers the message as sent, compressed names included.
+    let key = key("hmac-sha256");
+    let mut msg = MessageBuilder::reply_to(&query())
+        .answers((1..=3).map(|x| {
+            format!("www.example.com. 60 IN CNAME host{x}.example.com.")
+                .parse()
+                .unwrap()
+        }))
+        .build();
+    tsig::sign(&mut msg, &key, NOW, None);
+
+    let buf = encode(&msg);
+    let uncompressed = {
+        let mut encoder = Encoder::without_compression();
+        msg.encode_into(&mut encoder).unwrap();
+        encoder.into_bytes()
+    };
+    assert!(buf.len() < uncompressed.len());
+    assert!(tsig::verify(&buf, &key, NOW, None).is_ok());
+    assert_eq!(
+        tsig::verify(&uncompressed, &key, NOW, None),
+        Err(TsigError::BadSig)
+    );
+}
+
+#[test]
+fn test_verify_errors() {
+    let key = key("hmac-sha256");
+    let keys = KeyStore::from_iter([key.clone()]);
+    let (request, _) = signed_query(&key, NOW);
+    let buf = encode(&request);
+
+    // Unknown key and algorithm
+    assert_eq!(KeyStore::new().verify(&buf, NOW), Err(TsigError::BadKey));
+    assert_eq!(
+        tsig::verify(&buf, &self::key("hmac-sha512"), NOW, None),
+        Err(TsigError::BadKey)
+    );
+
+    // Tampered message and wrong secret
+    let mut tampered = buf.clone();
+    tampered[3] ^= 0x10;
+    assert_eq!(keys.verify(&tampered, NOW), Err(TsigError::BadSig));
+    let other = TsigKey::new(
+        "test-key".parse().unwrap(),
+        TsigAlgorithm::HmacSha256,
+        b"other secret".to_vec(),
+    );
+    assert_eq!(
+        tsig::verify(&buf, &other, NOW, None),
+        Err(TsigError::BadSig)
+    );
+
+    // Time out of fudge window
+    assert!(keys.verify(&buf, NOW - 300).is_ok());
+    assert_eq!(keys.verify(&buf, NOW + 301), Err(TsigError::BadTime));
+    assert_eq!(keys.verify(&buf, NOW - 301), Err(TsigError::BadTime));
+
+    // TSIG record must be the last one.
+    let mut misplaced = request.clone();
+    misplaced
+        .additionals
+        .push("example.com. 60 IN A 192.0.2.1".parse().unwrap());
+    assert_eq!(
+        keys.verify(&encode(&misplaced), NOW),
+        Err(TsigError::Malformed)
+    );
+    let mut in_answers = request.clone();
+    in_answers.answers.push(request.additionals[0].clone());
+    assert_eq!(
+        keys.verify(&encode(&in_answers), NOW),
+        Err(TsigError::Malformed)
+    );
+}
+
+#[test]
+fn test_truncated_mac() {
+    let full = key("hmac-sha256");
+    let truncated = full.clone().with_mac_len(16);
+    assert_eq!(truncated.mac_len(), 16);
+    // Clamped to 10 bytes or half of the MAC.
+    assert_eq!(full.clone().with_mac_len(4).mac_len(), 16);
+    assert_eq!(key("hmac-sha512").with_mac_len(4).mac_len(), 32);
+    assert_eq!(full.clone().with_mac_len(100).mac_len(), 32);
+
+    let (request, mac) = signed_query(&truncated, NOW);
+    assert_eq!(mac.len(), 16);
+    let buf = encode(&request);
+
+    // Accepted by policy, or rejected as too short
+    assert!(tsig::verify(&buf, &truncated, NOW, None).is_ok());
+    assert_eq!(
+        tsig::verify(&buf, &full, NOW, None),
+        Err(TsigError::BadTrunc)
+    );
+
+    // Truncated below the shortest length allowed
+    let mut msg = request.clone();
+    if let RData::TSIG { mac, .. } = &mut msg.additionals[0].data {
+        mac.truncate(8);
+    }
+    assert_eq!(
+        tsig::verify(&encode(&msg), &truncated, NOW, None),
+        Err(TsigError::Malformed)
+    );
+}
+
+#[test]
+fn test_reject() {
+    let key = key("hmac-sha256");
+    let keys = KeyStore::from_iter([key.clone()]);
+    let (request, request_mac) = signed_query(&key, NOW);
+
+    // BADSIG and BADKEY errors are unsigned.
+    for error in [TsigError::BadSig, TsigError::BadKey] {
+        let mut response = MessageBuilder::reply_to(&request).build();
+        tsig::reject(&mut response, &request, error, &keys, NOW);
+
+        assert_eq!(response.response_code(), ResponseCode::NotAuthoritative);
+        let RData::TSIG {
+            mac, error: code, ..
+        } = tsig_data(&response)
+        else {
+            panic!("Missing TSIG record");
+        };
+        assert!(mac.is_empty());
+        assert_eq!(*code, error.code());
+        assert_eq!(
+            tsig::verify(&encode(&response), &key, NOW, Some(&request_mac)),
+            Err(error)
+        );
+    }
+    assert_eq!(TsigError::BadSig.code(), ResponseCode::from(16));
+
+    // BADTIME error is signed, with server time.
+    let mut response = MessageBuilder::reply_to(&request).build();
+    tsig::reject(
+        &mut response,
+        &request,
+        TsigError::BadTime,
+        &keys,
+        NOW + 1000,
+    );
+    let RData::TSIG {
+        time_signed,
+        other_data,
+        error,
+        ..
+    } = tsig_data(&response)
+    else {
+        panic!("Missing TSIG record");
+    };
+    assert_eq!(*time_signed, NOW);
+    assert_eq!(*other_data, (NOW + 1000).to_be_bytes()[2..].to_vec());
+    assert_eq!(*error, ResponseCode::BadTime);
+    assert_eq!(
+        tsig::verify(&encode(&response), &key, NOW, Some(&request_mac)),
+        Err(TsigError::BadTime)
+    );
+    assert!(tsig_data(&response)
+        .to_string()
+        .ends_with(" BADTIME 6 AABlU/To"));
+
+    // Malformed TSIG gets a FORMERR.
+    let mut response = MessageBuilder::reply_to(&request).build();
+    tsig::reject(&mut response, &request, TsigError::Malformed, &keys, NOW);
+    assert_eq!(response.response_code(), ResponseCode::FormatError);
+    assert!(response.additionals.is_empty());
+}
+
+#[test]
+fn test_key_from_str() {
+    let key: TsigKey = format!("test-key.:{SECRET}").parse().unwrap();
+    assert_eq!(key.algorithm(), TsigAlgorithm::HmacSha256);
+    assert_eq!(key.name(), &"test-key".parse::<DomainName>().unwrap());
+    assert_eq!(key, self::key("hmac-sha256"));
+
+    for input in [
+        "test-key",
+        "hmac-md5:test-key:MDEy",
+        "test-key:not base64!",
+        "test-key:",
+        "a:b:c:d",
+    ] {
+        assert!(input.parse::<TsigKey>().is_err(), "{input}");
+    }
+}
+
+/// Serve answers on a local UDP port, signed for clients with a key of
+/// `keys` (`None` to ignore signatures).
+fn serve(keys: Option<KeyStore>) -> SocketAddr {
+    common::serve(move |query, input, _| {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        let mut response = MessageBuilder::reply_to(query)
+            .answer("example.com. 60 IN A 192.0.2.1".parse().unwrap())
+            .build();
+        if let Some(keys) = &keys {
+            match keys.verify(input, now) {
+                Ok(Some(verified)) => {
+                    verified.sign_response(&mut response, now);
+                }
+                Ok(None) => {}
+                Err(e) => tsig::reject(&mut response, query, e, keys, now),
+            }
+        }
+        Some(response)
+    })
+}
+
+#[test]
+fn test_client_signed_queries() {
+    let addr = serve(Some(KeyStore::from_iter([key("hmac-sha512")])));
+    let question = QuestionSection::new_a("example.com").unwrap();
+
+    let mut client = DnsClient::connect("127.0.0.1:0", addr).unwrap();
+    client.set_tsig_key(Some(key("hmac-sha512")));
+    let answer = client.query(&question).unwrap();
+    assert_eq!(answer.data, RData::A("192.0.2.1".parse().unwrap()));
+
+    // Server does not know the key.
+    let unknown: TsigKey = format!("hmac-sha512:other-key:{SECRET}").parse().unwrap();
+    client.set_tsig_key(Some(unknown));
+    assert!(matches!(
+        client.query(&question),
+        Err(DnsError::Tsig(TsigError::BadKey))
+    ));
+
+    // Wrong secret
+    client.set_tsig_key(Some(TsigKey::new(
+        "test-key".parse().unwrap(),
+        TsigAlgorithm::HmacSha512,
+        b"wrong".to_vec(),
+    )));
+    assert!(matches!(
+        client.query(&question),
+        Err(DnsError::Tsig(TsigError::BadSig))
+    ));
+
+    // Unsigned queries are still answered.
+    client.set_tsig_key(None);
+    assert!(client.query(&question).is_ok());
+
+    // Unsigned response to a signed query
+    let mut client = DnsClient::connect("127.0.0.1:0", serve(None)).unwrap();
+    client.set_tsig_key(Some(key("hmac-sha512")));
+    assert!(matches!(
+        client.query(&question),
+        Err(DnsError::Tsig(TsigError::Unsigned))
+    ));
+}